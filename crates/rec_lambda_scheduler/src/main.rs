@@ -0,0 +1,32 @@
+use lambda_http::lambda_runtime::{self, handler_fn, Context, Error};
+use receptionist::{run_due_schedules, SlackStateWorkaround};
+use serde_json::Value;
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+pub static SLACK_CONFIG: OnceCell<SlackStateWorkaround> = OnceCell::const_new();
+pub async fn get_or_init_slack_state() -> &'static SlackStateWorkaround {
+    SLACK_CONFIG
+        .get_or_init(|| async { SlackStateWorkaround::new_from_env() })
+        .await
+}
+
+#[tokio::main]
+async fn main() -> Result<(), lambda_runtime::Error> {
+    // You can view the logs emitted by your app in Amazon CloudWatch.
+    tracing_subscriber::fmt::init();
+    debug!("logger has been set up");
+
+    lambda_runtime::run(handler_fn(scheduler_lambda)).await?;
+
+    Ok(())
+}
+
+/// Invoked by an EventBridge rule every minute, the event payload itself is unused
+async fn scheduler_lambda(_event: Value, _ctx: Context) -> Result<(), Error> {
+    let slack_state = get_or_init_slack_state().await;
+
+    run_due_schedules(slack_state, chrono::Utc::now()).await?;
+
+    Ok(())
+}
@@ -10,13 +10,16 @@ use axum::{
 use receptionist::get_or_init_mem_db;
 
 use receptionist::{
-    axum_handler_handle_slack_commands_api, axum_handler_slack_events_api,
-    axum_handler_slack_interactions_api, config::get_or_init_app_config, setup_slack,
+    axum_handler_handle_slack_commands_api, axum_handler_inbound_webhook,
+    axum_handler_pagerduty_webhook, axum_handler_slack_events_api,
+    axum_handler_slack_interactions_api, axum_handler_slack_options_load,
+    config::get_or_init_app_config, run_due_schedules, setup_slack,
     verification::SlackRequestVerifier, ServiceBuilder, SlackEventSignatureVerifier,
+    SCHEDULER_TICK_SECS,
 };
-use std::env;
+use std::{env, time::Duration};
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{error, info};
 
 #[tokio::main]
 async fn main() {
@@ -33,11 +36,24 @@ async fn main() {
 
     let slack_arc = setup_slack();
 
+    // run scheduled responses in the background, last run times are kept in the db so restarts don't repeat them
+    let scheduler_slack = slack_arc.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(SCHEDULER_TICK_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = run_due_schedules(&scheduler_slack, chrono::Utc::now()).await {
+                error!("Error running scheduled responses: {}", e);
+            }
+        }
+    });
+
     // group slack routes into a separate Router so we can use basepath `/slack` & apply slack auth middleware
     let slack_api_router = Router::new()
         .route("/events", post(axum_handler_slack_events_api))
         .route("/interaction", post(axum_handler_slack_interactions_api))
         .route("/commands", post(axum_handler_handle_slack_commands_api))
+        .route("/options", post(axum_handler_slack_options_load))
         .layer(ServiceBuilder::new().layer_fn(|inner| {
             SlackRequestVerifier {
                 inner,
@@ -50,6 +66,9 @@ async fn main() {
 
     let app = Router::new()
         .nest("/slack", slack_api_router)
+        // webhooks are verified per Response with their own secret, not Slack's signing secret
+        .route("/hooks/:response_id", post(axum_handler_inbound_webhook))
+        .route("/pagerduty/webhook", post(axum_handler_pagerduty_webhook))
         .route("/", get(|| async { "Hello, World!" }))
         .layer(TraceLayer::new_for_http())
         .layer(AddExtensionLayer::new(slack_arc));
@@ -1,7 +1,11 @@
-use crate::PagerDuty;
+use crate::{
+    new_https_client, HttpsClient, OncallFallback, OncallProvider, OncallProviderKind, Opsgenie,
+    PagerDuty, PagerDutyEvents, RotationOncallProvider,
+};
 use arguably::ArgParser;
 use aws_sdk_dynamodb::Credentials;
 use dotenv::dotenv;
+use std::time::Duration;
 use tokio::sync::OnceCell;
 
 pub static APP_CONFIG: OnceCell<ReceptionistAppConfig> = OnceCell::const_new();
@@ -16,6 +20,18 @@ const ENV_FLAG_AWS_ENDPOINT_URL: &str = "AWS_ENDPOINT_URL";
 const ENV_FLAG_AWS_FAKE_CREDS: &str = "AWS_FAKE_CREDS";
 pub const ENV_OPTION_PD_KEY: &str = "PAGERDUTY_TOKEN";
 pub const ENV_OPTION_PD_BASE_URL: &str = "PAGERDUTY_BASE_URL";
+pub const ENV_OPTION_PD_WEBHOOK_SECRET: &str = "PAGERDUTY_WEBHOOK_SECRET";
+pub const ENV_OPTION_PD_EVENTS_BASE_URL: &str = "PAGERDUTY_EVENTS_BASE_URL";
+pub const ENV_OPTION_OPSGENIE_KEY: &str = "OPSGENIE_API_KEY";
+pub const ENV_OPTION_OPSGENIE_BASE_URL: &str = "OPSGENIE_BASE_URL";
+pub const ENV_OPTION_ONCALL_FALLBACK_NEXT_LEVEL: &str = "ONCALL_FALLBACK_NEXT_LEVEL";
+pub const ENV_OPTION_ONCALL_FALLBACK_USERGROUP: &str = "ONCALL_FALLBACK_USERGROUP";
+pub const ENV_OPTION_ONCALL_FALLBACK_PROFILE: &str = "ONCALL_FALLBACK_PROFILE";
+pub const ENV_OPTION_ONCALL_EMAIL_DOMAIN_REWRITES: &str = "ONCALL_EMAIL_DOMAIN_REWRITES";
+pub const ENV_OPTION_ONCALL_CACHE_TTL: &str = "ONCALL_CACHE_TTL_SECS";
+pub const ENV_OPTION_SLACK_USER_CACHE_TTL: &str = "SLACK_USER_CACHE_TTL_SECS";
+const DEFAULT_ONCALL_CACHE_TTL_SECS: u64 = 60;
+const DEFAULT_SLACK_USER_CACHE_TTL_SECS: u64 = 60 * 60;
 
 #[derive(Clone)]
 /// can load a .env file to the environment and parse cli args to build the app config
@@ -24,6 +40,20 @@ pub struct ReceptionistAppConfig {
     pub aws_fake_creds: Option<Credentials>,
     /// if no pagerduty configuration, remove pagerduty actions from Response Creator modal
     pub pagerduty_config: Option<PagerDuty>,
+    /// signing secret of the PagerDuty v3 webhook subscription, incident events are rejected without it
+    pub pagerduty_webhook_secret: Option<String>,
+    /// Events API v2 client, authenticated by each action's routing key so it needs no token
+    pub pagerduty_events: PagerDutyEvents,
+    /// oncall actions using Opsgenie fail to find a user without it
+    pub opsgenie_config: Option<Opsgenie>,
+    /// what oncall actions mention when the oncall user isn't found in Slack
+    pub oncall_fallback: OncallFallback,
+    /// how long oncall results are reused, 0 disables the cache
+    pub oncall_cache_ttl: Duration,
+    /// how long email to Slack user lookups are reused, 0 disables the cache
+    pub slack_user_cache_ttl: Duration,
+    /// shared by PagerDuty and outgoing webhook actions
+    pub http_client: HttpsClient,
 }
 
 impl ReceptionistAppConfig {
@@ -33,6 +63,16 @@ impl ReceptionistAppConfig {
     /// AWS_ENDPOINT_URL
     /// PAGERDUTY_TOKEN
     /// PAGERDUTY_BASE_URL
+    /// PAGERDUTY_WEBHOOK_SECRET
+    /// PAGERDUTY_EVENTS_BASE_URL
+    /// OPSGENIE_API_KEY
+    /// OPSGENIE_BASE_URL
+    /// ONCALL_FALLBACK_NEXT_LEVEL (`false` to disable, enabled by default)
+    /// ONCALL_FALLBACK_USERGROUP
+    /// ONCALL_FALLBACK_PROFILE (`false` to disable, enabled by default)
+    /// ONCALL_EMAIL_DOMAIN_REWRITES
+    /// ONCALL_CACHE_TTL_SECS (defaults to 60)
+    /// SLACK_USER_CACHE_TTL_SECS (defaults to 3600)
     ///
     /// Supported .env boolean flags:
     ///
@@ -66,17 +106,75 @@ the environment variable `{ENV_FLAG_AWS_ENDPOINT_URL}`\n Fake AWS Creds can auto
                 None
             };
 
+        let http_client = new_https_client();
+
         let pagerduty_config = std::env::var(ENV_OPTION_PD_KEY).map_or(None, |pd_key| {
-            Some(PagerDuty::new(
+            Some(PagerDuty::with_client(
                 pd_key,
                 std::env::var(ENV_OPTION_PD_BASE_URL).ok(),
+                http_client.clone(),
+            ))
+        });
+
+        let pagerduty_webhook_secret = std::env::var(ENV_OPTION_PD_WEBHOOK_SECRET).ok();
+
+        let pagerduty_events = PagerDutyEvents::with_client(
+            std::env::var(ENV_OPTION_PD_EVENTS_BASE_URL).ok(),
+            http_client.clone(),
+        );
+
+        let opsgenie_config = std::env::var(ENV_OPTION_OPSGENIE_KEY).map_or(None, |api_key| {
+            Some(Opsgenie::with_client(
+                api_key,
+                std::env::var(ENV_OPTION_OPSGENIE_BASE_URL).ok(),
+                http_client.clone(),
             ))
         });
 
+        let oncall_fallback = OncallFallback {
+            next_escalation_level: env_enabled_by_default(ENV_OPTION_ONCALL_FALLBACK_NEXT_LEVEL),
+            usergroup_id: std::env::var(ENV_OPTION_ONCALL_FALLBACK_USERGROUP)
+                .ok()
+                .filter(|usergroup_id| !usergroup_id.trim().is_empty()),
+            name_and_profile: env_enabled_by_default(ENV_OPTION_ONCALL_FALLBACK_PROFILE),
+            email_domain_rewrites: std::env::var(ENV_OPTION_ONCALL_EMAIL_DOMAIN_REWRITES)
+                .map(|rewrites| OncallFallback::parse_email_domain_rewrites(&rewrites))
+                .unwrap_or_default(),
+        };
+
+        let oncall_cache_ttl =
+            env_duration_secs(ENV_OPTION_ONCALL_CACHE_TTL, DEFAULT_ONCALL_CACHE_TTL_SECS);
+        let slack_user_cache_ttl = env_duration_secs(
+            ENV_OPTION_SLACK_USER_CACHE_TTL,
+            DEFAULT_SLACK_USER_CACHE_TTL_SECS,
+        );
+
         Self {
             aws_override_url,
             aws_fake_creds,
             pagerduty_config,
+            pagerduty_webhook_secret,
+            pagerduty_events,
+            opsgenie_config,
+            oncall_fallback,
+            oncall_cache_ttl,
+            slack_user_cache_ttl,
+            http_client,
+        }
+    }
+
+    /// `None` if the provider isn't configured
+    pub fn oncall_provider(&self, kind: OncallProviderKind) -> Option<&dyn OncallProvider> {
+        match kind {
+            OncallProviderKind::PagerDuty => self
+                .pagerduty_config
+                .as_ref()
+                .map(|pd| pd as &dyn OncallProvider),
+            OncallProviderKind::Opsgenie => self
+                .opsgenie_config
+                .as_ref()
+                .map(|opsgenie| opsgenie as &dyn OncallProvider),
+            OncallProviderKind::Rotation => Some(&RotationOncallProvider),
         }
     }
 
@@ -86,6 +184,21 @@ the environment variable `{ENV_FLAG_AWS_ENDPOINT_URL}`\n Fake AWS Creds can auto
     }
 }
 
+/// Only an explicit `false` (any case) disables the option
+fn env_enabled_by_default(key: &str) -> bool {
+    std::env::var(key).map_or(true, |value| !value.trim().eq_ignore_ascii_case("false"))
+}
+
+/// Seconds from the environment, the default is used when missing or not a number
+fn env_duration_secs(key: &str, default_secs: u64) -> Duration {
+    Duration::from_secs(
+        std::env::var(key)
+            .ok()
+            .and_then(|secs| secs.trim().parse().ok())
+            .unwrap_or(default_secs),
+    )
+}
+
 impl Default for ReceptionistAppConfig {
     fn default() -> Self {
         Self::new()
@@ -1,24 +1,51 @@
+use super::DeliveredEvent;
 use crate::config::get_or_init_app_config;
 use crate::ReceptionistListener;
 use crate::ReceptionistResponse;
+use crate::Rotation;
 use anyhow::{anyhow, bail, Result};
 use aws_sdk_dynamodb::model::{
-    AttributeValue, DeleteRequest, KeysAndAttributes, PutRequest, WriteRequest,
+    AttributeValue, Delete, DeleteRequest, KeysAndAttributes, Put, PutRequest, TransactWriteItem,
+    WriteRequest,
+};
+use aws_sdk_dynamodb::{
+    error::{PutItemErrorKind, UpdateItemErrorKind},
+    Client, Config, Endpoint, Region, SdkError,
 };
-use aws_sdk_dynamodb::output::BatchWriteItemOutput;
-use aws_sdk_dynamodb::{Client, Config, Endpoint, Region};
 use aws_types::Credentials;
 use serde::{Deserialize, Serialize};
 use serde_dynamo::aws_sdk_dynamodb_0_4::{from_item, from_items, to_attribute_value, to_item};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::str::FromStr;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::OnceCell;
+use tracing::warn;
 // Starter examples: https://github.com/awslabs/aws-sdk-rust/tree/main/examples/dynamodb/src/bin
 
 pub const TABLE_NAME: &str = "receptionist_bot";
 pub const INDEX_NAME: &str = "InvertedIndex";
+/// Max number of items DynamoDB accepts in a single batch write
+const MAX_BATCH_WRITE_ITEMS: usize = 25;
+/// Max number of keys DynamoDB accepts in a single batch get
+const MAX_BATCH_GET_KEYS: usize = 100;
+/// Max number of items DynamoDB accepts in a single transaction
+const MAX_TRANSACT_ITEMS: usize = 25;
+/// Batches DynamoDB only partly processed (when throttled) are resent this many times
+const MAX_UNPROCESSED_RETRIES: u32 = 5;
+/// Wait before resending the unprocessed part of a batch, doubled after each retry
+const UNPROCESSED_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+/// Workspace-wide listeners are read for every message,
+/// so their Responses are spread over this many partitions instead of sharing one
+const WILDCARD_PKEY_SHARDS: u32 = 4;
+/// Fired response records are only needed while a message is likely to be edited
+const FIRED_RESPONSE_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+/// Incident threads are kept long enough for slow resolutions
+const INCIDENT_THREAD_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+/// Delivered events are only remembered while their sender may still redeliver them
+const DELIVERED_EVENT_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+/// Oncall cache clears only matter while something cached before them could still be in use
+const ONCALL_CACHE_INVALIDATION_TTL: Duration = Duration::from_secs(60 * 60 * 24);
 
 pub static DYNAMO_CLIENT: OnceCell<aws_sdk_dynamodb::Client> = OnceCell::const_new();
 pub async fn get_or_init_dynamo_client() -> &'static aws_sdk_dynamodb::Client {
@@ -64,9 +91,7 @@ pub async fn setup_dynamo_client(
     }
 }
 
-pub async fn create_response(rec_response: ReceptionistResponse) -> Result<BatchWriteItemOutput> {
-    let client = get_or_init_dynamo_client().await;
-
+pub async fn create_response(rec_response: ReceptionistResponse) -> Result<()> {
     let table_items_before_formatting = convert_response_to_table_items(rec_response)?;
 
     // using a map iterator is breaking here when ? operator used :shrug:
@@ -75,46 +100,187 @@ pub async fn create_response(rec_response: ReceptionistResponse) -> Result<Batch
         all_items.push(to_item(item)?)
     }
 
-    let output = client
-        .batch_write_item()
-        .request_items(
-            TABLE_NAME.to_string(),
-            all_items
-                .into_iter()
-                .map(|item| {
-                    WriteRequest::builder()
-                        .put_request(PutRequest::builder().set_item(Some(item)).build())
-                        .build()
-                })
-                .collect::<Vec<WriteRequest>>(),
+    batch_write_all(
+        all_items
+            .into_iter()
+            .map(|item| {
+                WriteRequest::builder()
+                    .put_request(PutRequest::builder().set_item(Some(item)).build())
+                    .build()
+            })
+            .collect(),
+    )
+    .await
+}
+
+/// Send write requests in batches small enough for DynamoDB to accept.
+/// A response listening to many channels can need more items than a single batch holds
+async fn batch_write_all(write_requests: Vec<WriteRequest>) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    for chunk in write_requests.chunks(MAX_BATCH_WRITE_ITEMS) {
+        let mut pending = chunk.to_vec();
+        let mut retries = 0;
+
+        while !pending.is_empty() {
+            let result = client
+                .batch_write_item()
+                .request_items(TABLE_NAME.to_string(), pending)
+                .send()
+                .await?;
+
+            pending = result
+                .unprocessed_items
+                .and_then(|mut unprocessed| unprocessed.remove(TABLE_NAME))
+                .unwrap_or_default();
+
+            if !pending.is_empty() {
+                retries += 1;
+                wait_to_resend_unprocessed(retries, pending.len()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Get items by key in batches small enough for DynamoDB to accept
+async fn batch_get_all(
+    keys: Vec<HashMap<String, AttributeValue>>,
+) -> Result<Vec<HashMap<String, AttributeValue>>> {
+    let client = get_or_init_dynamo_client().await;
+    let mut items = Vec::new();
+
+    for chunk in keys.chunks(MAX_BATCH_GET_KEYS) {
+        let mut pending = chunk.to_vec();
+        let mut retries = 0;
+
+        while !pending.is_empty() {
+            let result = client
+                .batch_get_item()
+                .request_items(
+                    TABLE_NAME,
+                    KeysAndAttributes::builder().set_keys(Some(pending)).build(),
+                )
+                .send()
+                .await?;
+
+            if let Some(found) = result.responses.and_then(|mut r| r.remove(TABLE_NAME)) {
+                items.extend(found);
+            }
+
+            pending = result
+                .unprocessed_keys
+                .and_then(|mut unprocessed| unprocessed.remove(TABLE_NAME))
+                .and_then(|keys_and_attributes| keys_and_attributes.keys)
+                .unwrap_or_default();
+
+            if !pending.is_empty() {
+                retries += 1;
+                wait_to_resend_unprocessed(retries, pending.len()).await?;
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+/// DynamoDB leaves part of a batch unprocessed when it is throttled, back off before resending it
+async fn wait_to_resend_unprocessed(retries: u32, unprocessed_count: usize) -> Result<()> {
+    if retries > MAX_UNPROCESSED_RETRIES {
+        bail!(
+            "DynamoDB left {} batch items unprocessed after {} retries",
+            unprocessed_count,
+            MAX_UNPROCESSED_RETRIES
         )
-        .send()
-        .await?;
+    }
 
-    Ok(output)
+    tokio::time::sleep(UNPROCESSED_RETRY_BACKOFF * 2u32.pow(retries - 1)).await;
+    Ok(())
+}
+
+/// Every item in a partition, following the query's pages
+async fn query_partition(pk: String) -> Result<Vec<HashMap<String, AttributeValue>>> {
+    let client = get_or_init_dynamo_client().await;
+    let mut items = Vec::new();
+    let mut exclusive_start_key = None;
+
+    loop {
+        let result = client
+            .query()
+            .table_name(TABLE_NAME)
+            .key_condition_expression("pk = :pk")
+            .expression_attribute_values(":pk", to_attribute_value(pk.to_owned())?)
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await?;
+
+        items.extend(result.items.unwrap_or_default());
+
+        exclusive_start_key = result.last_evaluated_key;
+        if exclusive_start_key.is_none() {
+            return Ok(items);
+        }
+    }
 }
 
 pub async fn get_responses_for_listener(
     listener: ReceptionistListener,
 ) -> Result<Vec<ReceptionistResponse>> {
-    let client = get_or_init_dynamo_client().await;
+    let mut all_responses = Vec::new();
 
-    let result = client
-        .query()
-        .table_name(TABLE_NAME)
-        .key_condition_expression("pk = :listener_str")
-        .expression_attribute_values(
-            ":listener_str",
-            to_attribute_value(ListenerPKey::from(listener).to_string())?,
-        )
-        .send()
-        .await?;
+    for pkey in ListenerPKey::lookup_keys(listener) {
+        all_responses.extend(get_responses_for_pkey(pkey).await?);
+    }
 
-    if result.count() == 0 {
+    Ok(all_responses)
+}
+
+async fn get_responses_for_pkey(pkey: ListenerPKey) -> Result<Vec<ReceptionistResponse>> {
+    let items = query_partition(pkey.to_string()).await?;
+
+    if items.is_empty() {
         return Ok(Vec::new());
     }
 
-    Ok(from_items(result.items().unwrap().to_owned())?)
+    let mut responses = Vec::new();
+    let mut index_keys = Vec::new();
+
+    for item in from_items::<ReceptionistTableItem>(items)? {
+        match item {
+            ReceptionistTableItem::Response {
+                receptionist_response,
+                ..
+            } => responses.push(receptionist_response),
+            ReceptionistTableItem::ChannelIndex {
+                sk, listener_pk, ..
+            } => index_keys.push(
+                [
+                    ("pk".to_owned(), to_attribute_value(listener_pk)?),
+                    ("sk".to_owned(), to_attribute_value(sk)?),
+                ]
+                .into_iter()
+                .collect::<HashMap<String, AttributeValue>>(),
+            ),
+            ReceptionistTableItem::Collaborator { .. }
+            | ReceptionistTableItem::FiredResponse { .. }
+            | ReceptionistTableItem::DeliveredEvent { .. }
+            | ReceptionistTableItem::WelcomedMember { .. }
+            | ReceptionistTableItem::ScheduleState { .. }
+            | ReceptionistTableItem::IncidentThread { .. }
+            | ReceptionistTableItem::WebhookSecret { .. }
+            | ReceptionistTableItem::OncallCacheInvalidation { .. }
+            | ReceptionistTableItem::Rotation { .. } => (),
+        }
+    }
+
+    if !index_keys.is_empty() {
+        responses.extend(from_items::<ReceptionistResponse>(
+            batch_get_all(index_keys).await?,
+        )?);
+    }
+
+    Ok(responses)
 }
 
 pub async fn get_response_by_id(response_id: &str) -> Result<ReceptionistResponse> {
@@ -143,7 +309,16 @@ pub async fn get_response_by_id(response_id: &str) -> Result<ReceptionistRespons
                     receptionist_response,
                     ..
                 } => all_responses.push(receptionist_response),
-                ReceptionistTableItem::Collaborator { .. } => (),
+                ReceptionistTableItem::Collaborator { .. }
+                | ReceptionistTableItem::ChannelIndex { .. }
+                | ReceptionistTableItem::FiredResponse { .. }
+                | ReceptionistTableItem::DeliveredEvent { .. }
+                | ReceptionistTableItem::WelcomedMember { .. }
+                | ReceptionistTableItem::ScheduleState { .. }
+                | ReceptionistTableItem::IncidentThread { .. }
+                | ReceptionistTableItem::WebhookSecret { .. }
+                | ReceptionistTableItem::OncallCacheInvalidation { .. }
+                | ReceptionistTableItem::Rotation { .. } => (),
             }
         }
 
@@ -155,49 +330,137 @@ pub async fn get_response_by_id(response_id: &str) -> Result<ReceptionistRespons
     }
 }
 
-pub async fn delete_response(rec_response: ReceptionistResponse) -> Result<BatchWriteItemOutput> {
-    let client = get_or_init_dynamo_client().await;
-
+pub async fn delete_response(rec_response: ReceptionistResponse) -> Result<()> {
+    let response_id = rec_response.id.clone();
     let table_items_before_formatting = convert_response_to_table_items(rec_response)?;
 
-    let output = client
-        .batch_write_item()
-        .request_items(
-            TABLE_NAME.to_string(),
-            table_items_before_formatting
+    batch_write_all(
+        table_items_before_formatting
+            .into_iter()
+            .map(|item| {
+                let (pk, sk) = item.get_pk_sk_strings();
+                WriteRequest::builder()
+                    .delete_request(
+                        DeleteRequest::builder()
+                            .key("pk", to_attribute_value(pk).unwrap())
+                            .key("sk", to_attribute_value(sk).unwrap())
+                            .build(),
+                    )
+                    .build()
+            })
+            .collect(),
+    )
+    .await?;
+
+    delete_webhook_secret(&response_id).await
+}
+
+/// Items are keyed by listener and channel, so the previously saved items the new version
+/// doesn't have are deleted in the same transaction that writes the new ones
+pub async fn update_response(response: ReceptionistResponse) -> Result<()> {
+    let previous_items = match get_response_by_id(&response.id).await {
+        Ok(previous_response) => convert_response_to_table_items(previous_response)?,
+        Err(_) => Vec::new(),
+    };
+    let new_items = convert_response_to_table_items(response)?;
+
+    let new_keys: HashSet<(String, String)> = new_items
+        .iter()
+        .map(|item| item.get_pk_sk_strings())
+        .collect();
+    let stale_keys: Vec<(String, String)> = previous_items
+        .iter()
+        .map(|item| item.get_pk_sk_strings())
+        .filter(|key| !new_keys.contains(key))
+        .collect();
+
+    let mut new_table_items: Vec<HashMap<String, AttributeValue>> = Vec::new();
+    for item in new_items {
+        new_table_items.push(to_item(item)?)
+    }
+
+    if new_table_items.len() + stale_keys.len() > MAX_TRANSACT_ITEMS {
+        // too many channels for a single transaction. New items are written first,
+        // so a failure leaves stale channel indexes behind rather than a missing Response
+        warn!("Response has too many items to update in one transaction");
+        batch_write_all(
+            new_table_items
                 .into_iter()
                 .map(|item| {
-                    let (pk, sk) = item.get_pk_sk_strings();
                     WriteRequest::builder()
-                        // .put_request(PutRequest::builder().set_item(Some(item)).build())
-                        .delete_request(
-                            DeleteRequest::builder()
-                                .key("pk", to_attribute_value(pk).unwrap())
-                                .key("sk", to_attribute_value(sk).unwrap())
-                                .build(),
-                        )
+                        .put_request(PutRequest::builder().set_item(Some(item)).build())
                         .build()
                 })
-                .collect::<Vec<WriteRequest>>(),
+                .collect(),
         )
-        .send()
         .await?;
 
-    Ok(output)
-}
+        let mut delete_requests = Vec::new();
+        for (pk, sk) in stale_keys {
+            delete_requests.push(
+                WriteRequest::builder()
+                    .delete_request(
+                        DeleteRequest::builder()
+                            .key("pk", to_attribute_value(pk)?)
+                            .key("sk", to_attribute_value(sk)?)
+                            .build(),
+                    )
+                    .build(),
+            )
+        }
 
-pub async fn update_response(response: ReceptionistResponse) -> Result<BatchWriteItemOutput> {
-    create_response(response).await
+        return batch_write_all(delete_requests).await;
+    }
+
+    let mut transact_items = Vec::new();
+    for (pk, sk) in stale_keys {
+        transact_items.push(
+            TransactWriteItem::builder()
+                .delete(
+                    Delete::builder()
+                        .table_name(TABLE_NAME)
+                        .key("pk", to_attribute_value(pk)?)
+                        .key("sk", to_attribute_value(sk)?)
+                        .build(),
+                )
+                .build(),
+        )
+    }
+    for item in new_table_items {
+        transact_items.push(
+            TransactWriteItem::builder()
+                .put(
+                    Put::builder()
+                        .table_name(TABLE_NAME)
+                        .set_item(Some(item))
+                        .build(),
+                )
+                .build(),
+        )
+    }
+
+    get_or_init_dynamo_client()
+        .await
+        .transact_write_items()
+        .set_transact_items(Some(transact_items))
+        .send()
+        .await?;
+
+    Ok(())
 }
 
-async fn get_collaborator_items(user_id: &str) -> Result<Vec<ReceptionistTableItem>> {
+/// IDs of the Responses that already ran for a message
+pub async fn get_fired_response_ids(channel_id: &str, message_ts: &str) -> Result<Vec<String>> {
     let client = get_or_init_dynamo_client().await;
 
     let result = client
         .query()
         .table_name(TABLE_NAME)
-        .key_condition_expression("pk = :collaborator_id")
-        .expression_attribute_values(":collaborator_id", to_attribute_value(user_id.to_string())?)
+        .key_condition_expression("pk = :message_key")
+        .expression_attribute_values(
+            ":message_key",
+            to_attribute_value(fired_response_pk(channel_id, message_ts))?,
+        )
         .send()
         .await?;
 
@@ -205,8 +468,535 @@ async fn get_collaborator_items(user_id: &str) -> Result<Vec<ReceptionistTableIt
         return Ok(Vec::new());
     }
 
+    Ok(
+        from_items::<ReceptionistTableItem>(result.items().unwrap().to_owned())?
+            .into_iter()
+            .filter_map(|item| match item {
+                ReceptionistTableItem::FiredResponse { response_id, .. } => Some(response_id),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+/// Remember which Responses ran for a message so that edits don't run them again
+pub async fn record_fired_responses(
+    channel_id: &str,
+    message_ts: &str,
+    response_ids: Vec<String>,
+) -> Result<()> {
+    let expires_at = (SystemTime::now() + FIRED_RESPONSE_TTL)
+        .duration_since(UNIX_EPOCH)?
+        .as_secs();
+
+    let mut all_items: Vec<HashMap<String, AttributeValue>> = Vec::new();
+    for response_id in response_ids {
+        all_items.push(to_item(ReceptionistTableItem::FiredResponse {
+            pk: fired_response_pk(channel_id, message_ts),
+            sk: format!("fired/{response_id}"),
+            response_id,
+            expires_at,
+        })?)
+    }
+
+    batch_write_all(
+        all_items
+            .into_iter()
+            .map(|item| {
+                WriteRequest::builder()
+                    .put_request(PutRequest::builder().set_item(Some(item)).build())
+                    .build()
+            })
+            .collect(),
+    )
+    .await
+}
+
+fn fired_response_pk(channel_id: &str, message_ts: &str) -> String {
+    format!("fired-responses/{channel_id}/{message_ts}")
+}
+
+/// Atomically remember that an event was delivered. Returns false when it was delivered before,
+/// so a redelivered event is only handled once
+pub async fn claim_delivered_event(event: &DeliveredEvent) -> Result<bool> {
+    let client = get_or_init_dynamo_client().await;
+
+    let expires_at = (SystemTime::now() + DELIVERED_EVENT_TTL)
+        .duration_since(UNIX_EPOCH)?
+        .as_secs();
+
+    let result = client
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(to_item(ReceptionistTableItem::DeliveredEvent {
+            pk: event.to_string(),
+            sk: DELIVERED_EVENT_SK.to_string(),
+            expires_at,
+        })?))
+        .condition_expression("attribute_not_exists(pk)")
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(true),
+        Err(SdkError::ServiceError { err, .. })
+            if matches!(
+                err.kind,
+                PutItemErrorKind::ConditionalCheckFailedException(_)
+            ) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+const DELIVERED_EVENT_SK: &str = "delivered";
+
+/// Whether a Response that runs once per user already ran for this user
+pub async fn has_welcomed_member(response_id: &str, user_id: &str) -> Result<bool> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .get_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(welcomed_member_pk(response_id))?)
+        .key("sk", to_attribute_value(user_id.to_string())?)
+        .send()
+        .await?;
+
+    Ok(result.item().is_some())
+}
+
+pub async fn record_welcomed_member(response_id: &str, user_id: &str) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    client
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(to_item(ReceptionistTableItem::WelcomedMember {
+            pk: welcomed_member_pk(response_id),
+            sk: user_id.to_string(),
+        })?))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+fn welcomed_member_pk(response_id: &str) -> String {
+    format!("welcomed-members/{response_id}")
+}
+
+/// Unix timestamp of the last scheduled time a Response finished running for
+pub async fn get_last_scheduled_run(response_id: &str) -> Result<Option<i64>> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .get_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(schedule_state_pk(response_id))?)
+        .key("sk", to_attribute_value(SCHEDULE_STATE_SK.to_string())?)
+        .send()
+        .await?;
+
+    match result.item {
+        Some(item) => match from_item(item)? {
+            ReceptionistTableItem::ScheduleState { last_run, .. } => Ok(last_run),
+            _ => bail!("Unexpected item type for schedule state of {}", response_id),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Atomically claim a scheduled time before running it. Returns false when that time (or a later one)
+/// already finished or is claimed by another scheduler instance.
+/// Claims taken before `stale_before` never finished and can be claimed again
+pub async fn claim_scheduled_run(
+    response_id: &str,
+    run_at: i64,
+    claimed_at: i64,
+    stale_before: i64,
+) -> Result<bool> {
+    let client = get_or_init_dynamo_client().await;
+
+    // the first claim creates the item, so it needs the serde tag of ScheduleState
+    let result = client
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(schedule_state_pk(response_id))?)
+        .key("sk", to_attribute_value(SCHEDULE_STATE_SK.to_string())?)
+        .update_expression(
+            "SET item_type = :item_type, claimed_run = :run_at, claimed_at = :claimed_at",
+        )
+        .condition_expression(
+            "(attribute_not_exists(last_run) OR last_run < :run_at) \
+            AND (attribute_not_exists(claimed_run) OR claimed_run < :run_at \
+            OR claimed_at < :stale_before)",
+        )
+        .expression_attribute_values(":item_type", to_attribute_value("ScheduleState")?)
+        .expression_attribute_values(":run_at", to_attribute_value(run_at)?)
+        .expression_attribute_values(":claimed_at", to_attribute_value(claimed_at)?)
+        .expression_attribute_values(":stale_before", to_attribute_value(stale_before)?)
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(true),
+        Err(SdkError::ServiceError { err, .. })
+            if matches!(
+                err.kind,
+                UpdateItemErrorKind::ConditionalCheckFailedException(_)
+            ) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Mark a claimed scheduled time as finished, it won't be claimed again
+pub async fn complete_scheduled_run(response_id: &str, run_at: i64) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(schedule_state_pk(response_id))?)
+        .key("sk", to_attribute_value(SCHEDULE_STATE_SK.to_string())?)
+        .update_expression("SET last_run = :run_at")
+        .condition_expression(
+            "attribute_exists(pk) AND (attribute_not_exists(last_run) OR last_run < :run_at)",
+        )
+        .expression_attribute_values(":run_at", to_attribute_value(run_at)?)
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        // a later scheduled time already finished
+        Err(SdkError::ServiceError { err, .. })
+            if matches!(
+                err.kind,
+                UpdateItemErrorKind::ConditionalCheckFailedException(_)
+            ) =>
+        {
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+const SCHEDULE_STATE_SK: &str = "last-run";
+
+/// Remember the message posted for a PagerDuty incident so later events can reply in its thread
+pub async fn record_incident_thread(
+    incident_id: &str,
+    channel_id: &str,
+    message_ts: &str,
+) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    let expires_at = (SystemTime::now() + INCIDENT_THREAD_TTL)
+        .duration_since(UNIX_EPOCH)?
+        .as_secs();
+
+    client
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(to_item(ReceptionistTableItem::IncidentThread {
+            pk: incident_thread_pk(incident_id),
+            sk: format!("thread/{channel_id}/{message_ts}"),
+            channel_id: channel_id.to_string(),
+            message_ts: message_ts.to_string(),
+            expires_at,
+        })?))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// `(channel id, message ts)` of every message posted for a PagerDuty incident
+pub async fn get_incident_threads(incident_id: &str) -> Result<Vec<(String, String)>> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .query()
+        .table_name(TABLE_NAME)
+        .key_condition_expression("pk = :incident_key")
+        .expression_attribute_values(
+            ":incident_key",
+            to_attribute_value(incident_thread_pk(incident_id))?,
+        )
+        .send()
+        .await?;
+
+    let mut threads = Vec::new();
+    for item in result.items.unwrap_or_default() {
+        if let ReceptionistTableItem::IncidentThread {
+            channel_id,
+            message_ts,
+            ..
+        } = from_item(item)?
+        {
+            threads.push((channel_id, message_ts))
+        }
+    }
+
+    Ok(threads)
+}
+
+const WEBHOOK_SECRET_SK: &str = "secret";
+
+/// Signing secret of a webhook Response, kept apart from the Response so it never reaches a view
+pub async fn get_webhook_secret(response_id: &str) -> Result<Option<String>> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .get_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(webhook_secret_pk(response_id))?)
+        .key("sk", to_attribute_value(WEBHOOK_SECRET_SK)?)
+        .send()
+        .await?;
+
+    match result.item {
+        Some(item) => match from_item(item)? {
+            ReceptionistTableItem::WebhookSecret { secret, .. } => Ok(Some(secret)),
+            other => bail!("Unexpected item for webhook secret: {:?}", other),
+        },
+        None => Ok(None),
+    }
+}
+
+pub async fn set_webhook_secret(response_id: &str, secret: &str) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    client
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(to_item(ReceptionistTableItem::WebhookSecret {
+            pk: webhook_secret_pk(response_id),
+            sk: WEBHOOK_SECRET_SK.to_string(),
+            secret: secret.to_string(),
+        })?))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_webhook_secret(response_id: &str) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    client
+        .delete_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(webhook_secret_pk(response_id))?)
+        .key("sk", to_attribute_value(WEBHOOK_SECRET_SK)?)
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// Oncall cache clears are few and short lived, so they share a single partition
+const ONCALL_CACHE_INVALIDATIONS_PK: &str = "oncall-cache-invalidations";
+
+/// Record a clear of the oncall cache, for every instance to apply to its own cache.
+/// A later clear of the same target replaces the earlier one
+pub async fn record_oncall_cache_invalidation(
+    target: Option<&str>,
+    invalidated_at: i64,
+) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    let expires_at = (SystemTime::now() + ONCALL_CACHE_INVALIDATION_TTL)
+        .duration_since(UNIX_EPOCH)?
+        .as_secs();
+
+    client
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(to_item(
+            ReceptionistTableItem::OncallCacheInvalidation {
+                pk: ONCALL_CACHE_INVALIDATIONS_PK.to_string(),
+                sk: target.map_or("all".to_string(), |target| format!("target/{target}")),
+                target: target.map(str::to_string),
+                invalidated_at,
+                expires_at,
+            },
+        )?))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// `(target, unix millis)` of the oncall cache clears after `since`, `None` targets cleared everything
+pub async fn get_oncall_cache_invalidations(since: i64) -> Result<Vec<(Option<String>, i64)>> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .query()
+        .table_name(TABLE_NAME)
+        .key_condition_expression("pk = :invalidations_key")
+        .filter_expression("invalidated_at > :since")
+        .expression_attribute_values(
+            ":invalidations_key",
+            to_attribute_value(ONCALL_CACHE_INVALIDATIONS_PK)?,
+        )
+        .expression_attribute_values(":since", to_attribute_value(since)?)
+        .send()
+        .await?;
+
+    let mut invalidations = Vec::new();
+    for item in result.items.unwrap_or_default() {
+        if let ReceptionistTableItem::OncallCacheInvalidation {
+            target,
+            invalidated_at,
+            ..
+        } = from_item(item)?
+        {
+            invalidations.push((target, invalidated_at))
+        }
+    }
+
+    Ok(invalidations)
+}
+
+/// Rotations are few, so they share a single partition and are filtered after querying
+const ROTATIONS_PK: &str = "rotations/*";
+
+fn rotation_sk(rotation_id: &str) -> String {
+    format!("rotation/{rotation_id}")
+}
+
+fn rotation_item(rotation: Rotation) -> ReceptionistTableItem {
+    ReceptionistTableItem::Rotation {
+        pk: ROTATIONS_PK.to_string(),
+        sk: rotation_sk(&rotation.id),
+        rotation,
+    }
+}
+
+pub async fn create_rotation(rotation: Rotation) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    client
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(to_item(rotation_item(rotation))?))
+        .condition_expression("attribute_not_exists(sk)")
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_rotation(rotation: Rotation) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    client
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(to_item(rotation_item(rotation))?))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_rotation(rotation_id: &str) -> Result<()> {
+    let client = get_or_init_dynamo_client().await;
+
+    client
+        .delete_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(ROTATIONS_PK.to_string())?)
+        .key("sk", to_attribute_value(rotation_sk(rotation_id))?)
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_rotation_by_id(rotation_id: &str) -> Result<Rotation> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .get_item()
+        .table_name(TABLE_NAME)
+        .key("pk", to_attribute_value(ROTATIONS_PK.to_string())?)
+        .key("sk", to_attribute_value(rotation_sk(rotation_id))?)
+        .send()
+        .await?;
+
+    match result.item {
+        Some(item) => match from_item(item)? {
+            ReceptionistTableItem::Rotation { rotation, .. } => Ok(rotation),
+            _ => bail!("Unexpected item type for rotation {}", rotation_id),
+        },
+        None => bail!("no rotation found for that id"),
+    }
+}
+
+pub async fn get_all_rotations() -> Result<Vec<Rotation>> {
+    let client = get_or_init_dynamo_client().await;
+
+    let result = client
+        .query()
+        .table_name(TABLE_NAME)
+        .key_condition_expression("pk = :rotations_key")
+        .expression_attribute_values(
+            ":rotations_key",
+            to_attribute_value(ROTATIONS_PK.to_string())?,
+        )
+        .send()
+        .await?;
+
+    let mut rotations = Vec::new();
+    for item in result.items.unwrap_or_default() {
+        if let ReceptionistTableItem::Rotation { rotation, .. } = from_item(item)? {
+            rotations.push(rotation)
+        }
+    }
+
+    Ok(rotations)
+}
+
+/// Rotation names are matched ignoring case and surrounding whitespace
+pub async fn get_rotation_by_name(name: &str) -> Result<Option<Rotation>> {
+    Ok(get_all_rotations()
+        .await?
+        .into_iter()
+        .find(|rotation| rotation.name.trim().eq_ignore_ascii_case(name.trim())))
+}
+
+pub async fn get_rotations_for_collaborator(user_id: &str) -> Result<Vec<Rotation>> {
+    Ok(get_all_rotations()
+        .await?
+        .into_iter()
+        .filter(|rotation| rotation.collaborators.contains(&user_id.to_owned()))
+        .collect())
+}
+
+fn incident_thread_pk(incident_id: &str) -> String {
+    format!("incident-threads/{incident_id}")
+}
+
+fn schedule_state_pk(response_id: &str) -> String {
+    format!("schedule-state/{response_id}")
+}
+
+fn webhook_secret_pk(response_id: &str) -> String {
+    format!("webhook-secrets/{response_id}")
+}
+
+async fn get_collaborator_items(user_id: &str) -> Result<Vec<ReceptionistTableItem>> {
     Ok(from_items::<ReceptionistTableItem>(
-        result.items().unwrap().to_owned(),
+        query_partition(user_id.to_string()).await?,
     )?)
 }
 
@@ -217,14 +1007,14 @@ pub async fn get_responses_for_collaborator(user_id: &str) -> Result<Vec<Recepti
         return Ok(Vec::new());
     }
 
-    let mut keys_builder = KeysAndAttributes::builder();
+    let mut response_keys = Vec::new();
 
     for item in collaborator_items {
         match item {
             ReceptionistTableItem::Collaborator {
                 sk, listener_pk, ..
             } => {
-                keys_builder = keys_builder.keys(
+                response_keys.push(
                     [
                         ("pk".to_owned(), to_attribute_value(listener_pk).unwrap()),
                         ("sk".to_owned(), to_attribute_value(sk).unwrap()),
@@ -237,21 +1027,7 @@ pub async fn get_responses_for_collaborator(user_id: &str) -> Result<Vec<Recepti
         }
     }
 
-    let client = get_or_init_dynamo_client().await;
-    let result = client
-        .batch_get_item()
-        .request_items(TABLE_NAME, keys_builder.build())
-        .send()
-        .await?;
-
-    Ok(from_items(
-        result
-            .responses
-            .unwrap()
-            .get(TABLE_NAME)
-            .unwrap()
-            .to_owned(),
-    )?)
+    Ok(from_items(batch_get_all(response_keys).await?)?)
 
     // alternate method of writing the above without mutating array:
     // Ok(table_items
@@ -305,9 +1081,22 @@ fn convert_response_to_table_items(
 ) -> Result<Vec<ReceptionistTableItem>> {
     let mut all_items = vec![];
 
-    let response_pkey: ListenerPKey = rec_response.listener.clone().into();
+    let response_pkey = ListenerPKey::for_response(&rec_response);
     all_items.push(rec_response.clone().into());
 
+    if let ReceptionistListener::SlackChannels { channel_ids } = &rec_response.listener {
+        for channel_id in channel_ids {
+            all_items.push(ReceptionistTableItem::ChannelIndex {
+                pk: ReceptionistListener::SlackChannel {
+                    channel_id: channel_id.to_owned(),
+                }
+                .into(),
+                sk: rec_response.id.clone(),
+                listener_pk: response_pkey.clone(),
+            })
+        }
+    }
+
     for collaborator in rec_response.collaborators {
         all_items.push(ReceptionistTableItem::Collaborator {
             pk: collaborator,
@@ -337,6 +1126,97 @@ enum ReceptionistTableItem {
         sk: String,
         listener_pk: ListenerPKey,
     },
+    /// A Response that already ran for a message, expired by the table's TTL
+    FiredResponse {
+        /// Message Key: `fired-responses/C23456/1640000000.000100`
+        pk: String,
+        /// `fired/<response id>`, prefixed to keep these out of InvertedIndex lookups by Response ID
+        sk: String,
+        response_id: String,
+        /// Unix timestamp in seconds
+        expires_at: u64,
+    },
+    /// An event that was already handled, expired by the table's TTL
+    DeliveredEvent {
+        /// Event Key: `delivered-events/slack/Ev0123456`
+        pk: String,
+        /// Always `delivered`
+        sk: String,
+        /// Unix timestamp in seconds
+        expires_at: u64,
+    },
+    /// A user that a once per user Response already ran for
+    WelcomedMember {
+        /// Welcome Key: `welcomed-members/<response id>`
+        pk: String,
+        /// SlackUserID
+        sk: String,
+    },
+    /// When a scheduled Response last ran, survives restarts of the scheduler
+    ScheduleState {
+        /// Schedule Key: `schedule-state/<response id>`
+        pk: String,
+        /// Always `last-run`
+        sk: String,
+        /// Unix timestamp in seconds of the scheduled time that last finished running
+        #[serde(default)]
+        last_run: Option<i64>,
+        /// Unix timestamp in seconds of the scheduled time that is claimed to run next
+        #[serde(default)]
+        claimed_run: Option<i64>,
+        /// Unix timestamp in seconds of when `claimed_run` was claimed
+        #[serde(default)]
+        claimed_at: Option<i64>,
+    },
+    /// A message posted for a PagerDuty incident, expired by the table's TTL
+    IncidentThread {
+        /// Incident Key: `incident-threads/<pagerduty incident id>`
+        pk: String,
+        /// `thread/<channel id>/<message ts>`
+        sk: String,
+        channel_id: String,
+        message_ts: String,
+        /// Unix timestamp in seconds
+        expires_at: u64,
+    },
+    /// HMAC secret the caller of a webhook Response signs requests with
+    WebhookSecret {
+        /// Secret Key: `webhook-secrets/<response id>`
+        pk: String,
+        /// Always `secret`
+        sk: String,
+        secret: String,
+    },
+    /// A clear of the oncall cache that every instance applies to its own cache, expired by the table's TTL
+    OncallCacheInvalidation {
+        /// Always `oncall-cache-invalidations`
+        pk: String,
+        /// `target/<target>`, or `all` when everything was cleared
+        sk: String,
+        #[serde(default)]
+        target: Option<String>,
+        /// Unix timestamp in milliseconds
+        invalidated_at: i64,
+        /// Unix timestamp in seconds
+        expires_at: u64,
+    },
+    /// A rotation kept by Receptionist, see `Rotation`
+    Rotation {
+        /// Always `rotations/*`
+        pk: String,
+        /// `rotation/<rotation id>`, prefixed to keep these out of InvertedIndex lookups by Response ID
+        sk: String,
+        #[serde(flatten)]
+        rotation: Rotation,
+    },
+    /// Points a single channel's key at a Response listening to several channels
+    ChannelIndex {
+        /// Channel Listener: `slack-channel/C23456`
+        pk: ListenerPKey,
+        /// Bot's Response ID
+        sk: String,
+        listener_pk: ListenerPKey,
+    },
 }
 
 impl ReceptionistTableItem {
@@ -344,6 +1224,21 @@ impl ReceptionistTableItem {
         match self {
             ReceptionistTableItem::Response { pk, sk, .. } => (pk.to_string(), sk.to_string()),
             ReceptionistTableItem::Collaborator { pk, sk, .. } => (pk.to_string(), sk.to_string()),
+            ReceptionistTableItem::ChannelIndex { pk, sk, .. } => (pk.to_string(), sk.to_string()),
+            ReceptionistTableItem::FiredResponse { pk, sk, .. } => (pk.to_string(), sk.to_string()),
+            ReceptionistTableItem::DeliveredEvent { pk, sk, .. } => {
+                (pk.to_string(), sk.to_string())
+            }
+            ReceptionistTableItem::WelcomedMember { pk, sk } => (pk.to_string(), sk.to_string()),
+            ReceptionistTableItem::ScheduleState { pk, sk, .. } => (pk.to_string(), sk.to_string()),
+            ReceptionistTableItem::IncidentThread { pk, sk, .. } => {
+                (pk.to_string(), sk.to_string())
+            }
+            ReceptionistTableItem::WebhookSecret { pk, sk, .. } => (pk.to_string(), sk.to_string()),
+            ReceptionistTableItem::OncallCacheInvalidation { pk, sk, .. } => {
+                (pk.to_string(), sk.to_string())
+            }
+            ReceptionistTableItem::Rotation { pk, sk, .. } => (pk.to_string(), sk.to_string()),
         }
     }
 }
@@ -351,7 +1246,7 @@ impl ReceptionistTableItem {
 impl From<ReceptionistResponse> for ReceptionistTableItem {
     fn from(rec_response: ReceptionistResponse) -> Self {
         Self::Response {
-            pk: rec_response.listener.clone().into(),
+            pk: ListenerPKey::for_response(&rec_response),
             sk: rec_response.id.clone(),
             receptionist_response: rec_response,
         }
@@ -362,6 +1257,67 @@ impl From<ReceptionistResponse> for ReceptionistTableItem {
 /// String Representation of a Receptionist Listener: `slack-channel/C23456`
 struct ListenerPKey(String);
 
+/// PKey value for listeners that are not limited to a single channel: `app-mention/*`
+const ANY_CHANNEL_PKEY_VALUE: &str = "*";
+
+impl ListenerPKey {
+    /// PKey a Response is stored under, workspace-wide Responses go to one of their listener's shards
+    fn for_response(rec_response: &ReceptionistResponse) -> Self {
+        let pkey: Self = rec_response.listener.clone().into();
+
+        if matches!(
+            rec_response.listener,
+            ReceptionistListener::SlackChannelPattern { .. }
+                | ReceptionistListener::AllPublicChannels
+        ) {
+            pkey.shard(wildcard_shard(&rec_response.id))
+        } else {
+            pkey
+        }
+    }
+
+    /// `slack-channel-pattern/*/2`
+    fn shard(self, shard: u32) -> Self {
+        Self(format!("{}/{}", self.0, shard))
+    }
+
+    /// Every shard of a workspace-wide listener's PKey
+    fn all_shards(listener: ReceptionistListener) -> Vec<Self> {
+        let pkey: Self = listener.into();
+
+        (0..WILDCARD_PKEY_SHARDS)
+            .map(|shard| pkey.clone().shard(shard))
+            .collect()
+    }
+
+    /// All PKeys that can hold responses for an incoming event.
+    /// Workspace-wide responses are stored under their own sharded keys and must be queried alongside the channel's key
+    fn lookup_keys(listener: ReceptionistListener) -> Vec<Self> {
+        match listener {
+            ReceptionistListener::SlackChannel { .. } => [
+                vec![listener.into()],
+                Self::all_shards(ReceptionistListener::SlackChannelPattern {
+                    pattern: ANY_CHANNEL_PKEY_VALUE.to_string(),
+                }),
+                Self::all_shards(ReceptionistListener::AllPublicChannels),
+            ]
+            .concat(),
+            ReceptionistListener::AppMention {
+                channel_id: Some(_),
+            } => vec![
+                listener.into(),
+                ReceptionistListener::AppMention { channel_id: None }.into(),
+            ],
+            _ => vec![listener.into()],
+        }
+    }
+}
+
+/// Stable across processes, unlike `DefaultHasher`, so a Response always maps to the same shard
+fn wildcard_shard(response_id: &str) -> u32 {
+    response_id.bytes().map(u32::from).sum::<u32>() % WILDCARD_PKEY_SHARDS
+}
+
 impl Display for ListenerPKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -374,6 +1330,35 @@ impl From<ReceptionistListener> for ListenerPKey {
             ReceptionistListener::SlackChannel { channel_id } => {
                 format!("{}/{}", listener, channel_id)
             }
+            // multi-channel responses share a partition, they are found per channel
+            // through ChannelIndex items or filtered after querying
+            ReceptionistListener::SlackChannels { .. }
+            | ReceptionistListener::SlackChannelPattern { .. }
+            | ReceptionistListener::AllPublicChannels => {
+                format!("{}/{}", listener, ANY_CHANNEL_PKEY_VALUE)
+            }
+            ReceptionistListener::MemberJoinedChannel { channel_id, .. } => {
+                format!("{}/{}", listener, channel_id)
+            }
+            ReceptionistListener::PagerDutyIncident { service_id, .. } => {
+                format!("{}/{}", listener, service_id)
+            }
+            // regular and fallback DM responses are queried together,
+            // and the scheduler checks every scheduled response on each tick.
+            // webhooks are only ever looked up by their Response ID
+            ReceptionistListener::DirectMessage { .. }
+            | ReceptionistListener::Schedule { .. }
+            | ReceptionistListener::Webhook { .. } => {
+                format!("{}/{}", listener, ANY_CHANNEL_PKEY_VALUE)
+            }
+            ReceptionistListener::AppMention { channel_id } => format!(
+                "{}/{}",
+                listener,
+                channel_id.unwrap_or_else(|| ANY_CHANNEL_PKEY_VALUE.to_string())
+            ),
+            ReceptionistListener::SlackCommandKeyword { command, keyword } => {
+                format!("{}/{} {}", listener, command, keyword.to_lowercase())
+            }
         };
 
         Self(pk)
@@ -395,6 +1380,29 @@ impl TryInto<ReceptionistListener> for ListenerPKey {
             ReceptionistListener::SlackChannel { .. } => Ok(ReceptionistListener::SlackChannel {
                 channel_id: value.into(),
             }),
+            ReceptionistListener::AppMention { .. } => Ok(ReceptionistListener::AppMention {
+                channel_id: (value != ANY_CHANNEL_PKEY_VALUE).then(|| value.to_string()),
+            }),
+            ReceptionistListener::SlackChannels { .. }
+            | ReceptionistListener::SlackChannelPattern { .. }
+            | ReceptionistListener::AllPublicChannels
+            | ReceptionistListener::DirectMessage { .. }
+            | ReceptionistListener::MemberJoinedChannel { .. }
+            | ReceptionistListener::Schedule { .. }
+            | ReceptionistListener::Webhook { .. }
+            | ReceptionistListener::PagerDutyIncident { .. } => {
+                bail!("Listener can't be rebuilt from its PKey: {}", self)
+            }
+            ReceptionistListener::SlackCommandKeyword { .. } => {
+                let (command, keyword) = value
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("Unable to find command keyword delimiter"))?;
+
+                Ok(ReceptionistListener::SlackCommandKeyword {
+                    command: command.into(),
+                    keyword: keyword.into(),
+                })
+            }
         }
     }
 }
@@ -402,8 +1410,10 @@ impl TryInto<ReceptionistListener> for ListenerPKey {
 #[cfg(test)]
 mod test {
 
-    use super::convert_response_to_table_items;
-    use crate::mock_receptionist_response;
+    use super::{
+        convert_response_to_table_items, ListenerPKey, ReceptionistTableItem, WILDCARD_PKEY_SHARDS,
+    };
+    use crate::{mock_receptionist_response, ReceptionistListener, ReceptionistResponse};
     use anyhow::Result;
 
     #[test]
@@ -414,4 +1424,36 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_workspace_wide_response_is_found_in_its_shard() -> Result<()> {
+        let test_resp = ReceptionistResponse::new(
+            vec!["U0001".to_string()],
+            ReceptionistListener::SlackChannelPattern {
+                pattern: "help-*".to_string(),
+            },
+            vec![],
+            vec![],
+        );
+
+        let response_pk = match &convert_response_to_table_items(test_resp)?[0] {
+            ReceptionistTableItem::Response { pk, .. } => pk.to_string(),
+            _ => panic!("the Response item comes first"),
+        };
+
+        let lookup_keys: Vec<String> =
+            ListenerPKey::lookup_keys(ReceptionistListener::SlackChannel {
+                channel_id: "C0001".to_string(),
+            })
+            .iter()
+            .map(|pkey| pkey.to_string())
+            .collect();
+
+        assert!(response_pk.starts_with("slack-channel-pattern/*/"));
+        assert!(lookup_keys.contains(&response_pk));
+        // the channel's own key, then every shard of both workspace-wide listeners
+        assert_eq!(lookup_keys.len(), 1 + 2 * WILDCARD_PKEY_SHARDS as usize);
+
+        Ok(())
+    }
 }
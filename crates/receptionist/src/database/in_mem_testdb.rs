@@ -1,8 +1,10 @@
+use super::DeliveredEvent;
 use crate::write_serde_struct_to_file;
 use crate::ReceptionistListener;
 use crate::ReceptionistResponse;
+use crate::Rotation;
 use anyhow::{bail, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tokio::sync::OnceCell;
 use tokio::sync::RwLock;
 use tokio::sync::RwLockReadGuard;
@@ -18,6 +20,82 @@ pub async fn get_or_init_mem_db() -> &'static RwLock<HashMap<String, Receptionis
         .await
 }
 
+/// Response IDs that already ran, keyed by `<channel id>/<message ts>`
+pub static FIRED_RESPONSES: OnceCell<RwLock<HashMap<String, HashSet<String>>>> =
+    OnceCell::const_new();
+async fn get_or_init_fired_responses() -> &'static RwLock<HashMap<String, HashSet<String>>> {
+    FIRED_RESPONSES
+        .get_or_init(|| async { RwLock::new(HashMap::new()) })
+        .await
+}
+
+/// Keys of the events that were already delivered
+pub static DELIVERED_EVENTS: OnceCell<RwLock<HashSet<String>>> = OnceCell::const_new();
+async fn get_or_init_delivered_events() -> &'static RwLock<HashSet<String>> {
+    DELIVERED_EVENTS
+        .get_or_init(|| async { RwLock::new(HashSet::new()) })
+        .await
+}
+
+/// Users that once per user Responses already ran for, keyed by Response ID
+pub static WELCOMED_MEMBERS: OnceCell<RwLock<HashMap<String, HashSet<String>>>> =
+    OnceCell::const_new();
+async fn get_or_init_welcomed_members() -> &'static RwLock<HashMap<String, HashSet<String>>> {
+    WELCOMED_MEMBERS
+        .get_or_init(|| async { RwLock::new(HashMap::new()) })
+        .await
+}
+
+/// Claimed and finished runs of a scheduled Response as unix timestamps
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScheduleState {
+    pub last_run: Option<i64>,
+    pub claimed_run: Option<i64>,
+    pub claimed_at: i64,
+}
+
+/// Runs of each scheduled Response, keyed by Response ID
+pub static SCHEDULE_STATE: OnceCell<RwLock<HashMap<String, ScheduleState>>> = OnceCell::const_new();
+async fn get_or_init_schedule_state() -> &'static RwLock<HashMap<String, ScheduleState>> {
+    SCHEDULE_STATE
+        .get_or_init(|| async { RwLock::new(HashMap::new()) })
+        .await
+}
+
+/// `(channel id, message ts)` posted for each PagerDuty incident, keyed by incident ID
+pub static INCIDENT_THREADS: OnceCell<RwLock<HashMap<String, Vec<(String, String)>>>> =
+    OnceCell::const_new();
+async fn get_or_init_incident_threads() -> &'static RwLock<HashMap<String, Vec<(String, String)>>> {
+    INCIDENT_THREADS
+        .get_or_init(|| async { RwLock::new(HashMap::new()) })
+        .await
+}
+
+/// Signing secrets of webhook Responses, keyed by Response ID
+pub static WEBHOOK_SECRETS: OnceCell<RwLock<HashMap<String, String>>> = OnceCell::const_new();
+async fn get_or_init_webhook_secrets() -> &'static RwLock<HashMap<String, String>> {
+    WEBHOOK_SECRETS
+        .get_or_init(|| async { RwLock::new(HashMap::new()) })
+        .await
+}
+
+/// Unix millis of the latest oncall cache clear of each target, `None` for clearing everything
+pub static ONCALL_CACHE_INVALIDATIONS: OnceCell<RwLock<HashMap<Option<String>, i64>>> =
+    OnceCell::const_new();
+async fn get_or_init_oncall_cache_invalidations() -> &'static RwLock<HashMap<Option<String>, i64>> {
+    ONCALL_CACHE_INVALIDATIONS
+        .get_or_init(|| async { RwLock::new(HashMap::new()) })
+        .await
+}
+
+/// Rotations keyed by Rotation ID
+pub static ROTATIONS: OnceCell<RwLock<HashMap<String, Rotation>>> = OnceCell::const_new();
+async fn get_or_init_rotations() -> &'static RwLock<HashMap<String, Rotation>> {
+    ROTATIONS
+        .get_or_init(|| async { RwLock::new(HashMap::new()) })
+        .await
+}
+
 pub fn save_db_to_json(temp_db: RwLockReadGuard<HashMap<String, ReceptionistResponse>>) {
     let all_responses_as_vec: Vec<&ReceptionistResponse> =
         temp_db.iter().map(|(_k, v)| v).collect();
@@ -44,17 +122,13 @@ pub async fn create_response(rec_response: ReceptionistResponse) -> Result<()> {
 pub async fn get_responses_for_listener(
     listener: ReceptionistListener,
 ) -> Result<Vec<ReceptionistResponse>> {
-    let db_key = match listener {
-        ReceptionistListener::SlackChannel { channel_id } => channel_id,
-    };
-
     let db_lock = get_or_init_mem_db().await;
 
     let all_responses = db_lock.read().await;
 
     Ok(all_responses
         .values()
-        .filter(|response| response.listener.matches_slack_channel_id(&db_key))
+        .filter(|response| response.listener.matches(&listener))
         .map(|r| r.to_owned())
         .collect())
 }
@@ -87,7 +161,7 @@ pub async fn delete_response(response: ReceptionistResponse) -> Result<()> {
 
     all_responses.remove(&response.id);
 
-    Ok(())
+    delete_webhook_secret(&response.id).await
 }
 
 pub async fn get_responses_for_collaborator(user_id: &str) -> Result<Vec<ReceptionistResponse>> {
@@ -101,3 +175,251 @@ pub async fn get_responses_for_collaborator(user_id: &str) -> Result<Vec<Recepti
         .map(|r| r.to_owned())
         .collect())
 }
+
+pub async fn get_fired_response_ids(channel_id: &str, message_ts: &str) -> Result<Vec<String>> {
+    let fired_lock = get_or_init_fired_responses().await;
+
+    let fired_responses = fired_lock.read().await;
+
+    Ok(fired_responses
+        .get(&format!("{channel_id}/{message_ts}"))
+        .map(|ids| ids.iter().cloned().collect())
+        .unwrap_or_default())
+}
+
+pub async fn record_fired_responses(
+    channel_id: &str,
+    message_ts: &str,
+    response_ids: Vec<String>,
+) -> Result<()> {
+    let fired_lock = get_or_init_fired_responses().await;
+
+    let mut fired_responses = fired_lock.write().await;
+
+    fired_responses
+        .entry(format!("{channel_id}/{message_ts}"))
+        .or_default()
+        .extend(response_ids);
+
+    Ok(())
+}
+
+pub async fn claim_delivered_event(event: &DeliveredEvent) -> Result<bool> {
+    let delivered_lock = get_or_init_delivered_events().await;
+
+    Ok(delivered_lock.write().await.insert(event.to_string()))
+}
+
+pub async fn has_welcomed_member(response_id: &str, user_id: &str) -> Result<bool> {
+    let welcomed_lock = get_or_init_welcomed_members().await;
+
+    let welcomed_members = welcomed_lock.read().await;
+
+    Ok(welcomed_members
+        .get(response_id)
+        .map_or(false, |users| users.contains(user_id)))
+}
+
+pub async fn record_welcomed_member(response_id: &str, user_id: &str) -> Result<()> {
+    let welcomed_lock = get_or_init_welcomed_members().await;
+
+    let mut welcomed_members = welcomed_lock.write().await;
+
+    welcomed_members
+        .entry(response_id.to_string())
+        .or_default()
+        .insert(user_id.to_string());
+
+    Ok(())
+}
+
+pub async fn get_last_scheduled_run(response_id: &str) -> Result<Option<i64>> {
+    let schedule_lock = get_or_init_schedule_state().await;
+
+    Ok(schedule_lock
+        .read()
+        .await
+        .get(response_id)
+        .and_then(|state| state.last_run))
+}
+
+pub async fn claim_scheduled_run(
+    response_id: &str,
+    run_at: i64,
+    claimed_at: i64,
+    stale_before: i64,
+) -> Result<bool> {
+    let schedule_lock = get_or_init_schedule_state().await;
+
+    let mut schedule_state = schedule_lock.write().await;
+    let state = schedule_state.entry(response_id.to_string()).or_default();
+
+    let finished = state.last_run.map_or(false, |last_run| last_run >= run_at);
+    let claimed = state.claimed_run.map_or(false, |claimed_run| {
+        claimed_run >= run_at && state.claimed_at >= stale_before
+    });
+
+    if finished || claimed {
+        return Ok(false);
+    }
+
+    state.claimed_run = Some(run_at);
+    state.claimed_at = claimed_at;
+    Ok(true)
+}
+
+pub async fn complete_scheduled_run(response_id: &str, run_at: i64) -> Result<()> {
+    let schedule_lock = get_or_init_schedule_state().await;
+
+    let mut schedule_state = schedule_lock.write().await;
+    let state = schedule_state.entry(response_id.to_string()).or_default();
+
+    if state.last_run.map_or(true, |last_run| last_run < run_at) {
+        state.last_run = Some(run_at);
+    }
+
+    Ok(())
+}
+
+pub async fn record_incident_thread(
+    incident_id: &str,
+    channel_id: &str,
+    message_ts: &str,
+) -> Result<()> {
+    let threads_lock = get_or_init_incident_threads().await;
+
+    threads_lock
+        .write()
+        .await
+        .entry(incident_id.to_string())
+        .or_default()
+        .push((channel_id.to_string(), message_ts.to_string()));
+
+    Ok(())
+}
+
+pub async fn get_incident_threads(incident_id: &str) -> Result<Vec<(String, String)>> {
+    let threads_lock = get_or_init_incident_threads().await;
+
+    Ok(threads_lock
+        .read()
+        .await
+        .get(incident_id)
+        .cloned()
+        .unwrap_or_default())
+}
+
+pub async fn get_webhook_secret(response_id: &str) -> Result<Option<String>> {
+    let secrets_lock = get_or_init_webhook_secrets().await;
+
+    Ok(secrets_lock.read().await.get(response_id).cloned())
+}
+
+pub async fn set_webhook_secret(response_id: &str, secret: &str) -> Result<()> {
+    let secrets_lock = get_or_init_webhook_secrets().await;
+
+    secrets_lock
+        .write()
+        .await
+        .insert(response_id.to_string(), secret.to_string());
+
+    Ok(())
+}
+
+pub async fn delete_webhook_secret(response_id: &str) -> Result<()> {
+    let secrets_lock = get_or_init_webhook_secrets().await;
+
+    secrets_lock.write().await.remove(response_id);
+
+    Ok(())
+}
+
+pub async fn record_oncall_cache_invalidation(
+    target: Option<&str>,
+    invalidated_at: i64,
+) -> Result<()> {
+    let invalidations_lock = get_or_init_oncall_cache_invalidations().await;
+
+    invalidations_lock
+        .write()
+        .await
+        .insert(target.map(str::to_string), invalidated_at);
+
+    Ok(())
+}
+
+pub async fn get_oncall_cache_invalidations(since: i64) -> Result<Vec<(Option<String>, i64)>> {
+    let invalidations_lock = get_or_init_oncall_cache_invalidations().await;
+
+    Ok(invalidations_lock
+        .read()
+        .await
+        .iter()
+        .filter(|(_, invalidated_at)| **invalidated_at > since)
+        .map(|(target, invalidated_at)| (target.clone(), *invalidated_at))
+        .collect())
+}
+
+pub async fn create_rotation(rotation: Rotation) -> Result<()> {
+    let rotations_lock = get_or_init_rotations().await;
+
+    let mut rotations = rotations_lock.write().await;
+
+    if rotations.contains_key(&rotation.id) {
+        bail!("ID already exists: {}", rotation.id)
+    } else {
+        rotations.insert(rotation.id.to_owned(), rotation);
+    }
+
+    Ok(())
+}
+
+pub async fn update_rotation(rotation: Rotation) -> Result<()> {
+    let rotations_lock = get_or_init_rotations().await;
+
+    rotations_lock
+        .write()
+        .await
+        .insert(rotation.id.to_owned(), rotation);
+
+    Ok(())
+}
+
+pub async fn delete_rotation(rotation_id: &str) -> Result<()> {
+    let rotations_lock = get_or_init_rotations().await;
+
+    rotations_lock.write().await.remove(rotation_id);
+
+    Ok(())
+}
+
+pub async fn get_rotation_by_id(rotation_id: &str) -> Result<Rotation> {
+    let rotations_lock = get_or_init_rotations().await;
+
+    match rotations_lock.read().await.get(rotation_id) {
+        Some(rotation) => Ok(rotation.to_owned()),
+        None => bail!("no rotation found for that id"),
+    }
+}
+
+pub async fn get_all_rotations() -> Result<Vec<Rotation>> {
+    let rotations_lock = get_or_init_rotations().await;
+
+    Ok(rotations_lock.read().await.values().cloned().collect())
+}
+
+/// Rotation names are matched ignoring case and surrounding whitespace
+pub async fn get_rotation_by_name(name: &str) -> Result<Option<Rotation>> {
+    Ok(get_all_rotations()
+        .await?
+        .into_iter()
+        .find(|rotation| rotation.name.trim().eq_ignore_ascii_case(name.trim())))
+}
+
+pub async fn get_rotations_for_collaborator(user_id: &str) -> Result<Vec<Rotation>> {
+    Ok(get_all_rotations()
+        .await?
+        .into_iter()
+        .filter(|rotation| rotation.collaborators.contains(&user_id.to_owned()))
+        .collect())
+}
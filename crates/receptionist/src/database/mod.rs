@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 pub mod cloudformation;
 
 #[cfg(feature = "dynamodb")]
@@ -9,3 +11,24 @@ pub use dynamodb::*;
 mod in_mem_testdb;
 #[cfg(feature = "tempdb")]
 pub use in_mem_testdb::*;
+
+/// An event delivered to Receptionist, remembered by the ID its sender gave it
+/// so that redeliveries of the same event are only handled once
+#[derive(Debug, Clone)]
+pub enum DeliveredEvent {
+    /// `event_id` of a Slack Events API callback
+    Slack(String),
+    /// `id` of a PagerDuty v3 webhook event
+    PagerDuty(String),
+}
+
+impl Display for DeliveredEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveredEvent::Slack(event_id) => write!(f, "delivered-events/slack/{event_id}"),
+            DeliveredEvent::PagerDuty(event_id) => {
+                write!(f, "delivered-events/pagerduty/{event_id}")
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use hyper::{
+    client::{Client, HttpConnector},
+    Body,
+};
+use hyper_rustls::{ConfigBuilderExt, HttpsConnector, HttpsConnectorBuilder};
+
+/// hyper client used for every outgoing HTTP request (PagerDuty, webhooks), clones share one connection pool
+pub type HttpsClient = Client<HttpsConnector<HttpConnector>>;
+
+pub fn new_https_client() -> HttpsClient {
+    let https = HttpsConnectorBuilder::new()
+        .with_tls_config(
+            rustls::ClientConfig::builder()
+                .with_safe_defaults()
+                .with_native_roots()
+                .with_no_client_auth(),
+        )
+        .https_or_http()
+        .enable_http1()
+        .build();
+
+    Client::builder().build::<_, Body>(https)
+}
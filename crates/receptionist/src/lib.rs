@@ -8,17 +8,45 @@ extern crate macro_rules_attribute;
 
 pub mod config;
 mod database;
+mod http_client;
 mod manager_ui;
+mod oncall;
 mod pagerduty;
 mod response;
 mod response2;
+mod scheduler;
 mod slack;
 mod utils;
+mod webhooks;
 
 pub use database::*;
+pub use http_client::{new_https_client, HttpsClient};
 pub use manager_ui::*;
-pub use pagerduty::client::PagerDuty;
+pub use oncall::{
+    cache::{get_or_init_oncall_cache, CacheMetrics, OncallCache, TtlCache},
+    has_oncall_segment, next_escalation_level, oncall_users_to_mention,
+    opsgenie::Opsgenie,
+    rotation::{
+        new_rotation_override, HandoffCadence, Rotation, RotationOncallProvider, RotationOverride,
+        RotationShift,
+    },
+    unavailable_note, with_oncall_segment, OncallFallback, OncallProvider, OncallProviderKind,
+    OncallTarget, OncallTopicLocation, OncallUnavailableAction, OncallUser, SlackUnavailability,
+};
+pub use pagerduty::{
+    client::{PagerDuty, PagerDutyError, PagerDutyEvents, PagerDutyOncallTarget},
+    webhook::{axum_handler_pagerduty_webhook, handle_pagerduty_webhook},
+};
 pub use response::*;
+pub use scheduler::{parse_cron, parse_timezone, run_due_schedules, SCHEDULER_TICK_SECS};
 pub use slack::*;
 pub use tower::ServiceBuilder;
 pub use utils::*;
+pub use webhooks::{
+    inbound::{axum_handler_inbound_webhook, handle_inbound_webhook, rotate_webhook_secret},
+    outbound::{send_webhook, WEBHOOK_MAX_ATTEMPTS, WEBHOOK_TIMEOUT},
+    signature::{
+        sign_webhook_body, verify_webhook_signature, WEBHOOK_SIGNATURE_HEADER,
+        WEBHOOK_TIMESTAMP_HEADER,
+    },
+};
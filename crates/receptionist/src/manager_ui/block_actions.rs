@@ -1,9 +1,9 @@
 use super::BlockSectionRouter;
 #[cfg(any(feature = "tempdb", feature = "dynamodb"))]
-use crate::database::get_response_by_id;
+use crate::database::{get_response_by_id, get_rotation_by_id, get_webhook_secret};
 use crate::{
-    manager_ui::{select_mode, MetaForManagerView},
-    SlackStateWorkaround,
+    manager_ui::{select_mode, webhook_secret_view, MetaForManagerView},
+    rotate_webhook_secret, SlackStateWorkaround,
 };
 use anyhow::{anyhow, bail, Context, Result};
 use slack_morphism::prelude::*;
@@ -29,6 +29,8 @@ pub async fn process_action_event(
                 .as_deref()
                 .ok_or_else(|| anyhow!("no private_metadata field in view"))?;
 
+            let trigger_id = actions_event.trigger_id;
+
             let mut private_metadata: MetaForManagerView = serde_json::from_str(metadata_str)
                 .with_context(|| format!("invalid private metadata: {metadata_str}"))?;
 
@@ -102,28 +104,225 @@ pub async fn process_action_event(
                             .update_manager_modal_view(view_id.to_owned(), &private_metadata)
                             .await?
                     }
-                    BlockSectionRouter::CollaboratorSelection => {
-                        todo!()
+                    BlockSectionRouter::ListenerTypeSelected => {
+                        let mut response = private_metadata
+                            .response
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        let listener_value = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("no option selected"))?
+                            .value;
+
+                        response.update_listener_type(&listener_value)?;
+
+                        private_metadata.response = Some(response);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
                     }
-                    BlockSectionRouter::ListenerChannelSelected => {
-                        todo!()
+                    BlockSectionRouter::ListenerDmFallbackSelected => {
+                        let mut response = private_metadata
+                            .response
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        let fallback_value = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("no option selected"))?
+                            .value;
+
+                        response.update_dm_fallback(&fallback_value)?;
+
+                        private_metadata.response = Some(response);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
                     }
-                    BlockSectionRouter::MessageConditionValueInput => {
-                        todo!()
+                    BlockSectionRouter::EditedMessagesSelected => {
+                        let mut response = private_metadata
+                            .response
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        let edits_value = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("no option selected"))?
+                            .value;
+
+                        response.update_run_on_edits(&edits_value)?;
+
+                        private_metadata.response = Some(response);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
                     }
-                    BlockSectionRouter::AttachEmojiInput => {
-                        todo!()
+                    BlockSectionRouter::ListenerWelcomeFrequencySelected => {
+                        let mut response = private_metadata
+                            .response
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        let welcome_value = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("no option selected"))?
+                            .value;
+
+                        response.update_welcome_frequency(&welcome_value)?;
+
+                        private_metadata.response = Some(response);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
                     }
-                    BlockSectionRouter::ReplyThreadedMsgInput => {
-                        todo!()
+                    BlockSectionRouter::ListenerWelcomeGuestsSelected => {
+                        let mut response = private_metadata
+                            .response
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        let welcome_value = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("no option selected"))?
+                            .value;
+
+                        response.update_welcome_guests(&welcome_value)?;
+
+                        private_metadata.response = Some(response);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
                     }
-                    BlockSectionRouter::PostChannelMsgInput => {
-                        todo!()
+                    BlockSectionRouter::OncallProviderSelected => {
+                        let mut response = private_metadata
+                            .response
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        let provider_value = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("no option selected"))?
+                            .value;
+
+                        response
+                            .get_action_mut(index_result?)?
+                            .update_oncall_provider(&provider_value)?;
+
+                        private_metadata.response = Some(response);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
+                    }
+                    BlockSectionRouter::InviteeSelected => {
+                        let mut response = private_metadata
+                            .response
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        let invitee_value = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("no option selected"))?
+                            .value;
+
+                        response
+                            .get_action_mut(index_result?)?
+                            .update_invitee(&invitee_value)?;
+
+                        private_metadata.response = Some(response);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
+                    }
+                    BlockSectionRouter::RotationSelection => {
+                        let selected_item = action
+                            .selected_option
+                            .ok_or_else(|| anyhow!("No selection for rotation"))?;
+
+                        private_metadata.rotation =
+                            Some(get_rotation_by_id(&selected_item.value).await?);
+
+                        slack
+                            .update_manager_modal_view(view_id.to_owned(), &private_metadata)
+                            .await?
+                    }
+                    BlockSectionRouter::WebhookSecretRotateClicked => {
+                        let response = private_metadata
+                            .response
+                            .as_ref()
+                            .ok_or_else(|| anyhow!("No Response in view metadata"))?;
+
+                        // only a saved webhook has a secret, unsaved ones get theirs when they are saved
+                        let secret = match get_webhook_secret(&response.id).await? {
+                            Some(_) => Some(rotate_webhook_secret(&response.id).await?),
+                            None => None,
+                        };
+
+                        let view_push_request = SlackApiViewsPushRequest::new(
+                            trigger_id.clone(),
+                            webhook_secret_view(&response.id, secret.as_deref()),
+                        );
+
+                        slack
+                            .open_session()
+                            .views_push(&view_push_request)
+                            .await
+                            .map_err(|slack_err| {
+                                anyhow!("Error showing rotated webhook secret: {}", slack_err)
+                            })?;
                     }
-                    BlockSectionRouter::PDEscalationPolicyInput => todo!(),
-                    BlockSectionRouter::PDThreadedMsgInput => todo!(),
-                    BlockSectionRouter::FwdMsgToChanChannelInput => todo!(),
-                    BlockSectionRouter::FwdMsgToChanMsgContextInput => todo!(),
+                    // inputs that don't change the view, their value is read on submission
+                    BlockSectionRouter::CollaboratorSelection
+                    | BlockSectionRouter::RunResponseSelection
+                    | BlockSectionRouter::ListenerChannelSelected
+                    | BlockSectionRouter::MessageConditionValueInput
+                    | BlockSectionRouter::JsonConditionPathInput
+                    | BlockSectionRouter::JsonConditionValueInput
+                    | BlockSectionRouter::AttachEmojiInput
+                    | BlockSectionRouter::ReplyThreadedMsgInput
+                    | BlockSectionRouter::PostChannelMsgInput
+                    | BlockSectionRouter::PDEscalationPolicyInput
+                    | BlockSectionRouter::PDEscalationPolicySelected
+                    | BlockSectionRouter::PDThreadedMsgInput
+                    | BlockSectionRouter::FwdMsgToChanChannelInput
+                    | BlockSectionRouter::FwdMsgToChanMsgContextInput
+                    | BlockSectionRouter::ListenerMultiChannelSelected
+                    | BlockSectionRouter::ListenerChannelPatternInput
+                    | BlockSectionRouter::ListenerCommandKeywordInput
+                    | BlockSectionRouter::CommandReplyMsgInput
+                    | BlockSectionRouter::MemberWelcomeMsgInput
+                    | BlockSectionRouter::ListenerScheduleCronInput
+                    | BlockSectionRouter::ListenerScheduleTimezoneInput
+                    | BlockSectionRouter::ListenerPdServiceInput
+                    | BlockSectionRouter::ChannelActionMsgInput
+                    | BlockSectionRouter::ChannelActionEscalationPolicyInput
+                    | BlockSectionRouter::BlocksJsonInput
+                    | BlockSectionRouter::SenderMsgInput
+                    | BlockSectionRouter::WebhookUrlInput
+                    | BlockSectionRouter::WebhookMethodSelected
+                    | BlockSectionRouter::WebhookHeadersInput
+                    | BlockSectionRouter::WebhookBodyInput
+                    | BlockSectionRouter::WebhookReplySelected
+                    | BlockSectionRouter::WorkflowUrlInput
+                    | BlockSectionRouter::WorkflowVariablesInput
+                    | BlockSectionRouter::PDRoutingKeyInput
+                    | BlockSectionRouter::PDSummaryInput
+                    | BlockSectionRouter::PDSeveritySelected
+                    | BlockSectionRouter::OncallMentionLevelSelected
+                    | BlockSectionRouter::OncallUnavailableSelected
+                    | BlockSectionRouter::OncallTopicLocationSelected
+                    | BlockSectionRouter::OncallTopicLabelInput
+                    | BlockSectionRouter::InviteUsergroupInput
+                    | BlockSectionRouter::RotationNameInput
+                    | BlockSectionRouter::RotationMembersSelected
+                    | BlockSectionRouter::RotationCadenceSelected
+                    | BlockSectionRouter::RotationStartDateSelected
+                    | BlockSectionRouter::RotationHandoffTimeInput
+                    | BlockSectionRouter::RotationTimezoneInput
+                    | BlockSectionRouter::RotationOverrideUserSelected
+                    | BlockSectionRouter::RotationOverrideStartSelected
+                    | BlockSectionRouter::RotationOverrideEndSelected
+                    | BlockSectionRouter::RotationOverrideRemoveSelected => (),
                 }
             }
             Ok(())
@@ -12,21 +12,75 @@ pub enum BlockSectionRouter {
     CollaboratorSelection,
 
     // Listener Section
+    ListenerTypeSelected,
     ListenerChannelSelected,
+    ListenerCommandKeywordInput,
+    ListenerMultiChannelSelected,
+    ListenerChannelPatternInput,
+    ListenerDmFallbackSelected,
+    ListenerWelcomeFrequencySelected,
+    ListenerWelcomeGuestsSelected,
+    ListenerScheduleCronInput,
+    ListenerScheduleTimezoneInput,
+    ListenerPdServiceInput,
+    WebhookSecretRotateClicked,
+    EditedMessagesSelected,
 
     // Condition Section
     ConditionTypeSelected,
     MessageConditionValueInput,
+    JsonConditionPathInput,
+    JsonConditionValueInput,
 
     // Action Section
     ActionTypeSelected,
     AttachEmojiInput,
     ReplyThreadedMsgInput,
     PostChannelMsgInput,
+    SenderMsgInput,
     PDEscalationPolicyInput,
+    PDEscalationPolicySelected,
     PDThreadedMsgInput,
     FwdMsgToChanChannelInput,
     FwdMsgToChanMsgContextInput,
+    CommandReplyMsgInput,
+    MemberWelcomeMsgInput,
+    ChannelActionMsgInput,
+    ChannelActionEscalationPolicyInput,
+    BlocksJsonInput,
+    WebhookUrlInput,
+    WebhookMethodSelected,
+    WebhookHeadersInput,
+    WebhookBodyInput,
+    WebhookReplySelected,
+    WorkflowUrlInput,
+    WorkflowVariablesInput,
+    PDRoutingKeyInput,
+    PDSummaryInput,
+    PDSeveritySelected,
+    OncallProviderSelected,
+    OncallMentionLevelSelected,
+    OncallUnavailableSelected,
+    OncallTopicLocationSelected,
+    OncallTopicLabelInput,
+    InviteeSelected,
+    InviteUsergroupInput,
+
+    // Rotation Section
+    RotationSelection,
+    RotationNameInput,
+    RotationMembersSelected,
+    RotationCadenceSelected,
+    RotationStartDateSelected,
+    RotationHandoffTimeInput,
+    RotationTimezoneInput,
+    RotationOverrideUserSelected,
+    RotationOverrideStartSelected,
+    RotationOverrideEndSelected,
+    RotationOverrideRemoveSelected,
+
+    // Run Response Shortcut
+    RunResponseSelection,
 }
 
 impl BlockSectionRouter {
@@ -1,15 +1,24 @@
 use super::BlockSectionRouter;
 #[cfg(any(feature = "tempdb", feature = "dynamodb"))]
-use crate::database::{create_response, delete_response, update_response};
+use crate::database::{
+    create_response, create_rotation, delete_response, delete_rotation, delete_webhook_secret,
+    get_all_rotations, get_webhook_secret, update_response, update_rotation,
+};
 use crate::{
-    manager_ui::MetaForManagerView, ManagerViewModes, MessageAction, ReceptionistAction,
-    ReceptionistResponse, SlackResponseAction, ViewBlockStateType,
+    config::get_or_init_app_config,
+    manager_ui::{webhook_secret_view, MetaForManagerView},
+    new_rotation_override, parse_mention_level, rotate_webhook_secret, ChannelAction,
+    CommandAction, ManagerViewModes, MemberAction, MessageAction, OncallTarget,
+    OncallTopicLocation, OncallUnavailableAction, PagerDutyIncidentTrigger, ReceptionistAction,
+    ReceptionistListener, ReceptionistResponse, Rotation, SlackBlockValidationError,
+    SlackResponseAction, ViewBlockStateType, WebhookRequest, WorkflowTrigger,
 };
 use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
 use serde_json::{from_str, from_value};
 use slack_morphism::prelude::*;
 use std::{collections::HashMap, str::FromStr};
-use tracing::info;
+use tracing::{info, warn};
 
 pub async fn process_submission_event(
     submission_event: SlackInteractionViewSubmissionEvent,
@@ -44,25 +53,29 @@ pub async fn process_submission_event(
                 ManagerViewModes::Home => Ok(None),
                 ManagerViewModes::CreateResponse => {
                     // get response info from view states
-                    match parsed_view.response.validate() {
+                    match validate_response(&parsed_view.response).await {
                         Some(validation_errors) => Ok(Some(
                             SlackResponseAction::from_validation_errors(validation_errors),
                         )),
                         None => {
+                            let response_action =
+                                sync_webhook_secret(&parsed_view.response).await?;
                             create_response(parsed_view.response).await?;
-                            Ok(None)
+                            Ok(response_action)
                         }
                     }
                 }
                 ManagerViewModes::EditResponse => {
                     // get response_id from selector
-                    match parsed_view.response.validate() {
+                    match validate_response(&parsed_view.response).await {
                         Some(validation_errors) => Ok(Some(
                             SlackResponseAction::from_validation_errors(validation_errors),
                         )),
                         None => {
+                            let response_action =
+                                sync_webhook_secret(&parsed_view.response).await?;
                             update_response(parsed_view.response).await?;
-                            Ok(None)
+                            Ok(response_action)
                         }
                     }
                 }
@@ -72,13 +85,140 @@ pub async fn process_submission_event(
                     delete_response(parsed_view.response).await?;
                     Ok(None)
                 }
+                ManagerViewModes::CreateRotation | ManagerViewModes::EditRotation => {
+                    let mut rotation = parsed_view.rotation;
+                    rotation.remove_expired_overrides(Utc::now());
+
+                    let mut validation_errors = rotation
+                        .validate(&get_all_rotations().await?)
+                        .unwrap_or_default();
+                    validation_errors.extend(parsed_view.rotation_override_error);
+
+                    if !validation_errors.is_empty() {
+                        return Ok(Some(SlackResponseAction::from_validation_errors(
+                            validation_errors,
+                        )));
+                    }
+
+                    if matches!(parsed_view.mode, ManagerViewModes::CreateRotation) {
+                        create_rotation(rotation).await?;
+                    } else {
+                        update_rotation(rotation).await?;
+                    }
+                    Ok(None)
+                }
+                ManagerViewModes::DeleteRotation => {
+                    delete_rotation(&parsed_view.rotation.id).await?;
+                    Ok(None)
+                }
             };
         }
     }
 }
 
+/// A Response saved as a webhook for the first time gets a signing secret, shown once in the view that replaces the manager.
+/// The secret is dropped when the Response stops being a webhook
+async fn sync_webhook_secret(
+    response: &ReceptionistResponse,
+) -> Result<Option<SlackResponseAction>> {
+    if !matches!(response.listener, ReceptionistListener::Webhook { .. }) {
+        delete_webhook_secret(&response.id).await?;
+        return Ok(None);
+    }
+
+    if get_webhook_secret(&response.id).await?.is_some() {
+        return Ok(None);
+    }
+
+    let secret = rotate_webhook_secret(&response.id).await?;
+
+    Ok(Some(SlackResponseAction::Update {
+        view: webhook_secret_view(&response.id, Some(&secret)),
+    }))
+}
+
+/// PagerDuty targets are only looked up once everything else is valid, to keep the submission fast
+async fn validate_response(
+    response: &ReceptionistResponse,
+) -> Option<Vec<SlackBlockValidationError>> {
+    match response.validate() {
+        Some(validation_errors) => Some(validation_errors),
+        None => validate_pagerduty_targets(response).await,
+    }
+}
+
+/// Confirm the escalation policies and schedules of PagerDuty oncall actions exist, so typos
+/// are caught when saving instead of when the action runs. Lookup failures don't block saving
+async fn validate_pagerduty_targets(
+    response: &ReceptionistResponse,
+) -> Option<Vec<SlackBlockValidationError>> {
+    let pagerduty = get_or_init_app_config().await.pagerduty_config.as_ref()?;
+    let mut validation_errors = Vec::new();
+
+    for (index, action) in response.actions.iter().enumerate() {
+        let target = match action {
+            ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread { target, .. })
+            | ReceptionistAction::ForMessage(MessageAction::InviteOncallToChannel {
+                target,
+                usergroup_id: None,
+                ..
+            })
+            | ReceptionistAction::ForChannel(ChannelAction::OncallMessage { target, .. })
+            | ReceptionistAction::ForChannel(ChannelAction::OncallTopic { target, .. }) => {
+                match target.to_pagerduty_target() {
+                    Some(target) if !target.id().is_empty() => target,
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        };
+
+        match pagerduty.oncall_target_exists(&target).await {
+            Ok(true) => (),
+            Ok(false) => validation_errors.push(SlackBlockValidationError {
+                block_id: BlockSectionRouter::PDEscalationPolicySelected.to_block_id(Some(index)),
+                error_message: format!(
+                    "PagerDuty has no {} with id {}",
+                    target.kind_description(),
+                    target.id()
+                ),
+            }),
+            Err(e) => warn!("Unable to check PagerDuty {target} exists: {e}"),
+        }
+    }
+
+    if validation_errors.is_empty() {
+        None
+    } else {
+        Some(validation_errors)
+    }
+}
+
+fn webhook_request_mut(action: &mut ReceptionistAction) -> Result<&mut WebhookRequest> {
+    match action {
+        ReceptionistAction::ForMessage(MessageAction::Webhook(webhook)) => Ok(webhook),
+        _ => bail!("wrong action type for webhook input"),
+    }
+}
+
+fn workflow_trigger_mut(action: &mut ReceptionistAction) -> Result<&mut WorkflowTrigger> {
+    match action {
+        ReceptionistAction::ForMessage(MessageAction::TriggerWorkflow(trigger)) => Ok(trigger),
+        _ => bail!("wrong action type for workflow input"),
+    }
+}
+
+fn pagerduty_trigger_mut(action: &mut ReceptionistAction) -> Result<&mut PagerDutyIncidentTrigger> {
+    match action {
+        ReceptionistAction::ForMessage(MessageAction::TriggerPagerDutyIncident(trigger)) => {
+            Ok(trigger)
+        }
+        _ => bail!("wrong action type for pagerduty incident input"),
+    }
+}
+
 /// HashMap<ActionBlockId, ViewBlockStateType>
-fn extract_action_block_states(
+pub(crate) fn extract_action_block_states(
     view_state: SlackViewState,
 ) -> Result<HashMap<String, ViewBlockStateType>> {
     let mut block_state_map: HashMap<String, ViewBlockStateType> = HashMap::new();
@@ -107,6 +247,9 @@ pub struct ParsedManagerViewSubmission {
     pub mode: ManagerViewModes,
     pub selected_response_id: Option<String>,
     pub response: ReceptionistResponse,
+    pub rotation: Rotation,
+    /// an incomplete override can't be stored on the rotation, so it is reported separately
+    pub rotation_override_error: Option<SlackBlockValidationError>,
 }
 
 fn parse_manager_block_states(
@@ -118,6 +261,8 @@ fn parse_manager_block_states(
         mode: private_metadata.current_mode.to_owned(),
         response: private_metadata.response.unwrap_or_default(),
         selected_response_id: None,
+        rotation: private_metadata.rotation.unwrap_or_default(),
+        rotation_override_error: None,
     };
 
     // exit early if home view, no saving to database
@@ -126,6 +271,10 @@ fn parse_manager_block_states(
         return Ok(parsed_submission);
     }
 
+    let mut override_user = None;
+    let mut override_start = None;
+    let mut override_end = None;
+
     for (action_id_str, block_state) in block_id_map {
         let (route, index_result) = BlockSectionRouter::from_string_with_index(action_id_str)
             .ok_or_else(|| anyhow!("route not found"))?;
@@ -144,12 +293,65 @@ fn parse_manager_block_states(
                     index_result?,
                 )?
             }
+            BlockSectionRouter::ListenerTypeSelected => parsed_submission
+                .response
+                .update_listener_type(&block_state.get_value_from_static_select()?)?,
             BlockSectionRouter::ListenerChannelSelected => {
-                if let Some(channel_id) = block_state.get_conversation_select_value()? {
-                    parsed_submission
-                        .response
-                        .update_slack_channel(channel_id.to_string())?;
-                }
+                parsed_submission.response.update_slack_channel(
+                    block_state
+                        .get_conversation_select_value()?
+                        .map(|channel_id| channel_id.to_string()),
+                )?;
+            }
+            BlockSectionRouter::ListenerMultiChannelSelected => {
+                parsed_submission.response.update_slack_channels(
+                    block_state
+                        .get_multi_conversations_select_value()?
+                        .iter()
+                        .map(|channel_id| channel_id.to_string())
+                        .collect(),
+                )?;
+            }
+            BlockSectionRouter::ListenerChannelPatternInput => parsed_submission
+                .response
+                .update_channel_pattern(block_state.get_plain_text_value()?)?,
+            BlockSectionRouter::ListenerDmFallbackSelected => parsed_submission
+                .response
+                .update_dm_fallback(&block_state.get_value_from_static_select()?)?,
+            BlockSectionRouter::ListenerWelcomeFrequencySelected => parsed_submission
+                .response
+                .update_welcome_frequency(&block_state.get_value_from_static_select()?)?,
+            BlockSectionRouter::ListenerWelcomeGuestsSelected => parsed_submission
+                .response
+                .update_welcome_guests(&block_state.get_value_from_static_select()?)?,
+            BlockSectionRouter::EditedMessagesSelected => parsed_submission
+                .response
+                .update_run_on_edits(&block_state.get_value_from_static_select()?)?,
+            BlockSectionRouter::ListenerScheduleCronInput => parsed_submission
+                .response
+                .update_schedule_cron(block_state.get_plain_text_value()?)?,
+            BlockSectionRouter::ListenerScheduleTimezoneInput => parsed_submission
+                .response
+                .update_schedule_timezone(block_state.get_plain_text_value()?)?,
+            BlockSectionRouter::ListenerPdServiceInput => parsed_submission
+                .response
+                .update_pagerduty_service(block_state.get_plain_text_value()?)?,
+            // buttons have no state, the secret is rotated when it's clicked
+            BlockSectionRouter::WebhookSecretRotateClicked => (),
+            BlockSectionRouter::ListenerCommandKeywordInput => parsed_submission
+                .response
+                .update_command_keyword(block_state.get_plain_text_value()?)?,
+            BlockSectionRouter::JsonConditionPathInput => {
+                parsed_submission.response.update_json_condition_path(
+                    block_state.get_plain_text_value()?,
+                    index_result?,
+                )?;
+            }
+            BlockSectionRouter::JsonConditionValueInput => {
+                parsed_submission.response.update_json_condition_value(
+                    block_state.get_plain_text_value()?,
+                    index_result?,
+                )?;
             }
             BlockSectionRouter::MessageConditionValueInput => {
                 parsed_submission.response.update_message_condition_string(
@@ -169,6 +371,7 @@ fn parse_manager_block_states(
                             _ => bail!("wrong action type for emoji input"),
                         };
                     }
+                    _ => bail!("not a message action"),
                 }
             }
 
@@ -195,6 +398,7 @@ fn parse_manager_block_states(
                             _ => bail!("wrong action type for emoji input"),
                         };
                     }
+                    _ => bail!("not a message action"),
                 }
             }
             BlockSectionRouter::PostChannelMsgInput => {
@@ -209,41 +413,60 @@ fn parse_manager_block_states(
                             _ => bail!("wrong action type for emoji input"),
                         };
                     }
+                    _ => bail!("not a message action"),
                 }
             }
-            BlockSectionRouter::PDEscalationPolicyInput => {
+            BlockSectionRouter::SenderMsgInput => {
                 let action = parsed_submission.response.get_action_mut(index_result?)?;
 
                 match action {
                     ReceptionistAction::ForMessage(msg_action) => {
                         *msg_action = match msg_action {
-                            MessageAction::MsgOncallInThread { message, .. } => {
-                                MessageAction::MsgOncallInThread {
-                                    escalation_policy_id: block_state.get_plain_text_value()?,
-                                    message: std::mem::take(message),
-                                }
+                            MessageAction::EphemeralToSender(_) => {
+                                MessageAction::EphemeralToSender(
+                                    block_state.get_plain_text_value()?,
+                                )
                             }
-                            _ => bail!("wrong action type for emoji input"),
+                            MessageAction::DirectMessageToSender(_) => {
+                                MessageAction::DirectMessageToSender(
+                                    block_state.get_plain_text_value()?,
+                                )
+                            }
+                            _ => bail!("wrong action type for sender message input"),
                         };
                     }
+                    _ => bail!("not a message action"),
                 }
             }
-            BlockSectionRouter::PDThreadedMsgInput => {
+            BlockSectionRouter::PDEscalationPolicyInput => {
                 let action = parsed_submission.response.get_action_mut(index_result?)?;
 
                 match action {
-                    ReceptionistAction::ForMessage(msg_action) => {
-                        *msg_action = match msg_action {
-                            MessageAction::MsgOncallInThread {
-                                escalation_policy_id,
-                                ..
-                            } => MessageAction::MsgOncallInThread {
-                                escalation_policy_id: std::mem::take(escalation_policy_id),
-                                message: block_state.get_plain_text_value()?,
-                            },
-                            _ => bail!("wrong action type for emoji input"),
-                        };
+                    ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread {
+                        target,
+                        ..
+                    })
+                    | ReceptionistAction::ForMessage(MessageAction::InviteOncallToChannel {
+                        target,
+                        ..
+                    }) => {
+                        *target = OncallTarget::new(
+                            target.provider(),
+                            &block_state.get_plain_text_value()?,
+                        )
                     }
+                    _ => bail!("wrong action type for escalation policy input"),
+                }
+            }
+            BlockSectionRouter::PDThreadedMsgInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread {
+                        message,
+                        ..
+                    }) => *message = block_state.get_plain_text_value()?,
+                    _ => bail!("wrong action type for oncall message input"),
                 }
             }
             BlockSectionRouter::FwdMsgToChanChannelInput => {
@@ -267,12 +490,319 @@ fn parse_manager_block_states(
                             _ => bail!("wrong action type for Forward Message - Channel Input"),
                         };
                     }
+                    _ => bail!("not a message action"),
+                }
+            }
+            BlockSectionRouter::FwdMsgToChanMsgContextInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForMessage(MessageAction::ForwardMessageToChannel {
+                        msg_context,
+                        ..
+                    }) => *msg_context = block_state.get_plain_text_value()?,
+                    _ => bail!("wrong action type for forward message context input"),
+                }
+            }
+            BlockSectionRouter::CommandReplyMsgInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForCommand(command_action) => {
+                        *command_action = match command_action {
+                            CommandAction::EphemeralReply(_) => {
+                                CommandAction::EphemeralReply(block_state.get_plain_text_value()?)
+                            }
+                            CommandAction::ChannelReply(_) => {
+                                CommandAction::ChannelReply(block_state.get_plain_text_value()?)
+                            }
+                        };
+                    }
+                    _ => bail!("wrong action type for command reply input"),
                 }
             }
-            BlockSectionRouter::FwdMsgToChanMsgContextInput => todo!(),
+            BlockSectionRouter::MemberWelcomeMsgInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForMember(member_action) => {
+                        *member_action = match member_action {
+                            MemberAction::EphemeralWelcome(_) => {
+                                MemberAction::EphemeralWelcome(block_state.get_plain_text_value()?)
+                            }
+                            MemberAction::DirectMessageWelcome(_) => {
+                                MemberAction::DirectMessageWelcome(
+                                    block_state.get_plain_text_value()?,
+                                )
+                            }
+                        };
+                    }
+                    _ => bail!("wrong action type for welcome message input"),
+                }
+            }
+            BlockSectionRouter::ChannelActionMsgInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForChannel(channel_action) => {
+                        *channel_action = match channel_action {
+                            ChannelAction::ChannelMessage(_) => {
+                                ChannelAction::ChannelMessage(block_state.get_plain_text_value()?)
+                            }
+                            ChannelAction::OncallMessage {
+                                target,
+                                target_name,
+                                ..
+                            } => ChannelAction::OncallMessage {
+                                target: std::mem::take(target),
+                                target_name: std::mem::take(target_name),
+                                message: block_state.get_plain_text_value()?,
+                            },
+                            ChannelAction::ChannelBlocks(_) => {
+                                bail!("block kit actions are edited through the blocks json input")
+                            }
+                            ChannelAction::OncallTopic { .. } => {
+                                bail!("oncall topic actions have no message")
+                            }
+                        };
+                    }
+                    _ => bail!("wrong action type for channel message input"),
+                }
+            }
+            BlockSectionRouter::ChannelActionEscalationPolicyInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForChannel(ChannelAction::OncallMessage {
+                        target, ..
+                    })
+                    | ReceptionistAction::ForChannel(ChannelAction::OncallTopic {
+                        target, ..
+                    }) => {
+                        *target = OncallTarget::new(
+                            target.provider(),
+                            &block_state.get_plain_text_value()?,
+                        )
+                    }
+                    _ => bail!("wrong action type for escalation policy input"),
+                }
+            }
+            BlockSectionRouter::OncallProviderSelected => parsed_submission
+                .response
+                .get_action_mut(index_result?)?
+                .update_oncall_provider(&block_state.get_value_from_static_select()?)?,
+            BlockSectionRouter::PDEscalationPolicySelected => parsed_submission
+                .response
+                .get_action_mut(index_result?)?
+                .update_pagerduty_target(block_state.get_external_select_option()?)?,
+            BlockSectionRouter::OncallMentionLevelSelected => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread {
+                        mention_level,
+                        ..
+                    }) => {
+                        *mention_level =
+                            parse_mention_level(&block_state.get_value_from_static_select()?)?
+                    }
+                    _ => bail!("wrong action type for oncall mention level selection"),
+                }
+            }
+            BlockSectionRouter::OncallUnavailableSelected => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread {
+                        unavailable_action,
+                        ..
+                    }) => {
+                        *unavailable_action = OncallUnavailableAction::from_str(
+                            &block_state.get_value_from_static_select()?,
+                        )?
+                    }
+                    _ => bail!("wrong action type for oncall unavailable selection"),
+                }
+            }
+            BlockSectionRouter::OncallTopicLocationSelected => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForChannel(ChannelAction::OncallTopic {
+                        location, ..
+                    }) => {
+                        *location = OncallTopicLocation::from_str(
+                            &block_state.get_value_from_static_select()?,
+                        )?
+                    }
+                    _ => bail!("wrong action type for oncall topic location selection"),
+                }
+            }
+            BlockSectionRouter::InviteeSelected => parsed_submission
+                .response
+                .get_action_mut(index_result?)?
+                .update_invitee(&block_state.get_value_from_static_select()?)?,
+            BlockSectionRouter::InviteUsergroupInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForMessage(MessageAction::InviteOncallToChannel {
+                        usergroup_id,
+                        ..
+                    }) => {
+                        *usergroup_id = Some(block_state.get_plain_text_value()?.trim().to_owned())
+                    }
+                    _ => bail!("wrong action type for user group input"),
+                }
+            }
+            BlockSectionRouter::OncallTopicLabelInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+
+                match action {
+                    ReceptionistAction::ForChannel(ChannelAction::OncallTopic {
+                        label, ..
+                    }) => *label = block_state.get_plain_text_value()?,
+                    _ => bail!("wrong action type for oncall topic label input"),
+                }
+            }
+            BlockSectionRouter::BlocksJsonInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                let blocks_json = block_state.get_plain_text_value()?;
+
+                match action {
+                    ReceptionistAction::ForMessage(msg_action) => {
+                        *msg_action = match msg_action {
+                            MessageAction::ThreadedBlocks(_) => {
+                                MessageAction::ThreadedBlocks(blocks_json)
+                            }
+                            MessageAction::ChannelBlocks(_) => {
+                                MessageAction::ChannelBlocks(blocks_json)
+                            }
+                            _ => bail!("wrong action type for blocks input"),
+                        };
+                    }
+                    ReceptionistAction::ForChannel(channel_action) => {
+                        *channel_action = match channel_action {
+                            ChannelAction::ChannelBlocks(_) => {
+                                ChannelAction::ChannelBlocks(blocks_json)
+                            }
+                            _ => bail!("wrong action type for blocks input"),
+                        };
+                    }
+                    _ => bail!("not a message or channel action"),
+                }
+            }
+            BlockSectionRouter::WebhookUrlInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                webhook_request_mut(action)?.url =
+                    block_state.get_plain_text_value()?.trim().to_string();
+            }
+            BlockSectionRouter::WebhookMethodSelected => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                webhook_request_mut(action)?
+                    .update_method(&block_state.get_value_from_static_select()?)?;
+            }
+            BlockSectionRouter::WebhookHeadersInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                webhook_request_mut(action)?.headers = block_state.get_plain_text_value()?;
+            }
+            BlockSectionRouter::WebhookBodyInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                webhook_request_mut(action)?.body = block_state.get_plain_text_value()?;
+            }
+            BlockSectionRouter::WebhookReplySelected => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                webhook_request_mut(action)?
+                    .update_reply_in_thread(&block_state.get_value_from_static_select()?)?;
+            }
+            BlockSectionRouter::WorkflowUrlInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                workflow_trigger_mut(action)?.url =
+                    block_state.get_plain_text_value()?.trim().to_string();
+            }
+            BlockSectionRouter::WorkflowVariablesInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                workflow_trigger_mut(action)?.variables = block_state.get_plain_text_value()?;
+            }
+            BlockSectionRouter::PDRoutingKeyInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                pagerduty_trigger_mut(action)?.routing_key =
+                    block_state.get_plain_text_value()?.trim().to_string();
+            }
+            BlockSectionRouter::PDSummaryInput => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                pagerduty_trigger_mut(action)?.summary = block_state.get_plain_text_value()?;
+            }
+            BlockSectionRouter::PDSeveritySelected => {
+                let action = parsed_submission.response.get_action_mut(index_result?)?;
+                pagerduty_trigger_mut(action)?
+                    .update_severity(&block_state.get_value_from_static_select()?)?;
+            }
+            BlockSectionRouter::RotationSelection => {
+                parsed_submission.rotation.id = block_state.get_value_from_static_select()?
+            }
+            BlockSectionRouter::RotationNameInput => {
+                parsed_submission.rotation.name =
+                    block_state.get_plain_text_value()?.trim().to_string()
+            }
+            BlockSectionRouter::RotationMembersSelected => {
+                parsed_submission.rotation.members = block_state.get_multi_users_select_value()?
+            }
+            BlockSectionRouter::RotationCadenceSelected => parsed_submission
+                .rotation
+                .update_cadence(&block_state.get_value_from_static_select()?)?,
+            BlockSectionRouter::RotationStartDateSelected => {
+                parsed_submission.rotation.start_date =
+                    block_state.get_datepicker_value()?.unwrap_or_default()
+            }
+            BlockSectionRouter::RotationHandoffTimeInput => {
+                parsed_submission.rotation.handoff_time =
+                    block_state.get_plain_text_value()?.trim().to_string()
+            }
+            BlockSectionRouter::RotationTimezoneInput => {
+                parsed_submission.rotation.timezone =
+                    block_state.get_plain_text_value()?.trim().to_string()
+            }
+            BlockSectionRouter::RotationOverrideUserSelected => {
+                override_user = block_state.get_users_select_value()?
+            }
+            BlockSectionRouter::RotationOverrideStartSelected => {
+                override_start = block_state.get_datepicker_value()?
+            }
+            BlockSectionRouter::RotationOverrideEndSelected => {
+                override_end = block_state.get_datepicker_value()?
+            }
+            BlockSectionRouter::RotationOverrideRemoveSelected => parsed_submission
+                .rotation
+                .remove_overrides(&block_state.get_multi_static_select_values()?)?,
+            BlockSectionRouter::RunResponseSelection => {
+                bail!("run response selection is not part of the manager")
+            }
+        }
+    }
+
+    match new_rotation_override(override_user, override_start, override_end) {
+        Ok(Some(rotation_override)) => parsed_submission.rotation.overrides.push(rotation_override),
+        Ok(None) => (),
+        Err(e) => {
+            parsed_submission.rotation_override_error = Some(SlackBlockValidationError {
+                block_id: BlockSectionRouter::RotationOverrideStartSelected.to_block_id(None),
+                error_message: e.to_string(),
+            })
         }
     }
 
+    if !parsed_submission
+        .rotation
+        .collaborators
+        .contains(&user_id.into())
+    {
+        parsed_submission
+            .rotation
+            .collaborators
+            .push(user_id.to_owned())
+    }
+
     if !parsed_submission
         .response
         .collaborators
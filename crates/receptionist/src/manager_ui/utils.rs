@@ -1,6 +1,6 @@
 #[cfg(any(feature = "tempdb", feature = "dynamodb"))]
-use crate::database::get_responses_for_collaborator;
-use crate::{BlockSectionRouter, ReceptionistResponse};
+use crate::database::{get_responses_for_collaborator, get_rotations_for_collaborator};
+use crate::{BlockSectionRouter, ReceptionistResponse, Rotation};
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::to_string;
@@ -13,6 +13,9 @@ pub struct MetaForManagerView {
     pub user_id: String,
     pub current_mode: ManagerViewModes,
     pub response: Option<ReceptionistResponse>,
+    /// rotation being edited in the rotation modes
+    #[serde(default)]
+    pub rotation: Option<Rotation>,
 }
 
 impl MetaForManagerView {
@@ -22,11 +25,20 @@ impl MetaForManagerView {
             ManagerViewModes::CreateResponse => Some(ReceptionistResponse::default()),
             ManagerViewModes::EditResponse => None,
             ManagerViewModes::DeleteResponse => None,
+            ManagerViewModes::CreateRotation
+            | ManagerViewModes::EditRotation
+            | ManagerViewModes::DeleteRotation => None,
+        };
+
+        let rotation = match current_mode {
+            ManagerViewModes::CreateRotation => Some(Rotation::default()),
+            _ => None,
         };
 
         Self {
             current_mode,
             response,
+            rotation,
             user_id,
         }
     }
@@ -37,6 +49,7 @@ impl Default for MetaForManagerView {
         Self {
             current_mode: ManagerViewModes::Home,
             response: None,
+            rotation: None,
             user_id: "".to_string(),
         }
     }
@@ -62,6 +75,26 @@ fn manager_view_wrapper(blocks: Vec<SlackBlock>, meta: &MetaForManagerView) -> S
     )
 }
 
+/// Shows a webhook Response's signing secret, the only place it's shown after it's created or rotated
+pub fn webhook_secret_view(response_id: &str, secret: Option<&str>) -> SlackView {
+    let blocks = match secret {
+        Some(secret) => slack_blocks![
+            some_into(SlackSectionBlock::new().with_text(md!(
+                "Sign requests to `POST /hooks/{}` with this secret. It won't be shown again, rotate it from the Response editor if it's lost",
+                response_id
+            ))),
+            some_into(SlackSectionBlock::new().with_text(md!("```{}```", secret)))
+        ],
+        None => slack_blocks![some_into(SlackSectionBlock::new().with_text(pt!(
+            "The signing secret is created and shown when this Response is saved as a webhook"
+        )))],
+    };
+
+    SlackView::Modal(
+        SlackModalView::new("Webhook Signing Secret".into(), blocks).opt_close(Some("Done".into())),
+    )
+}
+
 pub async fn new_manager_view(meta: &MetaForManagerView) -> SlackView {
     let mut blocks: Vec<SlackBlock> = meta.current_mode.to_editor_blocks();
 
@@ -82,6 +115,17 @@ pub async fn new_manager_view(meta: &MetaForManagerView) -> SlackView {
             editing_blocks
         }
         ManagerViewModes::DeleteResponse => response_selector_blocks(&meta.user_id).await,
+        ManagerViewModes::CreateRotation => {
+            meta.rotation.clone().unwrap_or_default().to_editor_blocks()
+        }
+        ManagerViewModes::EditRotation => {
+            let mut editing_blocks = rotation_selector_blocks(&meta.user_id).await;
+            if let Some(rotation) = &meta.rotation {
+                editing_blocks.extend(rotation.to_editor_blocks())
+            }
+            editing_blocks
+        }
+        ManagerViewModes::DeleteRotation => rotation_selector_blocks(&meta.user_id).await,
     };
 
     blocks.extend(extra_blocks);
@@ -124,6 +168,37 @@ async fn response_selector_blocks(user_id: &str) -> Vec<SlackBlock> {
     ]
 }
 
+async fn rotation_selector_blocks(user_id: &str) -> Vec<SlackBlock> {
+    let rotations = get_rotations_for_collaborator(user_id)
+        .await
+        .expect("error getting rotations");
+
+    if rotations.is_empty() {
+        return slack_blocks![some_into(SlackSectionBlock::new().with_text(pt!(
+            "You are not collaborator on any rotations, please create a new rotation or ask another user to add you to an existing rotation."
+        )))];
+    }
+
+    let static_selector = SlackBlockStaticSelectElement::new(
+        BlockSectionRouter::RotationSelection.to_action_id(None),
+        pt!("Select one of your rotations"),
+    )
+    .with_options(rotations.iter().map(Rotation::to_choice_item).collect());
+
+    slack_blocks![
+        some_into(
+            SlackInputBlock::new(
+                SlackBlockPlainTextOnly::from("Select one of your rotations"),
+                SlackInputBlockElement::StaticSelect(static_selector)
+            )
+            .with_dispatch_action(true)
+            .without_optional()
+            .with_block_id(BlockSectionRouter::RotationSelection.to_block_id(None))
+        ),
+        some_into(SlackDividerBlock::new())
+    ]
+}
+
 #[derive(
     EnumDiscriminants,
     EnumIter,
@@ -142,6 +217,9 @@ pub enum ManagerViewModes {
     CreateResponse,
     EditResponse,
     DeleteResponse,
+    CreateRotation,
+    EditRotation,
+    DeleteRotation,
 }
 
 impl Default for ManagerViewModes {
@@ -157,6 +235,9 @@ impl ManagerViewModes {
             ManagerViewModes::CreateResponse => "Create a Receptionist Response",
             ManagerViewModes::EditResponse => "Edit an existing Response",
             ManagerViewModes::DeleteResponse => "Delete an existing Response",
+            ManagerViewModes::CreateRotation => "Create an oncall Rotation",
+            ManagerViewModes::EditRotation => "Edit an existing Rotation",
+            ManagerViewModes::DeleteRotation => "Delete an existing Rotation",
         };
 
         SlackBlockChoiceItem::new(pt!(description), self.to_string())
@@ -0,0 +1,371 @@
+//! Short lived cache of oncall lookups, so a channel firing repeatedly during an incident
+//! doesn't spend PagerDuty/Opsgenie and Slack rate limits on the same answers.
+//!
+//! The cache lives in the memory of each instance (server process or lambda) and is never shared.
+//! Clears are recorded in the database instead, and every instance applies them to its own cache
+//! the next time it looks up an oncall, see `OncallCache::sync_invalidations`
+#[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+use crate::database::{get_oncall_cache_invalidations, record_oncall_cache_invalidation};
+use crate::{config::get_or_init_app_config, OncallProvider, OncallTarget, OncallUser};
+use anyhow::Result;
+use chrono::Utc;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::OnceCell;
+use tracing::warn;
+
+/// How often an instance reads the clears recorded by other instances
+const INVALIDATION_SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+pub static ONCALL_CACHE: OnceCell<OncallCache> = OnceCell::const_new();
+pub async fn get_or_init_oncall_cache() -> &'static OncallCache {
+    ONCALL_CACHE
+        .get_or_init(|| async {
+            let config = get_or_init_app_config().await;
+            OncallCache::new(config.oncall_cache_ttl, config.slack_user_cache_ttl)
+        })
+        .await
+}
+
+/// Counters since startup, entries are the current size of the cache
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub entries: usize,
+}
+
+/// String keyed cache whose entries expire `ttl` after being inserted
+#[derive(Debug)]
+pub struct TtlCache<V: Clone> {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some((inserted, value)) if inserted.elapsed() < self.ttl => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value.clone())
+            }
+            expired => {
+                if expired.is_some() {
+                    entries.remove(key);
+                }
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn insert(&self, key: String, value: V) {
+        // a zero ttl disables the cache
+        if self.ttl.is_zero() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (inserted, _)| inserted.elapsed() < self.ttl);
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Remove every entry whose key matches, returns how many were removed
+    pub fn invalidate_where(&self, matches: impl Fn(&str) -> bool) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|key, _| !matches(key));
+
+        let removed = before - entries.len();
+        self.invalidations
+            .fetch_add(removed as u64, Ordering::Relaxed);
+        removed
+    }
+
+    pub fn clear(&self) -> usize {
+        self.invalidate_where(|_| true)
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+}
+
+/// Oncall users by provider and target, and Slack user ids by email, for this instance only
+#[derive(Debug)]
+pub struct OncallCache {
+    pub oncalls: TtlCache<Vec<OncallUser>>,
+    pub slack_users: TtlCache<String>,
+    /// Unix millis of the latest recorded clear applied here, starts at the instance's start
+    invalidations_synced_at: AtomicI64,
+    last_invalidation_sync: Mutex<Option<Instant>>,
+}
+
+impl OncallCache {
+    pub fn new(oncall_ttl: Duration, slack_user_ttl: Duration) -> Self {
+        Self {
+            oncalls: TtlCache::new(oncall_ttl),
+            slack_users: TtlCache::new(slack_user_ttl),
+            invalidations_synced_at: AtomicI64::new(Utc::now().timestamp_millis()),
+            last_invalidation_sync: Mutex::new(None),
+        }
+    }
+
+    /// PagerDuty schedules keep their `schedule:` prefix so they can't share a key with a policy
+    fn oncalls_key(target: &OncallTarget) -> String {
+        let id = match target.to_pagerduty_target() {
+            Some(pagerduty_target) => pagerduty_target.to_string(),
+            None => target.id().trim().to_string(),
+        };
+        format!("{}/{id}", target.provider())
+    }
+
+    /// Oncall users from the cache, asking the provider on a miss. Rotations are stored by
+    /// Receptionist and can change at any time, so they're never cached
+    pub async fn get_oncall_users(
+        &self,
+        provider: &dyn OncallProvider,
+        target: &OncallTarget,
+    ) -> Result<Vec<OncallUser>> {
+        if matches!(target, OncallTarget::Rotation(_)) {
+            return provider.get_oncall_users(target).await;
+        }
+
+        let key = Self::oncalls_key(target);
+        if let Some(oncall_users) = self.oncalls.get(&key) {
+            return Ok(oncall_users);
+        }
+
+        let oncall_users = provider.get_oncall_users(target).await?;
+        self.oncalls.insert(key, oncall_users.clone());
+        Ok(oncall_users)
+    }
+
+    /// Forget oncall results for a target (from every provider), or everything cached when `None`
+    pub fn invalidate(&self, target: Option<&str>) -> usize {
+        match target {
+            Some(target) => {
+                let target = target.trim();
+                self.oncalls.invalidate_where(|key| {
+                    key.split_once('/')
+                        .map_or(false, |(_provider, key_target)| key_target == target)
+                })
+            }
+            None => self.oncalls.clear() + self.slack_users.clear(),
+        }
+    }
+
+    /// Apply `(target, unix millis)` clears recorded by any instance that weren't applied here yet,
+    /// returns how many entries were removed
+    pub fn apply_invalidations(&self, invalidations: &[(Option<String>, i64)]) -> usize {
+        let synced_at = self.invalidations_synced_at.load(Ordering::Relaxed);
+
+        let mut removed = 0;
+        for (target, invalidated_at) in invalidations {
+            if *invalidated_at > synced_at {
+                removed += self.invalidate(target.as_deref());
+                self.invalidations_synced_at
+                    .fetch_max(*invalidated_at, Ordering::Relaxed);
+            }
+        }
+        removed
+    }
+
+    /// Apply the clears recorded by other instances, reading them at most every `INVALIDATION_SYNC_INTERVAL`
+    #[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+    pub async fn sync_invalidations(&self) {
+        {
+            let mut last_sync = self.last_invalidation_sync.lock().unwrap();
+            if last_sync.map_or(false, |synced| {
+                synced.elapsed() < INVALIDATION_SYNC_INTERVAL
+            }) {
+                return;
+            }
+            *last_sync = Some(Instant::now());
+        }
+
+        let since = self.invalidations_synced_at.load(Ordering::Relaxed);
+        match get_oncall_cache_invalidations(since).await {
+            Ok(invalidations) => {
+                self.apply_invalidations(&invalidations);
+            }
+            Err(e) => warn!("Unable to read oncall cache clears: {e}"),
+        }
+    }
+
+    /// Clear this instance's cache now and record the clear so the other instances apply it on their next sync.
+    /// Returns how many entries were removed here
+    #[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+    pub async fn invalidate_everywhere(&self, target: Option<&str>) -> Result<usize> {
+        let target = target.map(str::trim);
+        record_oncall_cache_invalidation(target, Utc::now().timestamp_millis()).await?;
+        Ok(self.invalidate(target))
+    }
+
+    pub fn get_slack_user(&self, email: &str) -> Option<String> {
+        self.slack_users.get(&email.to_lowercase())
+    }
+
+    pub fn insert_slack_user(&self, email: &str, slack_user_id: String) {
+        self.slack_users.insert(email.to_lowercase(), slack_user_id)
+    }
+
+    /// One line summary of this instance's cache for the cache command and logs
+    pub fn describe_metrics(&self) -> String {
+        let describe = |name: &str, metrics: CacheMetrics| {
+            format!(
+                "{name}: {} cached, {} hits, {} misses, {} invalidated",
+                metrics.entries, metrics.hits, metrics.misses, metrics.invalidations
+            )
+        };
+
+        format!(
+            "{} | {}",
+            describe("oncalls", self.oncalls.metrics()),
+            describe("slack users", self.slack_users.metrics())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingProvider {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl OncallProvider for CountingProvider {
+        async fn get_oncall_users(&self, target: &OncallTarget) -> Result<Vec<OncallUser>> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(vec![OncallUser {
+                name: target.id().to_string(),
+                email: Some(format!("{}@example.com", target.id())),
+                slack_user_id: None,
+                escalation_level: 1,
+                profile_url: None,
+            }])
+        }
+    }
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = TtlCache::new(Duration::from_millis(20));
+        cache.insert("key".to_string(), 1);
+
+        assert_eq!(cache.get("key"), Some(1));
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get("key"), None);
+
+        assert_eq!(
+            cache.metrics(),
+            CacheMetrics {
+                hits: 1,
+                misses: 1,
+                invalidations: 0,
+                entries: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn zero_ttl_disables_cache() {
+        let cache = TtlCache::new(Duration::ZERO);
+        cache.insert("key".to_string(), 1);
+
+        assert_eq!(cache.get("key"), None);
+    }
+
+    #[tokio::test]
+    async fn caches_oncalls_until_invalidated() {
+        let cache = OncallCache::new(Duration::from_secs(60), Duration::from_secs(60));
+        let provider = CountingProvider {
+            calls: AtomicUsize::new(0),
+        };
+        let policy = OncallTarget::PdEscalationPolicy("PPOLICY".to_string());
+
+        for _ in 0..3 {
+            cache.get_oncall_users(&provider, &policy).await.unwrap();
+        }
+        assert_eq!(provider.calls.load(Ordering::Relaxed), 1);
+
+        cache
+            .get_oncall_users(
+                &provider,
+                &OncallTarget::PdEscalationPolicy("POTHER".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(cache.invalidate(Some("PPOLICY")), 1);
+
+        cache.get_oncall_users(&provider, &policy).await.unwrap();
+        assert_eq!(provider.calls.load(Ordering::Relaxed), 3);
+        assert_eq!(cache.oncalls.metrics().hits, 2);
+    }
+
+    #[tokio::test]
+    async fn applies_each_recorded_clear_once() {
+        let cache = OncallCache::new(Duration::from_secs(60), Duration::from_secs(60));
+        let provider = CountingProvider {
+            calls: AtomicUsize::new(0),
+        };
+        let policy = OncallTarget::PdEscalationPolicy("PPOLICY".to_string());
+        let later = Utc::now().timestamp_millis() + 1000;
+
+        cache.get_oncall_users(&provider, &policy).await.unwrap();
+        // recorded before this instance started, already missing from its cache
+        assert_eq!(cache.apply_invalidations(&[(None, 0)]), 0);
+
+        let invalidations = vec![(Some("PPOLICY".to_string()), later)];
+        assert_eq!(cache.apply_invalidations(&invalidations), 1);
+
+        cache.get_oncall_users(&provider, &policy).await.unwrap();
+        assert_eq!(cache.apply_invalidations(&invalidations), 0);
+        assert_eq!(provider.calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn never_caches_rotations() {
+        let cache = OncallCache::new(Duration::from_secs(60), Duration::from_secs(60));
+        let provider = CountingProvider {
+            calls: AtomicUsize::new(0),
+        };
+
+        let rotation = OncallTarget::Rotation("payments".to_string());
+
+        for _ in 0..2 {
+            cache.get_oncall_users(&provider, &rotation).await.unwrap();
+        }
+        assert_eq!(provider.calls.load(Ordering::Relaxed), 2);
+    }
+}
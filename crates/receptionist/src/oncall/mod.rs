@@ -0,0 +1,720 @@
+//! Services that know who is oncall, so oncall actions aren't tied to PagerDuty
+pub mod cache;
+pub mod opsgenie;
+pub mod rotation;
+
+use crate::{PagerDuty, PagerDutyOncallTarget};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use slack_morphism::prelude::*;
+use std::{collections::HashMap, fmt, str::FromStr};
+use strum::{Display, EnumIter, EnumString};
+
+/// A user currently oncall, as reported by the provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct OncallUser {
+    pub name: String,
+    /// used to find the matching Slack user
+    pub email: Option<String>,
+    /// set by providers that already know the Slack user, skipping the email lookup
+    pub slack_user_id: Option<String>,
+    /// 1 is the first to be notified
+    pub escalation_level: u8,
+    /// the user's page on the provider, posted when they can't be found in Slack
+    pub profile_url: Option<String>,
+}
+
+impl OncallUser {
+    /// Name linked to the provider profile, for when the user can't be mentioned in Slack
+    pub fn to_fallback_text(&self) -> String {
+        match &self.profile_url {
+            Some(profile_url) => format!("<{profile_url}|{}>", self.name),
+            None => self.name.to_owned(),
+        }
+    }
+}
+
+/// What oncall actions try, in order, when none of the oncall users can be found in Slack
+#[derive(Debug, Clone, PartialEq)]
+pub struct OncallFallback {
+    /// mention everyone at the next escalation level
+    pub next_escalation_level: bool,
+    /// Slack user group to mention, ex: `S0123ABCD`
+    pub usergroup_id: Option<String>,
+    /// post the oncall users' names linked to their provider profiles
+    pub name_and_profile: bool,
+    /// provider email domain -> Slack email domain, for aliases that differ between the two
+    pub email_domain_rewrites: HashMap<String, String>,
+}
+
+impl Default for OncallFallback {
+    fn default() -> Self {
+        Self {
+            next_escalation_level: true,
+            usergroup_id: None,
+            name_and_profile: true,
+            email_domain_rewrites: HashMap::new(),
+        }
+    }
+}
+
+impl OncallFallback {
+    /// Parse domain rewrites written as `alias.com=corp.com,old.io=corp.com`, invalid pairs are skipped
+    pub fn parse_email_domain_rewrites(rewrites: &str) -> HashMap<String, String> {
+        rewrites
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(from, to)| (from.trim().to_lowercase(), to.trim().to_lowercase()))
+            .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+            .collect()
+    }
+
+    /// Emails to look up in Slack, the provider's email first and then its rewritten domain
+    pub fn candidate_emails(&self, email: &str) -> Vec<String> {
+        let mut candidates = vec![email.to_owned()];
+
+        if let Some((local_part, domain)) = email.rsplit_once('@') {
+            if let Some(rewritten) = self.email_domain_rewrites.get(&domain.to_lowercase()) {
+                candidates.push(format!("{local_part}@{rewritten}"));
+            }
+        }
+        candidates
+    }
+}
+
+/// The closest escalation level after `level` that has someone oncall
+pub fn next_escalation_level(oncall_users: &[OncallUser], level: u8) -> Option<u8> {
+    oncall_users
+        .iter()
+        .map(|user| user.escalation_level)
+        .filter(|escalation_level| *escalation_level > level)
+        .min()
+}
+
+#[async_trait]
+pub trait OncallProvider: Send + Sync {
+    /// Who is oncall for `target`, sorted so the first to be notified comes first.
+    /// Targets of another provider are an error
+    async fn get_oncall_users(&self, target: &OncallTarget) -> Result<Vec<OncallUser>>;
+}
+
+#[async_trait]
+impl OncallProvider for PagerDuty {
+    async fn get_oncall_users(&self, target: &OncallTarget) -> Result<Vec<OncallUser>> {
+        let pagerduty_target = target
+            .to_pagerduty_target()
+            .ok_or_else(|| anyhow!("{target} is not a PagerDuty target"))?;
+        let oncalls_list = self.get_oncalls(&pagerduty_target).await?;
+
+        Ok(oncalls_list
+            .oncalls
+            .into_iter()
+            .map(|oncall| OncallUser {
+                name: oncall.user.name,
+                email: Some(oncall.user.email),
+                slack_user_id: None,
+                escalation_level: oncall.escalation_level,
+                profile_url: oncall.user.html_url,
+            })
+            .collect())
+    }
+}
+
+/// Who to tag out of a provider's sorted oncall users: the first one, or everyone at `mention_level`.
+/// Users oncall through several schedules at the same level are only tagged once
+pub fn oncall_users_to_mention(
+    oncall_users: &[OncallUser],
+    mention_level: Option<u8>,
+) -> Vec<&OncallUser> {
+    let mention_level = match mention_level {
+        Some(level) => level,
+        None => return oncall_users.first().into_iter().collect(),
+    };
+
+    let mut to_mention: Vec<&OncallUser> = Vec::new();
+    for oncall_user in oncall_users
+        .iter()
+        .filter(|user| user.escalation_level == mention_level)
+    {
+        if !to_mention.iter().any(|user| {
+            user.email == oncall_user.email && user.slack_user_id == oncall_user.slack_user_id
+        }) {
+            to_mention.push(oncall_user)
+        }
+    }
+    to_mention
+}
+
+/// Which provider an oncall action asks, saved with the action
+#[derive(Debug, Serialize, Deserialize, PartialEq, EnumIter, EnumString, Display, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum OncallProviderKind {
+    #[strum(serialize = "pagerduty")]
+    #[serde(rename = "pagerduty")]
+    PagerDuty,
+    Opsgenie,
+    /// a rotation stored by Receptionist
+    Rotation,
+}
+
+/// Actions saved before providers were selectable all used PagerDuty
+impl Default for OncallProviderKind {
+    fn default() -> Self {
+        Self::PagerDuty
+    }
+}
+
+impl OncallProviderKind {
+    pub fn to_description(&self) -> &str {
+        match self {
+            OncallProviderKind::PagerDuty => "PagerDuty",
+            OncallProviderKind::Opsgenie => "Opsgenie",
+            OncallProviderKind::Rotation => "Receptionist Rotation",
+        }
+    }
+
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+
+    /// Label for the input that identifies who to look up
+    pub fn target_label(&self) -> &str {
+        match self {
+            OncallProviderKind::PagerDuty => "Search PagerDuty escalation policies and schedules",
+            OncallProviderKind::Opsgenie => "Enter the Opsgenie schedule name or ID",
+            OncallProviderKind::Rotation => "Enter the rotation name",
+        }
+    }
+}
+
+/// Who an oncall action looks up, each provider has its own kind of target
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(try_from = "SavedOncallTarget", into = "SavedOncallTarget")]
+pub enum OncallTarget {
+    PdEscalationPolicy(String),
+    PdSchedule(String),
+    /// Opsgenie schedule name or id
+    OpsgenieSchedule(String),
+    /// name of a rotation stored by Receptionist
+    Rotation(String),
+}
+
+impl Default for OncallTarget {
+    fn default() -> Self {
+        Self::new(OncallProviderKind::default(), "")
+    }
+}
+
+impl OncallTarget {
+    /// Target typed in for `provider`, PagerDuty schedules are written as `schedule:<ID>`
+    pub fn new(provider: OncallProviderKind, id: &str) -> Self {
+        match provider {
+            OncallProviderKind::PagerDuty => PagerDutyOncallTarget::from_str(id)
+                .map(Self::from)
+                .unwrap_or_else(|_| Self::PdEscalationPolicy(String::default())),
+            OncallProviderKind::Opsgenie => Self::OpsgenieSchedule(id.trim().to_string()),
+            OncallProviderKind::Rotation => Self::Rotation(id.trim().to_string()),
+        }
+    }
+
+    pub fn provider(&self) -> OncallProviderKind {
+        match self {
+            OncallTarget::PdEscalationPolicy(_) | OncallTarget::PdSchedule(_) => {
+                OncallProviderKind::PagerDuty
+            }
+            OncallTarget::OpsgenieSchedule(_) => OncallProviderKind::Opsgenie,
+            OncallTarget::Rotation(_) => OncallProviderKind::Rotation,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            OncallTarget::PdEscalationPolicy(id)
+            | OncallTarget::PdSchedule(id)
+            | OncallTarget::OpsgenieSchedule(id)
+            | OncallTarget::Rotation(id) => id,
+        }
+    }
+
+    pub fn kind_description(&self) -> &str {
+        match self {
+            OncallTarget::PdEscalationPolicy(_) => "escalation policy",
+            OncallTarget::PdSchedule(_) | OncallTarget::OpsgenieSchedule(_) => "schedule",
+            OncallTarget::Rotation(_) => "rotation",
+        }
+    }
+
+    /// `None` for targets of other providers
+    pub fn to_pagerduty_target(&self) -> Option<PagerDutyOncallTarget> {
+        match self {
+            OncallTarget::PdEscalationPolicy(id) => {
+                Some(PagerDutyOncallTarget::EscalationPolicy(id.to_owned()))
+            }
+            OncallTarget::PdSchedule(id) => Some(PagerDutyOncallTarget::Schedule(id.to_owned())),
+            OncallTarget::OpsgenieSchedule(_) | OncallTarget::Rotation(_) => None,
+        }
+    }
+}
+
+impl From<PagerDutyOncallTarget> for OncallTarget {
+    fn from(target: PagerDutyOncallTarget) -> Self {
+        match target {
+            PagerDutyOncallTarget::EscalationPolicy(id) => Self::PdEscalationPolicy(id),
+            PagerDutyOncallTarget::Schedule(id) => Self::PdSchedule(id),
+        }
+    }
+}
+
+/// ex: `PagerDuty schedule PXYZ123`
+impl fmt::Display for OncallTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.provider().to_description(),
+            self.kind_description(),
+            self.id()
+        )
+    }
+}
+
+/// How an `OncallTarget` is saved, flattened into its action. Actions saved before targets were typed
+/// have no `target`, they kept its id in `escalation_policy_id` and read it according to `provider`
+#[derive(Serialize, Deserialize)]
+struct SavedOncallTarget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<TaggedOncallTarget>,
+    #[serde(default, skip_serializing)]
+    provider: OncallProviderKind,
+    #[serde(default, skip_serializing)]
+    escalation_policy_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
+enum TaggedOncallTarget {
+    PdEscalationPolicy(String),
+    PdSchedule(String),
+    OpsgenieSchedule(String),
+    Rotation(String),
+}
+
+impl TryFrom<SavedOncallTarget> for OncallTarget {
+    type Error = anyhow::Error;
+
+    fn try_from(saved: SavedOncallTarget) -> Result<Self> {
+        Ok(match (saved.target, saved.escalation_policy_id) {
+            (Some(TaggedOncallTarget::PdEscalationPolicy(id)), _) => Self::PdEscalationPolicy(id),
+            (Some(TaggedOncallTarget::PdSchedule(id)), _) => Self::PdSchedule(id),
+            (Some(TaggedOncallTarget::OpsgenieSchedule(id)), _) => Self::OpsgenieSchedule(id),
+            (Some(TaggedOncallTarget::Rotation(id)), _) => Self::Rotation(id),
+            (None, Some(legacy_id)) => Self::new(saved.provider, &legacy_id),
+            (None, None) => bail!("oncall action has no target"),
+        })
+    }
+}
+
+impl From<OncallTarget> for SavedOncallTarget {
+    fn from(target: OncallTarget) -> Self {
+        let tagged = match target {
+            OncallTarget::PdEscalationPolicy(id) => TaggedOncallTarget::PdEscalationPolicy(id),
+            OncallTarget::PdSchedule(id) => TaggedOncallTarget::PdSchedule(id),
+            OncallTarget::OpsgenieSchedule(id) => TaggedOncallTarget::OpsgenieSchedule(id),
+            OncallTarget::Rotation(id) => TaggedOncallTarget::Rotation(id),
+        };
+
+        Self {
+            target: Some(tagged),
+            provider: OncallProviderKind::default(),
+            escalation_policy_id: None,
+        }
+    }
+}
+
+/// What an oncall action does when the Slack users it would tag can't be reached right now
+#[derive(Debug, Serialize, Deserialize, PartialEq, EnumIter, EnumString, Display, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum OncallUnavailableAction {
+    /// tag them without checking, the behavior of actions saved before this setting existed
+    TagAnyway,
+    /// skip them and tag the next escalation level when nobody else at the level is available
+    Escalate,
+    /// tag them with a note saying why they may not respond
+    PostNote,
+}
+
+impl Default for OncallUnavailableAction {
+    fn default() -> Self {
+        Self::TagAnyway
+    }
+}
+
+impl OncallUnavailableAction {
+    pub fn to_description(&self) -> &str {
+        match self {
+            OncallUnavailableAction::TagAnyway => "Tag them anyway",
+            OncallUnavailableAction::Escalate => "Escalate to the next level",
+            OncallUnavailableAction::PostNote => "Tag them with a note that they are unavailable",
+        }
+    }
+
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+}
+
+/// Where an oncall topic action keeps the current oncall
+#[derive(Debug, Serialize, Deserialize, PartialEq, EnumIter, EnumString, Display, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum OncallTopicLocation {
+    /// parts of a topic are separated by ` | `
+    Topic,
+    /// a message pinned by the bot, one part per line
+    PinnedMessage,
+}
+
+impl Default for OncallTopicLocation {
+    fn default() -> Self {
+        Self::Topic
+    }
+}
+
+impl OncallTopicLocation {
+    pub fn to_description(&self) -> &str {
+        match self {
+            OncallTopicLocation::Topic => "Channel topic",
+            OncallTopicLocation::PinnedMessage => "Pinned message",
+        }
+    }
+
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+
+    /// What separates the oncall from the rest of the text
+    pub fn separator(&self) -> &str {
+        match self {
+            OncallTopicLocation::Topic => " | ",
+            OncallTopicLocation::PinnedMessage => "\n",
+        }
+    }
+}
+
+/// `text` with its `label: ...` part (case insensitive) set to the mentions, the rest of the text is kept.
+/// The part is appended when missing. Parts are split by `separator` with surrounding spaces ignored
+pub fn with_oncall_segment(text: &str, label: &str, mentions: &str, separator: &str) -> String {
+    let split_on = match separator.trim() {
+        "" => separator,
+        trimmed => trimmed,
+    };
+    let segment = format!("{label}: {mentions}");
+
+    let mut replaced = false;
+    let parts = text
+        .split(split_on)
+        .map(|part| {
+            if replaced || !is_oncall_segment(part, label) {
+                return part.to_owned();
+            }
+            replaced = true;
+
+            let leading = &part[..part.len() - part.trim_start().len()];
+            let trailing = &part[part.trim_end().len()..];
+            format!("{leading}{segment}{trailing}")
+        })
+        .collect::<Vec<_>>();
+
+    if replaced {
+        parts.join(split_on)
+    } else if text.trim().is_empty() {
+        segment
+    } else {
+        format!("{}{separator}{segment}", text.trim_end())
+    }
+}
+
+/// Whether `text` has a `label: ...` part that `with_oncall_segment` would replace
+pub fn has_oncall_segment(text: &str, label: &str, separator: &str) -> bool {
+    let split_on = match separator.trim() {
+        "" => separator,
+        trimmed => trimmed,
+    };
+    text.split(split_on)
+        .any(|part| is_oncall_segment(part, label))
+}
+
+fn is_oncall_segment(part: &str, label: &str) -> bool {
+    part.trim_start()
+        .to_lowercase()
+        .starts_with(&format!("{}:", label.trim().to_lowercase()))
+}
+
+/// Why a tagged Slack user is unlikely to see the mention soon
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SlackUnavailability {
+    Deactivated,
+    DoNotDisturb,
+    Away,
+}
+
+impl SlackUnavailability {
+    pub fn to_description(&self) -> &str {
+        match self {
+            SlackUnavailability::Deactivated => "deactivated",
+            SlackUnavailability::DoNotDisturb => "on Do Not Disturb",
+            SlackUnavailability::Away => "away",
+        }
+    }
+}
+
+/// Posted next to the mentions, ex: `<@U123> is on Do Not Disturb, <@U456> is away`
+pub fn unavailable_note(unavailable: &[(SlackUserId, SlackUnavailability)]) -> String {
+    unavailable
+        .iter()
+        .map(|(slack_user_id, reason)| format!("<@{slack_user_id}> is {}", reason.to_description()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_replace_only_the_oncall_part_of_a_topic() {
+        let separator = OncallTopicLocation::Topic.separator();
+
+        assert_eq!(
+            with_oncall_segment(
+                "Runbook: go/pay | Oncall: <@U1> | #pay-alerts",
+                "oncall",
+                "<@U2>",
+                separator
+            ),
+            "Runbook: go/pay | oncall: <@U2> | #pay-alerts"
+        );
+        assert_eq!(
+            with_oncall_segment("Runbook: go/pay", "oncall", "<@U2>", separator),
+            "Runbook: go/pay | oncall: <@U2>"
+        );
+        assert_eq!(
+            with_oncall_segment("", "oncall", "<@U2>", separator),
+            "oncall: <@U2>"
+        );
+        assert!(has_oncall_segment("a|oncall:@old", "oncall", separator));
+        assert!(!has_oncall_segment(
+            "oncall rotation docs",
+            "oncall",
+            separator
+        ));
+    }
+
+    #[test]
+    fn should_replace_only_the_oncall_line_of_a_pinned_message() {
+        let separator = OncallTopicLocation::PinnedMessage.separator();
+
+        assert_eq!(
+            with_oncall_segment(
+                "primary: <@U1>\nEscalate in #incidents",
+                "primary",
+                "<@U2> <@U3>",
+                separator
+            ),
+            "primary: <@U2> <@U3>\nEscalate in #incidents"
+        );
+    }
+
+    #[test]
+    fn should_describe_unavailable_users() {
+        let note = unavailable_note(&[
+            ("U1".into(), SlackUnavailability::DoNotDisturb),
+            ("U2".into(), SlackUnavailability::Deactivated),
+        ]);
+        assert_eq!(note, "<@U1> is on Do Not Disturb, <@U2> is deactivated");
+
+        assert_eq!(
+            OncallUnavailableAction::from_str("post_note").unwrap(),
+            OncallUnavailableAction::PostNote
+        );
+        assert_eq!(
+            serde_json::from_value::<OncallUnavailableAction>(json!("escalate")).unwrap(),
+            OncallUnavailableAction::Escalate
+        );
+    }
+    use crate::pagerduty::stub_server::{spawn_stub_server, StubResponse};
+    use serde_json::json;
+
+    fn pd_oncall(email: &str, level: u8) -> serde_json::Value {
+        json!({
+            "escalation_policy": {},
+            "user": {"name": email, "email": email, "id": "PUSER", "type": "user"},
+            "schedule": null,
+            "escalation_level": level,
+            "start": null,
+            "end": null
+        })
+    }
+
+    #[tokio::test]
+    async fn pagerduty_provider_sorts_by_escalation_level() {
+        let stub = spawn_stub_server(vec![StubResponse::json(
+            200,
+            json!({"oncalls": [pd_oncall("second@example.com", 2), pd_oncall("first@example.com", 1)]}),
+        )])
+        .await;
+
+        let provider: Box<dyn OncallProvider> = Box::new(PagerDuty::new(
+            "token".to_string(),
+            Some(stub.base_url.clone()),
+        ));
+        let users = provider
+            .get_oncall_users(&OncallTarget::PdEscalationPolicy("PPOLICY".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            users
+                .iter()
+                .map(|u| u.email.as_deref().unwrap_or_default())
+                .collect::<Vec<_>>(),
+            vec!["first@example.com", "second@example.com"]
+        );
+        assert!(stub.requests()[0]
+            .path
+            .contains("escalation_policy_ids[]=PPOLICY"));
+    }
+
+    #[test]
+    fn mentions_first_oncall_or_everyone_at_level() {
+        let user = |email: &str, escalation_level: u8| OncallUser {
+            name: email.to_string(),
+            email: Some(email.to_string()),
+            slack_user_id: None,
+            escalation_level,
+            profile_url: None,
+        };
+        let oncall_users = vec![
+            user("first@example.com", 1),
+            user("second@example.com", 1),
+            user("first@example.com", 1),
+            user("manager@example.com", 2),
+        ];
+        let emails = |users: Vec<&OncallUser>| {
+            users
+                .iter()
+                .map(|u| u.email.clone().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            emails(oncall_users_to_mention(&oncall_users, None)),
+            vec!["first@example.com"]
+        );
+        assert_eq!(
+            emails(oncall_users_to_mention(&oncall_users, Some(1))),
+            vec!["first@example.com", "second@example.com"]
+        );
+        assert_eq!(
+            emails(oncall_users_to_mention(&oncall_users, Some(2))),
+            vec!["manager@example.com"]
+        );
+        assert!(oncall_users_to_mention(&oncall_users, Some(3)).is_empty());
+    }
+
+    #[test]
+    fn finds_next_escalation_level() {
+        let user = |escalation_level: u8| OncallUser {
+            name: "someone".to_string(),
+            email: None,
+            slack_user_id: None,
+            escalation_level,
+            profile_url: None,
+        };
+        let oncall_users = vec![user(1), user(3), user(5)];
+
+        assert_eq!(next_escalation_level(&oncall_users, 1), Some(3));
+        assert_eq!(next_escalation_level(&oncall_users, 3), Some(5));
+        assert_eq!(next_escalation_level(&oncall_users, 5), None);
+    }
+
+    #[test]
+    fn rewrites_alias_email_domains() {
+        let fallback = OncallFallback {
+            email_domain_rewrites: OncallFallback::parse_email_domain_rewrites(
+                " Alias.com = corp.com ,broken, =corp.com",
+            ),
+            ..OncallFallback::default()
+        };
+
+        assert_eq!(fallback.email_domain_rewrites.len(), 1);
+        assert_eq!(
+            fallback.candidate_emails("jane@ALIAS.com"),
+            vec!["jane@ALIAS.com", "jane@corp.com"]
+        );
+        assert_eq!(
+            fallback.candidate_emails("jane@corp.com"),
+            vec!["jane@corp.com"]
+        );
+    }
+
+    #[test]
+    fn fallback_text_links_profile() {
+        let user = OncallUser {
+            name: "Jane Doe".to_string(),
+            email: Some("jane@alias.com".to_string()),
+            slack_user_id: None,
+            escalation_level: 1,
+            profile_url: Some("https://acme.pagerduty.com/users/PUSER".to_string()),
+        };
+
+        assert_eq!(
+            user.to_fallback_text(),
+            "<https://acme.pagerduty.com/users/PUSER|Jane Doe>"
+        );
+    }
+
+    #[test]
+    fn oncall_target_loads_legacy_fields() {
+        let saved =
+            |fields: serde_json::Value| serde_json::from_value::<OncallTarget>(fields).unwrap();
+
+        assert_eq!(
+            saved(json!({"escalation_policy_id": "PLMIEBZ"})),
+            OncallTarget::PdEscalationPolicy("PLMIEBZ".to_string())
+        );
+        assert_eq!(
+            saved(json!({"provider": "pagerduty", "escalation_policy_id": "schedule:PSCHED1"})),
+            OncallTarget::PdSchedule("PSCHED1".to_string())
+        );
+        assert_eq!(
+            saved(json!({"provider": "rotation", "escalation_policy_id": "payments"})),
+            OncallTarget::Rotation("payments".to_string())
+        );
+        assert!(serde_json::from_value::<OncallTarget>(json!({})).is_err());
+
+        let target = OncallTarget::OpsgenieSchedule("Payments Rota".to_string());
+        let value = serde_json::to_value(&target).unwrap();
+        assert_eq!(
+            value,
+            json!({"target": {"type": "opsgenie_schedule", "value": "Payments Rota"}})
+        );
+        assert_eq!(saved(value), target);
+    }
+
+    #[test]
+    fn provider_kind_defaults_to_pagerduty() {
+        assert_eq!(
+            OncallProviderKind::from_str("opsgenie").unwrap(),
+            OncallProviderKind::Opsgenie
+        );
+        assert_eq!(
+            serde_json::to_value(OncallProviderKind::PagerDuty).unwrap(),
+            json!("pagerduty")
+        );
+        assert_eq!(OncallProviderKind::default(), OncallProviderKind::PagerDuty);
+    }
+}
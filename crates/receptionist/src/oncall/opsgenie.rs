@@ -0,0 +1,177 @@
+use crate::{
+    new_https_client, percent_encode, HttpsClient, OncallProvider, OncallTarget, OncallUser,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use hyper::header::AUTHORIZATION;
+use hyper::{Body, Request, Uri};
+use serde::{Deserialize, Serialize};
+use serde_json::from_slice;
+
+const DEFAULT_OPSGENIE_URL: &str = "https://api.opsgenie.com";
+
+/// Opsgenie REST API client, authenticated with an API integration key
+#[derive(Debug, Clone)]
+pub struct Opsgenie {
+    api_key: String,
+    base_url: String,
+    client: HttpsClient,
+}
+
+/// Response of `GET /v2/schedules/{identifier}/on-calls?flat=true`
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OnCallsResponse {
+    pub data: OnCalls,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OnCalls {
+    /// usernames of the oncall users, Opsgenie usernames are emails
+    #[serde(default)]
+    pub on_call_recipients: Vec<String>,
+}
+
+impl Opsgenie {
+    pub fn new(api_key: String, base_url: Option<String>) -> Self {
+        Self::with_client(api_key, base_url, new_https_client())
+    }
+
+    /// Reuse an existing client (and its connection pool) instead of creating a new one
+    pub fn with_client(api_key: String, base_url: Option<String>, client: HttpsClient) -> Self {
+        Self {
+            api_key,
+            base_url: base_url.unwrap_or_else(|| DEFAULT_OPSGENIE_URL.to_string()),
+            client,
+        }
+    }
+
+    /// Usernames (emails) of whoever is oncall for a schedule, looked up by id or name
+    pub async fn get_schedule_oncalls(&self, schedule: &str) -> Result<Vec<String>> {
+        let identifier_type = if is_opsgenie_id(schedule) {
+            "id"
+        } else {
+            "name"
+        };
+        let resource = format!(
+            "{}/v2/schedules/{}/on-calls?scheduleIdentifierType={identifier_type}&flat=true",
+            self.base_url,
+            percent_encode(schedule)
+        );
+
+        let uri: Uri = resource
+            .parse()
+            .map_err(|e| anyhow!("invalid url schema: {} {}", resource, e))?;
+
+        let request = Request::builder()
+            .header(AUTHORIZATION, format!("GenieKey {}", self.api_key))
+            .uri(uri)
+            .body(Body::empty())?;
+
+        let response = self.client.request(request).await?;
+        let status = response.status();
+        let bytes = hyper::body::to_bytes(response.into_body()).await?;
+
+        if !status.is_success() {
+            bail!(
+                "Opsgenie oncall lookup for schedule {} failed with status {}: {}",
+                schedule,
+                status,
+                String::from_utf8_lossy(&bytes)
+            );
+        }
+
+        let oncalls: OnCallsResponse = from_slice(bytes.as_ref())?;
+        Ok(oncalls.data.on_call_recipients)
+    }
+}
+
+#[async_trait]
+impl OncallProvider for Opsgenie {
+    /// Opsgenie schedules have no escalation levels, everyone oncall is notified first
+    async fn get_oncall_users(&self, target: &OncallTarget) -> Result<Vec<OncallUser>> {
+        let schedule = match target {
+            OncallTarget::OpsgenieSchedule(schedule) => schedule,
+            _ => bail!("{target} is not an Opsgenie target"),
+        };
+
+        Ok(self
+            .get_schedule_oncalls(schedule)
+            .await?
+            .into_iter()
+            .map(|username| OncallUser {
+                name: username.clone(),
+                email: Some(username),
+                slack_user_id: None,
+                profile_url: None,
+                escalation_level: 1,
+            })
+            .collect())
+    }
+}
+
+/// Opsgenie ids are UUIDs, anything else is treated as a schedule name
+fn is_opsgenie_id(identifier: &str) -> bool {
+    identifier.len() == 36
+        && identifier.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagerduty::stub_server::{spawn_stub_server, StubResponse};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn should_get_schedule_oncalls_by_name() {
+        let stub = spawn_stub_server(vec![StubResponse::json(
+            200,
+            json!({
+                "data": {
+                    "_parent": {"id": "d875e654-9b4e-4219-a803-0c26ca6ea5b8", "name": "Payments Rota", "enabled": true},
+                    "onCallRecipients": ["jane@example.com", "joe@example.com"]
+                },
+                "took": 0.1,
+                "requestId": "abc"
+            }),
+        )])
+        .await;
+
+        let opsgenie = Opsgenie::new("key".to_string(), Some(stub.base_url.clone()));
+        let users = opsgenie
+            .get_oncall_users(&OncallTarget::OpsgenieSchedule("Payments Rota".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].email.as_deref(), Some("jane@example.com"));
+        assert_eq!(users[1].escalation_level, 1);
+        assert_eq!(
+            stub.requests()[0].path,
+            "/v2/schedules/Payments%20Rota/on-calls?scheduleIdentifierType=name&flat=true"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_use_id_identifier_and_fail_on_error_status() {
+        let stub = spawn_stub_server(vec![StubResponse::json(
+            404,
+            json!({"message": "Schedule not found", "took": 0.01, "requestId": "abc"}),
+        )])
+        .await;
+
+        let opsgenie = Opsgenie::new("key".to_string(), Some(stub.base_url.clone()));
+        let err = opsgenie
+            .get_schedule_oncalls("d875e654-9b4e-4219-a803-0c26ca6ea5b8")
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("404"));
+        assert!(stub.requests()[0]
+            .path
+            .contains("scheduleIdentifierType=id"));
+    }
+}
@@ -0,0 +1,603 @@
+//! Rotations kept by Receptionist itself, for teams without a PagerDuty or Opsgenie schedule
+#[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+use crate::database::get_rotation_by_name;
+use crate::{
+    parse_timezone, BlockSectionRouter, OncallProvider, OncallTarget, OncallUser,
+    SlackBlockValidationError,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use nanoid::nanoid;
+use serde::{Deserialize, Serialize};
+use slack_morphism::prelude::*;
+use std::str::FromStr;
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const HANDOFF_TIME_FORMAT: &str = "%H:%M";
+
+/// How long each member is oncall before handing off to the next
+#[derive(Debug, Serialize, Deserialize, PartialEq, EnumIter, EnumString, Display, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HandoffCadence {
+    Daily,
+    Weekly,
+    Fortnightly,
+}
+
+impl Default for HandoffCadence {
+    fn default() -> Self {
+        Self::Weekly
+    }
+}
+
+impl HandoffCadence {
+    pub fn shift_length(&self) -> Duration {
+        match self {
+            HandoffCadence::Daily => Duration::days(1),
+            HandoffCadence::Weekly => Duration::weeks(1),
+            HandoffCadence::Fortnightly => Duration::weeks(2),
+        }
+    }
+
+    fn to_choice_item(self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        let description = match self {
+            HandoffCadence::Daily => "Every day",
+            HandoffCadence::Weekly => "Every week",
+            HandoffCadence::Fortnightly => "Every two weeks",
+        };
+
+        SlackBlockChoiceItem::new(pt!(description), self.to_string())
+    }
+}
+
+/// Someone covering the rotation for whole days, ex: while the scheduled member is on vacation
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RotationOverride {
+    pub user_id: String,
+    /// first day covered, `YYYY-MM-DD` in the rotation's timezone
+    pub start_date: String,
+    /// last day covered (inclusive)
+    pub end_date: String,
+}
+
+impl RotationOverride {
+    fn dates(&self) -> Result<(NaiveDate, NaiveDate)> {
+        Ok((parse_date(&self.start_date)?, parse_date(&self.end_date)?))
+    }
+
+    fn to_choice_item(&self, index: usize) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(
+            pt!(format!(
+                "{} to {} ({})",
+                self.start_date, self.end_date, self.user_id
+            )),
+            index.to_string(),
+        )
+    }
+}
+
+/// Who is oncall for a rotation right now
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationShift {
+    pub user_id: String,
+    /// end of the shift in the rotation's timezone
+    pub until: NaiveDateTime,
+    pub is_override: bool,
+}
+
+/// Members take turns being oncall, handing off at the same local time every `cadence`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Rotation {
+    pub id: String,
+    /// used to find the rotation from commands and oncall actions, unique ignoring case
+    pub name: String,
+    /// Slack user ids, oncall in this order
+    pub members: Vec<String>,
+    pub cadence: HandoffCadence,
+    /// `YYYY-MM-DD` the first member's first shift starts
+    pub start_date: String,
+    /// `HH:MM` local time of every handoff
+    pub handoff_time: String,
+    /// IANA timezone name, ex: `Europe/London`
+    pub timezone: String,
+    #[serde(default)]
+    pub overrides: Vec<RotationOverride>,
+    pub collaborators: Vec<String>,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self {
+            id: nanoid!(),
+            name: String::default(),
+            members: Vec::default(),
+            cadence: HandoffCadence::default(),
+            start_date: Utc::now()
+                .naive_utc()
+                .date()
+                .format(DATE_FORMAT)
+                .to_string(),
+            handoff_time: "09:00".to_string(),
+            timezone: "UTC".to_string(),
+            overrides: Vec::default(),
+            collaborators: Vec::default(),
+        }
+    }
+}
+
+impl Rotation {
+    /// The override covering today if there is one, otherwise the member whose turn it is.
+    /// `None` when the rotation has no members
+    pub fn current_shift(&self, now: DateTime<Utc>) -> Result<Option<RotationShift>> {
+        let timezone = parse_timezone(&self.timezone)?;
+        let local_now = now.with_timezone(&timezone).naive_local();
+        let today = local_now.date();
+
+        for rotation_override in &self.overrides {
+            let (start_date, end_date) = rotation_override.dates()?;
+            if start_date <= today && today <= end_date {
+                return Ok(Some(RotationShift {
+                    user_id: rotation_override.user_id.to_owned(),
+                    until: (end_date + Duration::days(1)).and_hms(0, 0, 0),
+                    is_override: true,
+                }));
+            }
+        }
+
+        if self.members.is_empty() {
+            return Ok(None);
+        }
+
+        // handoffs are compared in local time so they stay at the same hour across DST changes
+        let first_handoff = self.first_handoff()?;
+        let shift_length = self.cadence.shift_length();
+        let completed_shifts = if local_now < first_handoff {
+            0
+        } else {
+            (local_now - first_handoff).num_seconds() / shift_length.num_seconds()
+        };
+
+        Ok(Some(RotationShift {
+            user_id: self.members[completed_shifts as usize % self.members.len()].to_owned(),
+            until: first_handoff + shift_length * (completed_shifts as i32 + 1),
+            is_override: false,
+        }))
+    }
+
+    fn first_handoff(&self) -> Result<NaiveDateTime> {
+        let handoff_time = NaiveTime::parse_from_str(self.handoff_time.trim(), HANDOFF_TIME_FORMAT)
+            .map_err(|_| anyhow!("handoff time should be HH:MM, ex: 09:00"))?;
+
+        Ok(parse_date(&self.start_date)?.and_time(handoff_time))
+    }
+
+    /// Overrides that ended before today (in the rotation's timezone) are no longer needed
+    pub fn remove_expired_overrides(&mut self, now: DateTime<Utc>) {
+        let today = match parse_timezone(&self.timezone) {
+            Ok(timezone) => now.with_timezone(&timezone).naive_local().date(),
+            Err(_) => return,
+        };
+
+        self.overrides.retain(|rotation_override| {
+            rotation_override
+                .dates()
+                .map_or(true, |(_start, end_date)| end_date >= today)
+        });
+    }
+
+    pub fn validate(&self, all_rotations: &[Rotation]) -> Option<Vec<SlackBlockValidationError>> {
+        let mut validation_errors = Vec::new();
+        let mut error = |route: BlockSectionRouter, error_message: String| {
+            validation_errors.push(SlackBlockValidationError {
+                block_id: route.to_block_id(None),
+                error_message,
+            })
+        };
+
+        if self.name.trim().is_empty() {
+            error(
+                BlockSectionRouter::RotationNameInput,
+                "name is empty".to_string(),
+            );
+        } else if all_rotations.iter().any(|rotation| {
+            rotation.id != self.id && rotation.name.trim().eq_ignore_ascii_case(self.name.trim())
+        }) {
+            error(
+                BlockSectionRouter::RotationNameInput,
+                "a rotation with this name already exists".to_string(),
+            );
+        }
+
+        if self.members.is_empty() {
+            error(
+                BlockSectionRouter::RotationMembersSelected,
+                "add at least one member".to_string(),
+            );
+        }
+
+        if parse_date(&self.start_date).is_err() {
+            error(
+                BlockSectionRouter::RotationStartDateSelected,
+                "pick the date of the first shift".to_string(),
+            );
+        } else if let Err(e) = self.first_handoff() {
+            error(BlockSectionRouter::RotationHandoffTimeInput, e.to_string());
+        }
+
+        if let Err(e) = parse_timezone(&self.timezone) {
+            error(BlockSectionRouter::RotationTimezoneInput, e.to_string());
+        }
+
+        for rotation_override in &self.overrides {
+            match rotation_override.dates() {
+                Ok((start_date, end_date)) if end_date < start_date => error(
+                    BlockSectionRouter::RotationOverrideEndSelected,
+                    "the override ends before it starts".to_string(),
+                ),
+                Ok(_) => (),
+                Err(e) => error(
+                    BlockSectionRouter::RotationOverrideStartSelected,
+                    e.to_string(),
+                ),
+            }
+        }
+
+        if validation_errors.is_empty() {
+            None
+        } else {
+            Some(validation_errors)
+        }
+    }
+
+    pub fn update_cadence(&mut self, cadence_str: &str) -> Result<()> {
+        self.cadence = HandoffCadence::from_str(cadence_str)?;
+        Ok(())
+    }
+
+    /// Remove overrides by their position, as selected in the editor
+    pub fn remove_overrides(&mut self, selected_indexes: &[String]) -> Result<()> {
+        let indexes = selected_indexes
+            .iter()
+            .map(|index| index.parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        let mut position = 0;
+        self.overrides.retain(|_| {
+            let keep = !indexes.contains(&position);
+            position += 1;
+            keep
+        });
+
+        Ok(())
+    }
+
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.name.to_owned()), self.id.to_owned())
+    }
+
+    /// Text for commands and previews, ex: `<@U123> is oncall for *payments* until 2022-05-09 09:00 (Europe/London)`
+    pub fn describe_shift(&self, shift: &RotationShift) -> String {
+        format!(
+            "<@{}> is oncall for *{}*{} until {} ({})",
+            shift.user_id,
+            self.name,
+            if shift.is_override { " (override)" } else { "" },
+            shift.until.format("%a %Y-%m-%d %H:%M"),
+            self.timezone
+        )
+    }
+
+    pub fn to_editor_blocks(&self) -> Vec<SlackBlock> {
+        let cadence_options: Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> =
+            HandoffCadence::iter()
+                .map(HandoffCadence::to_choice_item)
+                .collect();
+
+        let mut blocks = slack_blocks![
+            some_into(
+                SlackInputBlock::new(
+                    pt!("Rotation name"),
+                    SlackInputBlockElement::PlainTextInput(
+                        SlackBlockPlainTextInputElement::new(
+                            BlockSectionRouter::RotationNameInput.to_action_id(None),
+                            pt!("payments-oncall")
+                        )
+                        .with_initial_value(self.name.to_owned())
+                    )
+                )
+                .with_block_id(BlockSectionRouter::RotationNameInput.to_block_id(None))
+            ),
+            some_into(SlackContextBlock::new(vec![md!(
+                "_Find who's on duty with_ `/rec-cmd oncall <rotation name>` _or pick Rotation as the provider of an oncall action_"
+            )])),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("Members, oncall in the order they are added"),
+                    SlackInputBlockElement::MultiUsersSelect(
+                        SlackBlockMultiUsersSelectElement::new(
+                            BlockSectionRouter::RotationMembersSelected.to_action_id(None),
+                            pt!("Select members")
+                        )
+                        .with_initial_users(self.members.clone())
+                    )
+                )
+                .with_block_id(BlockSectionRouter::RotationMembersSelected.to_block_id(None))
+            ),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("Hand off"),
+                    SlackInputBlockElement::StaticSelect(
+                        SlackBlockStaticSelectElement::new(
+                            BlockSectionRouter::RotationCadenceSelected.to_action_id(None),
+                            pt!("select how often to hand off")
+                        )
+                        .with_options(cadence_options)
+                        .with_initial_option(self.cadence.to_choice_item())
+                    )
+                )
+                .with_block_id(BlockSectionRouter::RotationCadenceSelected.to_block_id(None))
+            ),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("First shift starts on"),
+                    SlackInputBlockElement::DatePicker(
+                        SlackBlockDatePickerElement::new(
+                            BlockSectionRouter::RotationStartDateSelected.to_action_id(None)
+                        )
+                        .with_initial_date(self.start_date.to_owned())
+                    )
+                )
+                .with_block_id(BlockSectionRouter::RotationStartDateSelected.to_block_id(None))
+            ),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("Handoff time (HH:MM)"),
+                    SlackInputBlockElement::PlainTextInput(
+                        SlackBlockPlainTextInputElement::new(
+                            BlockSectionRouter::RotationHandoffTimeInput.to_action_id(None),
+                            pt!("09:00")
+                        )
+                        .with_initial_value(self.handoff_time.to_owned())
+                    )
+                )
+                .with_block_id(BlockSectionRouter::RotationHandoffTimeInput.to_block_id(None))
+            ),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("Timezone"),
+                    SlackInputBlockElement::PlainTextInput(
+                        SlackBlockPlainTextInputElement::new(
+                            BlockSectionRouter::RotationTimezoneInput.to_action_id(None),
+                            pt!("America/Los_Angeles")
+                        )
+                        .with_initial_value(self.timezone.to_owned())
+                    )
+                )
+                .with_block_id(BlockSectionRouter::RotationTimezoneInput.to_block_id(None))
+            ),
+            some_into(SlackDividerBlock::new()),
+            some_into(SlackSectionBlock::new().with_text(md!(
+                ":palm_tree: *Overrides* - someone covering whole days instead of the scheduled member"
+            ))),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("Add an override for"),
+                    SlackInputBlockElement::UsersSelect(SlackBlockUsersSelectElement::new(
+                        BlockSectionRouter::RotationOverrideUserSelected.to_action_id(None),
+                        pt!("Select who covers")
+                    ))
+                )
+                .with_optional(true)
+                .with_block_id(BlockSectionRouter::RotationOverrideUserSelected.to_block_id(None))
+            ),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("From"),
+                    SlackInputBlockElement::DatePicker(SlackBlockDatePickerElement::new(
+                        BlockSectionRouter::RotationOverrideStartSelected.to_action_id(None)
+                    ))
+                )
+                .with_optional(true)
+                .with_block_id(BlockSectionRouter::RotationOverrideStartSelected.to_block_id(None))
+            ),
+            some_into(
+                SlackInputBlock::new(
+                    pt!("To (defaults to the same day)"),
+                    SlackInputBlockElement::DatePicker(SlackBlockDatePickerElement::new(
+                        BlockSectionRouter::RotationOverrideEndSelected.to_action_id(None)
+                    ))
+                )
+                .with_optional(true)
+                .with_block_id(BlockSectionRouter::RotationOverrideEndSelected.to_block_id(None))
+            )
+        ];
+
+        if !self.overrides.is_empty() {
+            let override_options: Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> = self
+                .overrides
+                .iter()
+                .enumerate()
+                .map(|(index, rotation_override)| rotation_override.to_choice_item(index))
+                .collect();
+
+            blocks.extend(slack_blocks![some_into(
+                SlackInputBlock::new(
+                    pt!("Remove overrides"),
+                    SlackInputBlockElement::MultiStaticSelect(
+                        SlackBlockMultiStaticSelectElement::new(
+                            BlockSectionRouter::RotationOverrideRemoveSelected.to_action_id(None),
+                            pt!("Select overrides to remove")
+                        )
+                        .with_options(override_options)
+                    )
+                )
+                .with_optional(true)
+                .with_block_id(
+                    BlockSectionRouter::RotationOverrideRemoveSelected.to_block_id(None)
+                )
+            )]);
+        }
+
+        blocks
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), DATE_FORMAT)
+        .map_err(|_| anyhow!("`{date}` is not a YYYY-MM-DD date"))
+}
+
+/// Add an override from the editor's override inputs, the end date defaults to the start date
+pub fn new_rotation_override(
+    user_id: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Option<RotationOverride>> {
+    match (user_id, start_date) {
+        (Some(user_id), Some(start_date)) => Ok(Some(RotationOverride {
+            user_id,
+            end_date: end_date.unwrap_or_else(|| start_date.clone()),
+            start_date,
+        })),
+        (None, None) => Ok(None),
+        _ => bail!("pick both who covers and the first day of the override"),
+    }
+}
+
+/// Oncall provider for rotations stored by Receptionist, the target is the rotation name
+pub struct RotationOncallProvider;
+
+#[async_trait]
+impl OncallProvider for RotationOncallProvider {
+    async fn get_oncall_users(&self, target: &OncallTarget) -> Result<Vec<OncallUser>> {
+        let name = match target {
+            OncallTarget::Rotation(name) => name,
+            _ => bail!("{target} is not a rotation"),
+        };
+
+        let rotation = get_rotation_by_name(name)
+            .await?
+            .ok_or_else(|| anyhow!("no rotation named {name}"))?;
+
+        Ok(rotation
+            .current_shift(Utc::now())?
+            .into_iter()
+            .map(|shift| OncallUser {
+                name: shift.user_id.to_owned(),
+                email: None,
+                slack_user_id: Some(shift.user_id),
+                profile_url: None,
+                escalation_level: 1,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Timestamps are easier to read as local dates and times
+    fn utc_at(timezone: &str, local: &str) -> DateTime<Utc> {
+        parse_timezone(timezone)
+            .unwrap()
+            .from_local_datetime(&NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M").unwrap())
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn weekly_rotation() -> Rotation {
+        Rotation {
+            name: "payments".to_string(),
+            members: vec!["U1".to_string(), "U2".to_string(), "U3".to_string()],
+            cadence: HandoffCadence::Weekly,
+            start_date: "2022-05-02".to_string(),
+            handoff_time: "09:00".to_string(),
+            timezone: "Europe/London".to_string(),
+            ..Rotation::default()
+        }
+    }
+
+    #[test]
+    fn test_current_shift_hands_off_weekly() {
+        let rotation = weekly_rotation();
+        let shift_at = |local: &str| {
+            rotation
+                .current_shift(utc_at("Europe/London", local))
+                .unwrap()
+                .unwrap()
+        };
+
+        assert_eq!(shift_at("2022-05-01 12:00").user_id, "U1");
+        assert_eq!(shift_at("2022-05-09 08:59").user_id, "U1");
+        assert_eq!(shift_at("2022-05-09 09:00").user_id, "U2");
+        assert_eq!(shift_at("2022-05-16 09:00").user_id, "U3");
+        assert_eq!(shift_at("2022-05-23 09:00").user_id, "U1");
+        assert_eq!(
+            shift_at("2022-05-10 12:00").until,
+            NaiveDateTime::parse_from_str("2022-05-16 09:00", "%Y-%m-%d %H:%M").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_override_takes_precedence() {
+        let mut rotation = weekly_rotation();
+        rotation.overrides.push(RotationOverride {
+            user_id: "U9".to_string(),
+            start_date: "2022-05-10".to_string(),
+            end_date: "2022-05-11".to_string(),
+        });
+
+        let shift = rotation
+            .current_shift(utc_at("Europe/London", "2022-05-11 23:30"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(shift.user_id, "U9");
+        assert!(shift.is_override);
+
+        let shift = rotation
+            .current_shift(utc_at("Europe/London", "2022-05-12 00:00"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(shift.user_id, "U2");
+
+        rotation.remove_expired_overrides(utc_at("Europe/London", "2022-05-12 00:00"));
+        assert!(rotation.overrides.is_empty());
+    }
+
+    #[test]
+    fn test_validate_rotation() {
+        let rotation = weekly_rotation();
+        assert!(rotation.validate(&[]).is_none());
+
+        let same_name = Rotation {
+            name: "Payments ".to_string(),
+            ..weekly_rotation()
+        };
+        assert_eq!(rotation.validate(&[same_name]).unwrap().len(), 1);
+
+        let invalid = Rotation {
+            members: vec![],
+            handoff_time: "9am".to_string(),
+            timezone: "Mars/Olympus".to_string(),
+            ..weekly_rotation()
+        };
+        assert_eq!(invalid.validate(&[]).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_new_rotation_override() {
+        let rotation_override =
+            new_rotation_override(Some("U9".to_string()), Some("2022-05-10".to_string()), None)
+                .unwrap()
+                .unwrap();
+        assert_eq!(rotation_override.end_date, "2022-05-10");
+
+        assert!(new_rotation_override(None, None, None).unwrap().is_none());
+        assert!(new_rotation_override(Some("U9".to_string()), None, None).is_err());
+    }
+}
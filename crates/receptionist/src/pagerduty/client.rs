@@ -1,76 +1,351 @@
-use crate::pagerduty::models::OncallList;
-use anyhow::{anyhow, Result};
-use hyper::client::{Client, HttpConnector};
-use hyper::header::AUTHORIZATION;
-use hyper::{Body, Request, Uri};
-use hyper_rustls::{ConfigBuilderExt, HttpsConnector, HttpsConnectorBuilder};
+use crate::{
+    new_https_client,
+    pagerduty::models::{
+        EscalationPolicyList, EventResponse, EventV2, NamedResource, OncallList, ScheduleList,
+    },
+    percent_encode, HttpsClient,
+};
+use anyhow::{anyhow, bail, Result};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use hyper::{Body, Method, Request, StatusCode, Uri};
+use serde::de::DeserializeOwned;
 use serde_json::from_slice;
+use std::{fmt, str::FromStr, time::Duration};
+use tracing::warn;
 
 const DEFAULT_PD_URL: &str = "https://api.pagerduty.com";
+const DEFAULT_PD_EVENTS_URL: &str = "https://events.pagerduty.com";
+/// largest page PagerDuty allows for classic pagination
+const PD_PAGE_LIMIT: usize = 100;
+/// results per search, enough to pick from while typing
+const PD_SEARCH_LIMIT: usize = 25;
+/// attempts per request while PagerDuty keeps answering 429
+const PD_MAX_ATTEMPTS: usize = 3;
+/// used when a 429 has no `Retry-After` header
+const PD_DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// longer waits are given up on, oncall actions shouldn't hang on a rate limit
+const PD_MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+/// prefix for oncall targets that are schedules instead of escalation policies
+const PD_SCHEDULE_PREFIX: &str = "schedule:";
+
+/// Failed PagerDuty REST API request, by status so credentials problems and rate limits can be told apart
+#[derive(Debug, PartialEq)]
+pub enum PagerDutyError {
+    /// 401, the API token is missing or invalid
+    Unauthorized,
+    /// 403, the API token can't read this resource
+    Forbidden,
+    /// 404, ex: an escalation policy or schedule id that doesn't exist
+    NotFound,
+    /// 429 on every attempt
+    RateLimited { retry_after: Option<Duration> },
+    /// any other unsuccessful status, with PagerDuty's error message
+    Api { status: u16, message: String },
+}
+
+impl fmt::Display for PagerDutyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PagerDutyError::Unauthorized => write!(f, "PagerDuty rejected the API token (401)"),
+            PagerDutyError::Forbidden => {
+                write!(f, "PagerDuty API token is not allowed to read this (403)")
+            }
+            PagerDutyError::NotFound => write!(f, "PagerDuty resource not found (404)"),
+            PagerDutyError::RateLimited { retry_after } => match retry_after {
+                Some(retry_after) => write!(
+                    f,
+                    "PagerDuty rate limit hit (429), retry after {}s",
+                    retry_after.as_secs()
+                ),
+                None => write!(f, "PagerDuty rate limit hit (429)"),
+            },
+            PagerDutyError::Api { status, message } => {
+                write!(
+                    f,
+                    "PagerDuty request failed with status {status}: {message}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PagerDutyError {}
+
+impl PagerDutyError {
+    fn from_response(status: StatusCode, retry_after: Option<Duration>, body: &[u8]) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => PagerDutyError::Unauthorized,
+            StatusCode::FORBIDDEN => PagerDutyError::Forbidden,
+            StatusCode::NOT_FOUND => PagerDutyError::NotFound,
+            StatusCode::TOO_MANY_REQUESTS => PagerDutyError::RateLimited { retry_after },
+            _ => PagerDutyError::Api {
+                status: status.as_u16(),
+                message: String::from_utf8_lossy(body).to_string(),
+            },
+        }
+    }
+}
+
+/// What to find oncalls for, schedules are written as `schedule:<ID>` and anything else is an escalation policy id
+#[derive(Debug, Clone, PartialEq)]
+pub enum PagerDutyOncallTarget {
+    EscalationPolicy(String),
+    Schedule(String),
+}
+
+impl FromStr for PagerDutyOncallTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(target: &str) -> Result<Self> {
+        let target = target.trim();
+        let parsed = match target.strip_prefix(PD_SCHEDULE_PREFIX) {
+            Some(schedule_id) => PagerDutyOncallTarget::Schedule(schedule_id.trim().to_string()),
+            None => PagerDutyOncallTarget::EscalationPolicy(target.to_string()),
+        };
+
+        if parsed.id().is_empty() {
+            bail!("no PagerDuty escalation policy or schedule id provided")
+        }
+        Ok(parsed)
+    }
+}
+
+impl PagerDutyOncallTarget {
+    pub fn id(&self) -> &str {
+        match self {
+            PagerDutyOncallTarget::EscalationPolicy(id) | PagerDutyOncallTarget::Schedule(id) => id,
+        }
+    }
+
+    pub fn kind_description(&self) -> &str {
+        match self {
+            PagerDutyOncallTarget::EscalationPolicy(_) => "escalation policy",
+            PagerDutyOncallTarget::Schedule(_) => "schedule",
+        }
+    }
+
+    fn to_query(&self) -> String {
+        match self {
+            PagerDutyOncallTarget::EscalationPolicy(id) => {
+                format!("escalation_policy_ids[]={}", percent_encode(id))
+            }
+            PagerDutyOncallTarget::Schedule(id) => {
+                format!("schedule_ids[]={}", percent_encode(id))
+            }
+        }
+    }
+
+    fn to_resource_path(&self) -> String {
+        match self {
+            PagerDutyOncallTarget::EscalationPolicy(id) => {
+                format!("/escalation_policies/{}", percent_encode(id))
+            }
+            PagerDutyOncallTarget::Schedule(id) => format!("/schedules/{}", percent_encode(id)),
+        }
+    }
+}
+
+/// The form saved with oncall actions, parsed back by `from_str`
+impl fmt::Display for PagerDutyOncallTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PagerDutyOncallTarget::EscalationPolicy(id) => write!(f, "{id}"),
+            PagerDutyOncallTarget::Schedule(id) => write!(f, "{PD_SCHEDULE_PREFIX}{id}"),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct PagerDuty {
     auth: String,
     base_url: String,
-    client: Client<HttpsConnector<HttpConnector>>,
+    client: HttpsClient,
 }
 
 impl PagerDuty {
-    pub async fn get_oncalls(&self, escalation_policy: String) -> Result<OncallList> {
-        let resource = self.base_url.clone()
-            + "/oncalls?earliest=true&include[]=users&escalation_policy_ids[]="
-            + escalation_policy.as_str();
+    /// Everyone oncall for an escalation policy or schedule across all pages, sorted by escalation level
+    pub async fn get_oncalls(&self, target: &PagerDutyOncallTarget) -> Result<OncallList> {
+        let mut oncalls = Vec::new();
+
+        loop {
+            let page: OncallList = self
+                .get_json(&format!(
+                    "/oncalls?earliest=true&include[]=users&{}&limit={PD_PAGE_LIMIT}&offset={}",
+                    target.to_query(),
+                    oncalls.len()
+                ))
+                .await?;
+
+            let has_more = page.more.unwrap_or(false) && !page.oncalls.is_empty();
+            oncalls.extend(page.oncalls);
+
+            if !has_more {
+                break;
+            }
+        }
+
+        oncalls.sort_by(|a, b| a.escalation_level.cmp(&b.escalation_level));
+
+        Ok(OncallList {
+            oncalls,
+            ..OncallList::default()
+        })
+    }
+
+    /// Escalation policies whose name matches `query`, for the escalation policy picker
+    pub async fn search_escalation_policies(&self, query: &str) -> Result<Vec<NamedResource>> {
+        let policy_list: EscalationPolicyList = self
+            .get_json(&format!(
+                "/escalation_policies?query={}&limit={PD_SEARCH_LIMIT}",
+                percent_encode(query.trim())
+            ))
+            .await?;
+
+        Ok(policy_list.escalation_policies)
+    }
+
+    /// Schedules whose name matches `query`, for the escalation policy picker
+    pub async fn search_schedules(&self, query: &str) -> Result<Vec<NamedResource>> {
+        let schedule_list: ScheduleList = self
+            .get_json(&format!(
+                "/schedules?query={}&limit={PD_SEARCH_LIMIT}",
+                percent_encode(query.trim())
+            ))
+            .await?;
+
+        Ok(schedule_list.schedules)
+    }
+
+    /// `false` when PagerDuty has no escalation policy or schedule with the target's id
+    pub async fn oncall_target_exists(&self, target: &PagerDutyOncallTarget) -> Result<bool> {
+        match self
+            .get_json::<serde_json::Value>(&target.to_resource_path())
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) if e.downcast_ref::<PagerDutyError>() == Some(&PagerDutyError::NotFound) => {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// GET a REST API resource, waiting out rate limits for up to `PD_MAX_ATTEMPTS` attempts.
+    /// Unsuccessful statuses are returned as a `PagerDutyError`
+    async fn get_json<T: DeserializeOwned>(&self, path_and_query: &str) -> Result<T> {
+        let resource = self.base_url.clone() + path_and_query;
+
+        let uri: Uri = resource
+            .parse()
+            .map_err(|e| anyhow!("invalid url schema: {} {}", resource, e))?;
+
+        let mut attempt = 1;
+        loop {
+            let request = Request::builder()
+                .header(AUTHORIZATION, format!("Token token={}", self.auth))
+                .uri(uri.clone())
+                .body(Body::empty())?;
+
+            let response = self.client.request(request).await?;
+            let status = response.status();
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let bytes = hyper::body::to_bytes(response.into_body()).await?;
+
+            if status.is_success() {
+                return Ok(from_slice(bytes.as_ref())?);
+            }
+
+            let wait = retry_after.unwrap_or(PD_DEFAULT_RETRY_AFTER);
+            if status != StatusCode::TOO_MANY_REQUESTS
+                || attempt >= PD_MAX_ATTEMPTS
+                || wait > PD_MAX_RETRY_AFTER
+            {
+                return Err(PagerDutyError::from_response(status, retry_after, &bytes).into());
+            }
+
+            warn!(
+                "PagerDuty rate limited {}, retrying in {}s",
+                path_and_query,
+                wait.as_secs()
+            );
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Events API v2 client, events are authenticated by the routing key in their body instead of a token
+#[derive(Debug, Clone)]
+pub struct PagerDutyEvents {
+    base_url: String,
+    client: HttpsClient,
+}
+
+impl PagerDutyEvents {
+    pub fn new(base_url: Option<String>) -> Self {
+        Self::with_client(base_url, new_https_client())
+    }
+
+    pub fn with_client(base_url: Option<String>, client: HttpsClient) -> Self {
+        Self {
+            base_url: base_url.unwrap_or_else(|| DEFAULT_PD_EVENTS_URL.to_string()),
+            client,
+        }
+    }
+
+    pub async fn send_event(&self, event: &EventV2) -> Result<EventResponse> {
+        let resource = self.base_url.clone() + "/v2/enqueue";
 
         let uri: Uri = resource
             .parse()
             .map_err(|e| anyhow!("invalid url schema: {} {}", resource, e))?;
 
-        let authorization_token = "Token token=".to_owned() + self.auth.as_str();
         let request = Request::builder()
-            .header(AUTHORIZATION, authorization_token)
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json")
             .uri(uri)
-            .body(Body::empty())?;
+            .body(Body::from(serde_json::to_vec(event)?))?;
 
         let response = self.client.request(request).await?;
+        let status = response.status();
         let bytes = hyper::body::to_bytes(response.into_body()).await?;
 
-        let mut oncalls_list: OncallList = from_slice(bytes.as_ref())?;
+        if !status.is_success() {
+            bail!(
+                "PagerDuty rejected event with status {}: {}",
+                status,
+                String::from_utf8_lossy(&bytes)
+            );
+        }
+
+        Ok(from_slice(bytes.as_ref())?)
+    }
+
+    /// One alert per Slack message, so re-running a Response on the same message doesn't page twice
+    pub fn message_dedup_key(channel_id: &str, message_ts: &str) -> String {
+        format!("receptionist/{channel_id}/{message_ts}")
+    }
 
-        oncalls_list
-            .oncalls
-            .sort_by(|a, b| a.escalation_level.cmp(&b.escalation_level));
+    /// `(channel id, message ts)` of the message an alert was triggered from, see `message_dedup_key`
+    pub fn parse_message_dedup_key(dedup_key: &str) -> Option<(String, String)> {
+        let (channel_id, message_ts) = dedup_key.strip_prefix("receptionist/")?.split_once('/')?;
 
-        Ok(oncalls_list)
+        Some((channel_id.to_string(), message_ts.to_string()))
     }
 }
 
 impl PagerDuty {
     pub fn new(auth: String, base_url: Option<String>) -> Self {
-        // let https_connector = HttpsConnector::new();
-
-        // let mut root_store = rustls::RootCertStore::empty();
-        // root_store.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
-        //     rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
-        //         ta.subject,
-        //         ta.spki,
-        //         ta.name_constraints,
-        //     )
-        // }));
-        let https = HttpsConnectorBuilder::new()
-            .with_tls_config(
-                rustls::ClientConfig::builder()
-                    .with_safe_defaults()
-                    // .with_native_roots()
-                    // .with_root_certificates(root_store)
-                    .with_native_roots()
-                    // .with_webpki_roots()
-                    .with_no_client_auth(),
-            )
-            .https_or_http()
-            .enable_http1()
-            .build();
-        let client = Client::builder().build::<_, Body>(https);
+        Self::with_client(auth, base_url, new_https_client())
+    }
 
+    /// Reuse an existing client (and its connection pool) instead of creating a new one
+    pub fn with_client(auth: String, base_url: Option<String>, client: HttpsClient) -> Self {
         Self {
             auth,
             base_url: base_url.unwrap_or_else(|| DEFAULT_PD_URL.to_string()),
@@ -82,6 +357,233 @@ impl PagerDuty {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pagerduty::{
+        models::EventPayload,
+        stub_server::{spawn_stub_server, StubResponse},
+    };
+    use serde_json::{json, Value};
+
+    fn oncall(email: &str, level: u8) -> Value {
+        json!({
+            "escalation_policy": {},
+            "user": {"name": email, "email": email, "id": "PUSER", "type": "user"},
+            "schedule": null,
+            "escalation_level": level,
+            "start": null,
+            "end": null
+        })
+    }
+
+    #[tokio::test]
+    async fn should_get_every_page_of_oncalls() {
+        let stub = spawn_stub_server(vec![
+            StubResponse::json(
+                200,
+                json!({"oncalls": [oncall("second@example.com", 2)], "more": true, "offset": 0, "limit": 100}),
+            ),
+            StubResponse::json(
+                200,
+                json!({"oncalls": [oncall("first@example.com", 1)], "more": false, "offset": 1, "limit": 100}),
+            ),
+        ])
+        .await;
+
+        let pd = PagerDuty::new("token".to_string(), Some(stub.base_url.clone()));
+        let oncalls = pd
+            .get_oncalls(&PagerDutyOncallTarget::from_str("schedule:PSCHED1").unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            oncalls
+                .oncalls
+                .iter()
+                .map(|o| o.user.email.as_str())
+                .collect::<Vec<_>>(),
+            vec!["first@example.com", "second@example.com"]
+        );
+
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].path.contains("schedule_ids[]=PSCHED1"));
+        assert!(requests[0].path.ends_with("offset=0"));
+        assert!(requests[1].path.ends_with("offset=1"));
+    }
+
+    #[tokio::test]
+    async fn should_retry_after_rate_limit() {
+        let stub = spawn_stub_server(vec![
+            StubResponse::json(429, json!({"error": {"message": "Rate Limit Exceeded"}}))
+                .with_header("retry-after", "0"),
+            StubResponse::json(200, json!({"oncalls": [oncall("first@example.com", 1)]})),
+        ])
+        .await;
+
+        let pd = PagerDuty::new("token".to_string(), Some(stub.base_url.clone()));
+        let oncalls = pd
+            .get_oncalls(&PagerDutyOncallTarget::EscalationPolicy(
+                "PPOLICY".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(oncalls.oncalls.len(), 1);
+        assert_eq!(stub.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_return_typed_errors_by_status() {
+        let stub = spawn_stub_server(vec![
+            StubResponse::json(401, json!({"error": {"message": "Unauthorized"}})),
+            StubResponse::json(429, json!({})).with_header("retry-after", "60"),
+        ])
+        .await;
+
+        let pd = PagerDuty::new("bad token".to_string(), Some(stub.base_url.clone()));
+        let target = PagerDutyOncallTarget::EscalationPolicy("PPOLICY".to_string());
+
+        let unauthorized = pd.get_oncalls(&target).await.unwrap_err();
+        assert_eq!(
+            unauthorized.downcast_ref::<PagerDutyError>(),
+            Some(&PagerDutyError::Unauthorized)
+        );
+
+        // too long to wait, given up on without retrying
+        let rate_limited = pd.get_oncalls(&target).await.unwrap_err();
+        assert_eq!(
+            rate_limited.downcast_ref::<PagerDutyError>(),
+            Some(&PagerDutyError::RateLimited {
+                retry_after: Some(Duration::from_secs(60))
+            })
+        );
+        assert_eq!(stub.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_search_escalation_policies_and_check_targets() {
+        let stub = spawn_stub_server(vec![
+            StubResponse::json(
+                200,
+                json!({"escalation_policies": [{"id": "PPOLICY", "name": "Payments", "type": "escalation_policy"}]}),
+            ),
+            StubResponse::json(200, json!({"escalation_policy": {"id": "PPOLICY"}})),
+            StubResponse::json(404, json!({"error": {"message": "Not Found"}})),
+        ])
+        .await;
+
+        let pd = PagerDuty::new("token".to_string(), Some(stub.base_url.clone()));
+
+        let policies = pd.search_escalation_policies("pay ments").await.unwrap();
+        assert_eq!(
+            policies,
+            vec![NamedResource {
+                id: "PPOLICY".to_string(),
+                name: "Payments".to_string()
+            }]
+        );
+
+        let policy = PagerDutyOncallTarget::EscalationPolicy("PPOLICY".to_string());
+        assert!(pd.oncall_target_exists(&policy).await.unwrap());
+        let schedule = PagerDutyOncallTarget::Schedule("PGONE".to_string());
+        assert!(!pd.oncall_target_exists(&schedule).await.unwrap());
+
+        let requests = stub.requests();
+        assert_eq!(
+            requests[0].path,
+            "/escalation_policies?query=pay%20ments&limit=25"
+        );
+        assert_eq!(requests[1].path, "/escalation_policies/PPOLICY");
+        assert_eq!(requests[2].path, "/schedules/PGONE");
+    }
+
+    #[test]
+    fn should_parse_oncall_targets() {
+        assert_eq!(
+            PagerDutyOncallTarget::from_str(" PPOLICY ").unwrap(),
+            PagerDutyOncallTarget::EscalationPolicy("PPOLICY".to_string())
+        );
+        assert_eq!(
+            PagerDutyOncallTarget::from_str("schedule: PSCHED1").unwrap(),
+            PagerDutyOncallTarget::Schedule("PSCHED1".to_string())
+        );
+        assert!(PagerDutyOncallTarget::from_str("schedule:").is_err());
+        assert_eq!(
+            PagerDutyOncallTarget::Schedule("PSCHED1".to_string()).to_string(),
+            "schedule:PSCHED1"
+        );
+    }
+
+    #[test]
+    fn should_parse_message_dedup_key() {
+        let dedup_key = PagerDutyEvents::message_dedup_key("C1", "1650000000.000100");
+
+        assert_eq!(
+            PagerDutyEvents::parse_message_dedup_key(&dedup_key),
+            Some(("C1".to_string(), "1650000000.000100".to_string()))
+        );
+        // alerts sent by other integrations
+        assert_eq!(
+            PagerDutyEvents::parse_message_dedup_key("e2f1c6b0a6c1"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn should_send_event_to_configured_base_url() {
+        let stub = spawn_stub_server(vec![StubResponse::json(
+            202,
+            json!({"status": "success", "message": "Event processed", "dedup_key": "receptionist/C1/1.2"}),
+        )])
+        .await;
+
+        let events = PagerDutyEvents::new(Some(stub.base_url.clone()));
+        let response = events
+            .send_event(&EventV2 {
+                routing_key: "R0UT1NGKEY".to_string(),
+                event_action: "trigger".to_string(),
+                dedup_key: Some(PagerDutyEvents::message_dedup_key("C1", "1.2")),
+                payload: Some(EventPayload {
+                    summary: "prod is down".to_string(),
+                    source: "Slack channel C1".to_string(),
+                    severity: "critical".to_string(),
+                    custom_details: None,
+                }),
+                links: vec![],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.dedup_key.as_deref(), Some("receptionist/C1/1.2"));
+
+        let requests = stub.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/v2/enqueue");
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["routing_key"], "R0UT1NGKEY");
+        assert_eq!(body["payload"]["severity"], "critical");
+    }
+
+    #[tokio::test]
+    async fn should_error_when_event_is_rejected() {
+        let stub = spawn_stub_server(vec![StubResponse::json(
+            400,
+            json!({"status": "invalid event", "message": "Event object is invalid"}),
+        )])
+        .await;
+
+        let events = PagerDutyEvents::new(Some(stub.base_url.clone()));
+        let result = events
+            .send_event(&EventV2 {
+                routing_key: String::default(),
+                event_action: "trigger".to_string(),
+                dedup_key: None,
+                payload: None,
+                links: vec![],
+            })
+            .await;
+
+        assert!(result.is_err());
+    }
 
     #[tokio::test]
     #[ignore]
@@ -94,7 +596,11 @@ mod tests {
         );
 
         // let resp = pd.get_oncalls(String::from("PS32312")).await;
-        let resp = pd.get_oncalls(String::from("PLMIEBZ")).await;
+        let resp = pd
+            .get_oncalls(&PagerDutyOncallTarget::EscalationPolicy(String::from(
+                "PLMIEBZ",
+            )))
+            .await;
 
         match resp {
             Ok(ok) => {
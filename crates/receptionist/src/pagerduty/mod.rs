@@ -1,2 +1,5 @@
 pub mod client;
 pub mod models;
+#[cfg(test)]
+pub(crate) mod stub_server;
+pub mod webhook;
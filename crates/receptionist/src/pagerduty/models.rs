@@ -1,60 +1,168 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-pub struct OncallList {
-    pub oncalls: Vec<OncallInstance>,
-}
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-pub struct OncallInstance {
-    escalation_policy: EscalationPolicy,
-    pub user: User,
-    schedule: Option<Schedule>,
-    pub escalation_level: u8,
-    start: Option<String>,
-    end: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-struct EscalationPolicy {}
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-pub struct User {
-    pub name: String,
-    pub email: String,
-    pub id: String,
-    #[serde(rename = "type")]
-    pub user_type: String,
-}
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-struct Schedule {}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::{from_value, json, to_value};
-
-    #[test]
-    fn should_serialize_oncall_list() {
-        let oncalls_list_json = json!(
-            {"oncalls" : [{
-                "escalation_policy": {},
-                "user": {
-                    "name": "receptionist bot",
-                    "email": "bot@receptionist.com",
-                    "id": "PS12345",
-                    "type": "admin"
-                },
-                "schedule": {},
-                "escalation_level": 1,
-                "start": "any date string",
-                "end": "any date string",
-            }]}
-        );
-
-        let oncalls_list: OncallList = from_value(oncalls_list_json.clone()).unwrap();
-
-        assert_eq!(to_value(oncalls_list).unwrap(), oncalls_list_json);
-    }
-}
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct OncallList {
+    pub oncalls: Vec<OncallInstance>,
+    /// classic pagination, `true` when there are more results after this page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub more: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OncallInstance {
+    escalation_policy: EscalationPolicy,
+    pub user: User,
+    schedule: Option<Schedule>,
+    pub escalation_level: u8,
+    start: Option<String>,
+    end: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct EscalationPolicy {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct User {
+    pub name: String,
+    pub email: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub user_type: String,
+    /// profile page, only included with `include[]=users`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Schedule {}
+
+/// Escalation policy or schedule, as listed by `GET /escalation_policies` and `GET /schedules`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct NamedResource {
+    pub id: String,
+    pub name: String,
+}
+
+/// Response of `GET /escalation_policies`
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EscalationPolicyList {
+    pub escalation_policies: Vec<NamedResource>,
+}
+
+/// Response of `GET /schedules`
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ScheduleList {
+    pub schedules: Vec<NamedResource>,
+}
+
+/// Body of a PagerDuty v3 webhook request
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct WebhookEvent {
+    pub id: String,
+    /// ex: `incident.triggered`, `incident.acknowledged`, `incident.resolved`
+    pub event_type: String,
+    pub resource_type: String,
+    pub occurred_at: String,
+    /// who caused the event, missing for events PagerDuty caused itself
+    pub agent: Option<Reference>,
+    /// shape depends on `resource_type`, see `Incident` for incident events
+    pub data: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Incident {
+    pub id: String,
+    pub number: u64,
+    pub title: String,
+    pub status: String,
+    pub html_url: String,
+    pub urgency: Option<String>,
+    pub service: Reference,
+    /// Dedup key of the alert that opened the incident, if it came from the Events API
+    #[serde(default)]
+    pub incident_key: Option<String>,
+}
+
+/// Another PagerDuty object referenced by an event
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Reference {
+    pub id: String,
+    pub summary: Option<String>,
+    pub html_url: Option<String>,
+}
+
+/// Events API v2 request body, authenticated by the integration's routing key
+/// https://developer.pagerduty.com/docs/ZG9jOjExMDI5NTgw-events-api-v2-overview
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EventV2 {
+    pub routing_key: String,
+    /// `trigger`, `acknowledge` or `resolve`
+    pub event_action: String,
+    /// events with the same key are grouped into one alert, so repeats don't page twice
+    pub dedup_key: Option<String>,
+    pub payload: Option<EventPayload>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<EventLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EventPayload {
+    pub summary: String,
+    pub source: String,
+    /// `critical`, `error`, `warning` or `info`
+    pub severity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_details: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EventLink {
+    pub href: String,
+    pub text: String,
+}
+
+/// Response of the Events API v2, `status` is `success` when the event was accepted
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EventResponse {
+    pub status: String,
+    pub message: String,
+    pub dedup_key: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_value, json, to_value};
+
+    #[test]
+    fn should_serialize_oncall_list() {
+        let oncalls_list_json = json!(
+            {"oncalls" : [{
+                "escalation_policy": {},
+                "user": {
+                    "name": "receptionist bot",
+                    "email": "bot@receptionist.com",
+                    "id": "PS12345",
+                    "type": "admin"
+                },
+                "schedule": {},
+                "escalation_level": 1,
+                "start": "any date string",
+                "end": "any date string",
+            }]}
+        );
+
+        let oncalls_list: OncallList = from_value(oncalls_list_json.clone()).unwrap();
+
+        assert_eq!(to_value(oncalls_list).unwrap(), oncalls_list_json);
+    }
+}
@@ -0,0 +1,120 @@
+//! Local HTTP server standing in for PagerDuty/Opsgenie in tests, point a client's base URL at `base_url`
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde_json::Value;
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone)]
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubResponse {
+    pub fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// path and query, ex: `/oncalls?earliest=true`
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+pub struct StubServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StubServer {
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Serve `responses` in order, one per request. Requests after the last response get a 500
+pub async fn spawn_stub_server(responses: Vec<StubResponse>) -> StubServer {
+    let responses = Arc::new(Mutex::new(VecDeque::from(responses)));
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let make_service = {
+        let responses = responses.clone();
+        let requests = requests.clone();
+
+        make_service_fn(move |_conn| {
+            let responses = responses.clone();
+            let requests = requests.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let responses = responses.clone();
+                    let requests = requests.clone();
+
+                    async move {
+                        let (parts, body) = request.into_parts();
+                        let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+
+                        requests.lock().unwrap().push(RecordedRequest {
+                            method: parts.method.to_string(),
+                            path: parts
+                                .uri
+                                .path_and_query()
+                                .map(|pq| pq.to_string())
+                                .unwrap_or_default(),
+                            body: body.to_vec(),
+                        });
+
+                        let stub = responses.lock().unwrap().pop_front();
+                        Ok::<_, Infallible>(to_response(stub))
+                    }
+                }))
+            }
+        })
+    };
+
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let base_url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+
+    StubServer { base_url, requests }
+}
+
+fn to_response(stub: Option<StubResponse>) -> Response<Body> {
+    let stub = match stub {
+        Some(stub) => stub,
+        None => {
+            let mut response = Response::new(Body::from("no stub response left"));
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            return response;
+        }
+    };
+
+    let mut builder = Response::builder().status(stub.status);
+    for (name, value) in stub.headers {
+        builder = builder.header(name, value);
+    }
+
+    builder
+        .body(Body::from(stub.body))
+        .expect("invalid stub response")
+}
@@ -0,0 +1,400 @@
+//! PagerDuty v3 webhooks: post to Slack when incidents trigger and follow up in the thread as they change
+#[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+use crate::database::{
+    claim_delivered_event, get_incident_threads, get_responses_for_listener, record_incident_thread,
+};
+use crate::{
+    config::get_or_init_app_config,
+    pagerduty::models::{Incident, WebhookEvent, WebhookPayload},
+    run_response_actions, ActionContext, DeliveredEvent, PagerDutyEvents, ReceptionistListener,
+    ReceptionistResponse, SlackStateWorkaround,
+};
+use anyhow::{anyhow, bail, Result};
+use axum::{
+    body::Bytes,
+    extract::Extension,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use slack_morphism::prelude::*;
+use slack_morphism_hyper::SlackClientHyperHttpsConnector;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{error, info, warn};
+
+pub const PAGERDUTY_SIGNATURE_HEADER: &str = "x-pagerduty-signature";
+
+const INCIDENT_TRIGGERED: &str = "incident.triggered";
+const INCIDENT_ACKNOWLEDGED: &str = "incident.acknowledged";
+const INCIDENT_RESOLVED: &str = "incident.resolved";
+
+pub async fn axum_handler_pagerduty_webhook(
+    Extension(slack_state): Extension<Arc<SlackStateWorkaround>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let signature = headers
+        .get(PAGERDUTY_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    handle_pagerduty_webhook(&*slack_state, signature, &body).await
+}
+
+pub async fn handle_pagerduty_webhook(
+    slack_state: &SlackStateWorkaround,
+    signature: Option<&str>,
+    body: &[u8],
+) -> StatusCode {
+    let secret = match &get_or_init_app_config().await.pagerduty_webhook_secret {
+        Some(secret) => secret,
+        None => {
+            error!("No PagerDuty webhook secret configured, rejecting webhook");
+            return StatusCode::UNAUTHORIZED;
+        }
+    };
+
+    if let Err(e) = signature
+        .ok_or_else(|| anyhow!("Missing PagerDuty signature header"))
+        .and_then(|signature| verify_pagerduty_signature(secret, signature, body))
+    {
+        warn!("Rejected PagerDuty webhook: {}", e);
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let event = match serde_json::from_slice::<WebhookPayload>(body) {
+        Ok(payload) => payload.event,
+        Err(e) => {
+            warn!("Unable to parse PagerDuty webhook: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    if event.resource_type != "incident" || !is_first_delivery(&event.id).await {
+        return StatusCode::OK;
+    }
+
+    if let Err(e) = handle_incident_event(slack_state, &event).await {
+        error!("Error handling PagerDuty event {}: {}", event.id, e);
+    }
+
+    // PagerDuty retries failed deliveries, only ask for a retry when the request itself was bad
+    StatusCode::OK
+}
+
+/// PagerDuty redelivers events it isn't sure arrived, only the first delivery is handled.
+/// Claimed before handling so a redelivery that arrives mid-run doesn't post a second root message
+async fn is_first_delivery(event_id: &str) -> bool {
+    match claim_delivered_event(&DeliveredEvent::PagerDuty(event_id.to_string())).await {
+        Ok(true) => true,
+        Ok(false) => {
+            info!("Ignoring redelivered PagerDuty event {}", event_id);
+            false
+        }
+        Err(e) => {
+            // better to risk posting twice than to lose the event
+            error!(
+                "Unable to check if PagerDuty event {} was delivered before: {}",
+                event_id, e
+            );
+            true
+        }
+    }
+}
+
+async fn handle_incident_event(
+    slack_state: &SlackStateWorkaround,
+    event: &WebhookEvent,
+) -> Result<()> {
+    let incident: Incident = serde_json::from_value(event.data.clone())?;
+    let slack_session = slack_state.open_session();
+
+    match event.event_type.as_str() {
+        INCIDENT_TRIGGERED => {
+            if let Some((channel_id, message_ts)) = incident
+                .incident_key
+                .as_deref()
+                .and_then(PagerDutyEvents::parse_message_dedup_key)
+            {
+                link_incident_in_thread(&slack_session, &incident, channel_id, message_ts).await;
+            }
+
+            let rec_responses =
+                get_responses_for_listener(ReceptionistListener::PagerDutyIncident {
+                    service_id: incident.service.id.to_owned(),
+                    channel_id: String::default(),
+                })
+                .await?;
+
+            for rec_response in &rec_responses {
+                let channel_id = match &rec_response.listener {
+                    ReceptionistListener::PagerDutyIncident { channel_id, .. } => channel_id,
+                    _ => continue,
+                };
+
+                if let Err(e) = run_incident_response(
+                    &slack_session,
+                    rec_response,
+                    channel_id,
+                    event,
+                    &incident,
+                )
+                .await
+                {
+                    error!(
+                        "Error running response {} for PagerDuty event {}: {}",
+                        rec_response.id, event.id, e
+                    );
+                }
+            }
+        }
+        INCIDENT_ACKNOWLEDGED | INCIDENT_RESOLVED => {
+            let update = incident_update_text(event, &incident);
+
+            for (channel_id, message_ts) in get_incident_threads(&incident.id).await? {
+                if let Err(slack_err) = slack_session
+                    .chat_post_message(
+                        &SlackApiChatPostMessageRequest::new(
+                            channel_id.into(),
+                            SlackMessageContent::new().with_text(update.to_owned()),
+                        )
+                        .with_thread_ts(message_ts.into()),
+                    )
+                    .await
+                {
+                    error!("Unable to post incident update to thread: {}", slack_err);
+                }
+            }
+        }
+        other => info!("Ignoring PagerDuty event type {}", other),
+    }
+
+    Ok(())
+}
+
+/// Alerts triggered from a Slack message only know their dedup key when they are sent,
+/// the incident they open is linked in the message's thread once PagerDuty creates it
+async fn link_incident_in_thread(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    incident: &Incident,
+    channel_id: String,
+    message_ts: String,
+) {
+    if let Err(slack_err) = slack_session
+        .chat_post_message(
+            &SlackApiChatPostMessageRequest::new(
+                channel_id.to_owned().into(),
+                SlackMessageContent::new().with_text(incident_link_text(incident)),
+            )
+            .with_thread_ts(message_ts.to_owned().into()),
+        )
+        .await
+    {
+        error!(
+            "Unable to link incident {} in thread: {}",
+            incident.id, slack_err
+        );
+        return;
+    }
+
+    // acknowledged and resolved updates follow up in the same thread
+    if let Err(e) = record_incident_thread(&incident.id, &channel_id, &message_ts).await {
+        error!(
+            "Unable to record incident thread for {}: {}",
+            incident.id, e
+        );
+    }
+}
+
+/// Run a Response for a triggered incident, remembering its messages so updates reply in their threads
+async fn run_incident_response(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    rec_response: &ReceptionistResponse,
+    channel_id: &str,
+    event: &WebhookEvent,
+    incident: &Incident,
+) -> Result<()> {
+    let context = ActionContext {
+        channel_id: channel_id.to_owned().into(),
+        message_ts: None,
+        sender: String::default(),
+        template_vars: incident_template_vars(event, incident, channel_id),
+    };
+
+    for message_ts in run_response_actions(slack_session, rec_response, &context).await {
+        if let Err(e) = record_incident_thread(&incident.id, channel_id, message_ts.as_ref()).await
+        {
+            error!(
+                "Unable to record incident thread for {}: {}",
+                incident.id, e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Values that incident actions can reference as `{{name}}`
+fn incident_template_vars(
+    event: &WebhookEvent,
+    incident: &Incident,
+    channel_id: &str,
+) -> HashMap<String, String> {
+    HashMap::from([
+        ("channel_id".to_string(), channel_id.to_string()),
+        ("event_type".to_string(), event.event_type.to_owned()),
+        ("incident_id".to_string(), incident.id.to_owned()),
+        ("incident_number".to_string(), incident.number.to_string()),
+        ("incident_title".to_string(), incident.title.to_owned()),
+        ("incident_status".to_string(), incident.status.to_owned()),
+        ("incident_url".to_string(), incident.html_url.to_owned()),
+        (
+            "incident_urgency".to_string(),
+            incident.urgency.to_owned().unwrap_or_default(),
+        ),
+        ("service_id".to_string(), incident.service.id.to_owned()),
+        (
+            "service_name".to_string(),
+            incident.service.summary.to_owned().unwrap_or_default(),
+        ),
+        ("agent_name".to_string(), agent_name(event)),
+    ])
+}
+
+fn agent_name(event: &WebhookEvent) -> String {
+    event
+        .agent
+        .as_ref()
+        .and_then(|agent| agent.summary.to_owned())
+        .unwrap_or_else(|| "PagerDuty".to_string())
+}
+
+fn incident_update_text(event: &WebhookEvent, incident: &Incident) -> String {
+    let status = match event.event_type.as_str() {
+        INCIDENT_ACKNOWLEDGED => ":eyes: Acknowledged",
+        _ => ":white_check_mark: Resolved",
+    };
+
+    format!(
+        "{status} by {} - <{}|#{} {}>",
+        agent_name(event),
+        incident.html_url,
+        incident.number,
+        incident.title
+    )
+}
+
+fn incident_link_text(incident: &Incident) -> String {
+    format!(
+        ":rotating_light: PagerDuty incident <{}|#{} {}>",
+        incident.html_url, incident.number, incident.title
+    )
+}
+
+/// PagerDuty sends a signature per active secret: `v1=<hex>,v1=<hex>`, any match is valid
+pub fn verify_pagerduty_signature(secret: &str, signatures: &str, body: &[u8]) -> Result<()> {
+    for signature in signatures.split(',') {
+        let signature_bytes = match signature
+            .trim()
+            .strip_prefix("v1=")
+            .and_then(|hex_signature| hex::decode(hex_signature).ok())
+        {
+            Some(signature_bytes) => signature_bytes,
+            None => continue,
+        };
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|e| anyhow!("Invalid PagerDuty webhook secret: {}", e))?;
+        mac.update(body);
+
+        if mac.verify_slice(&signature_bytes).is_ok() {
+            return Ok(());
+        }
+    }
+
+    bail!("No PagerDuty signature matched")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verify_pagerduty_signature() {
+        let body = br#"{"event":{}}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(body);
+        let valid = format!("v1={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert!(verify_pagerduty_signature("secret", &valid, body).is_ok());
+        // rotated secrets send several signatures
+        assert!(verify_pagerduty_signature("secret", &format!("v1=abcd,{valid}"), body).is_ok());
+        assert!(verify_pagerduty_signature("other", &valid, body).is_err());
+        assert!(verify_pagerduty_signature("secret", "v1=abcd", body).is_err());
+    }
+
+    #[test]
+    fn test_incident_event_fields() {
+        let payload: WebhookPayload = serde_json::from_value(json!({
+            "event": {
+                "id": "01CELK9EZ2NQ2XAG3VBN4G8OQ8",
+                "event_type": "incident.acknowledged",
+                "resource_type": "incident",
+                "occurred_at": "2022-03-01T17:00:00.000Z",
+                "agent": {"id": "PUSER01", "summary": "Jane Doe", "html_url": null, "type": "user_reference"},
+                "data": {
+                    "id": "PINC001",
+                    "type": "incident",
+                    "number": 42,
+                    "title": "Disk full on db-1",
+                    "status": "acknowledged",
+                    "html_url": "https://example.pagerduty.com/incidents/PINC001",
+                    "urgency": "high",
+                    "service": {"id": "PSVC001", "summary": "Database", "html_url": null}
+                }
+            }
+        }))
+        .unwrap();
+
+        let incident: Incident = serde_json::from_value(payload.event.data.clone()).unwrap();
+        let vars = incident_template_vars(&payload.event, &incident, "C123");
+
+        assert_eq!(vars["incident_number"], "42");
+        assert_eq!(vars["service_name"], "Database");
+        assert_eq!(vars["agent_name"], "Jane Doe");
+        assert_eq!(incident.incident_key, None);
+        assert_eq!(
+            incident_update_text(&payload.event, &incident),
+            ":eyes: Acknowledged by Jane Doe - <https://example.pagerduty.com/incidents/PINC001|#42 Disk full on db-1>"
+        );
+    }
+
+    #[test]
+    fn test_triggered_incident_links_its_message() {
+        let incident: Incident = serde_json::from_value(json!({
+            "id": "PINC002",
+            "type": "incident",
+            "number": 43,
+            "title": "deploy failed",
+            "status": "triggered",
+            "html_url": "https://example.pagerduty.com/incidents/PINC002",
+            "urgency": "high",
+            "incident_key": "receptionist/C0001/1650000000.000100",
+            "service": {"id": "PSVC001", "summary": "Database", "html_url": null}
+        }))
+        .unwrap();
+
+        assert_eq!(
+            incident
+                .incident_key
+                .as_deref()
+                .and_then(PagerDutyEvents::parse_message_dedup_key),
+            Some(("C0001".to_string(), "1650000000.000100".to_string()))
+        );
+        assert_eq!(
+            incident_link_text(&incident),
+            ":rotating_light: PagerDuty incident <https://example.pagerduty.com/incidents/PINC002|#43 deploy failed>"
+        );
+    }
+}
@@ -1,8 +1,15 @@
 use crate::{
-    response::utils::slack_plain_text_input_block_for_view, BlockSectionRouter,
-    ReceptionistListener, SlackBlockValidationError,
+    response::{
+        block_kit::{block_kit_builder_url, blocks_json_from_text, validate_block_kit_json},
+        utils::{slack_multiline_input_block_for_view, slack_plain_text_input_block_for_view},
+    },
+    BlockSectionRouter, OncallProviderKind, OncallTarget, OncallTopicLocation,
+    OncallUnavailableAction, ReceptionistListener, SlackBlockValidationError,
+    WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
+use hyper::Uri;
+use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
 use slack_morphism::prelude::*;
 use std::str::FromStr;
@@ -12,6 +19,9 @@ use strum::{Display, EnumDiscriminants, EnumIter, EnumString, IntoEnumIterator};
 #[serde(rename_all = "snake_case", tag = "type", content = "value")]
 pub enum ReceptionistAction {
     ForMessage(MessageAction),
+    ForCommand(CommandAction),
+    ForMember(MemberAction),
+    ForChannel(ChannelAction),
 }
 
 impl ReceptionistAction {
@@ -36,24 +46,48 @@ impl ReceptionistAction {
                         error_message: "message is empty".to_string(),
                     })
                 }
+                MessageAction::EphemeralToSender(msg_str)
+                | MessageAction::DirectMessageToSender(msg_str) => {
+                    msg_str.is_empty().then(|| SlackBlockValidationError {
+                        block_id: BlockSectionRouter::SenderMsgInput.to_block_id(index),
+                        error_message: "message is empty".to_string(),
+                    })
+                }
+                MessageAction::Webhook(webhook) => webhook.validate(index),
+                MessageAction::TriggerWorkflow(trigger) => trigger.validate(index),
+                MessageAction::TriggerPagerDutyIncident(trigger) => trigger.validate(index),
+                MessageAction::ThreadedBlocks(blocks_json)
+                | MessageAction::ChannelBlocks(blocks_json) => {
+                    validate_blocks_json(blocks_json, index)
+                }
                 MessageAction::MsgOncallInThread {
-                    escalation_policy_id,
-                    message,
+                    target, message, ..
                 } => {
                     if message.is_empty() {
                         Some(SlackBlockValidationError {
                             block_id: BlockSectionRouter::PostChannelMsgInput.to_block_id(index),
                             error_message: "message is empty".to_string(),
                         })
-                    } else if escalation_policy_id.is_empty() {
-                        Some(SlackBlockValidationError {
-                            block_id: BlockSectionRouter::PostChannelMsgInput.to_block_id(index),
-                            error_message: "no escalation policy provided".to_string(),
-                        })
                     } else {
-                        None
+                        validate_oncall_target(
+                            target,
+                            BlockSectionRouter::PDEscalationPolicyInput,
+                            index,
+                        )
                     }
                 }
+                MessageAction::InviteOncallToChannel {
+                    target,
+                    usergroup_id,
+                    ..
+                } => match usergroup_id {
+                    Some(usergroup_id) => validate_usergroup_id(usergroup_id, index),
+                    None => validate_oncall_target(
+                        target,
+                        BlockSectionRouter::PDEscalationPolicyInput,
+                        index,
+                    ),
+                },
                 MessageAction::ForwardMessageToChannel {
                     channel,
                     msg_context,
@@ -75,17 +109,101 @@ impl ReceptionistAction {
                     }
                 }
             },
+            ReceptionistAction::ForCommand(command_action) => match command_action {
+                CommandAction::EphemeralReply(msg_str) | CommandAction::ChannelReply(msg_str) => {
+                    msg_str.is_empty().then(|| SlackBlockValidationError {
+                        block_id: BlockSectionRouter::CommandReplyMsgInput.to_block_id(index),
+                        error_message: "message is empty".to_string(),
+                    })
+                }
+            },
+            ReceptionistAction::ForMember(member_action) => match member_action {
+                MemberAction::EphemeralWelcome(msg_str)
+                | MemberAction::DirectMessageWelcome(msg_str) => {
+                    msg_str.is_empty().then(|| SlackBlockValidationError {
+                        block_id: BlockSectionRouter::MemberWelcomeMsgInput.to_block_id(index),
+                        error_message: "message is empty".to_string(),
+                    })
+                }
+            },
+            ReceptionistAction::ForChannel(channel_action) => match channel_action {
+                ChannelAction::ChannelMessage(msg_str) => {
+                    msg_str.is_empty().then(|| SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ChannelActionMsgInput.to_block_id(index),
+                        error_message: "message is empty".to_string(),
+                    })
+                }
+                ChannelAction::ChannelBlocks(blocks_json) => {
+                    validate_blocks_json(blocks_json, index)
+                }
+                ChannelAction::OncallMessage {
+                    target, message, ..
+                } => {
+                    if message.is_empty() {
+                        Some(SlackBlockValidationError {
+                            block_id: BlockSectionRouter::ChannelActionMsgInput.to_block_id(index),
+                            error_message: "message is empty".to_string(),
+                        })
+                    } else {
+                        validate_oncall_target(
+                            target,
+                            BlockSectionRouter::ChannelActionEscalationPolicyInput,
+                            index,
+                        )
+                    }
+                }
+                ChannelAction::OncallTopic { target, label, .. } => {
+                    if label.trim().is_empty() {
+                        Some(SlackBlockValidationError {
+                            block_id: BlockSectionRouter::OncallTopicLabelInput.to_block_id(index),
+                            error_message: "label is empty".to_string(),
+                        })
+                    } else {
+                        validate_oncall_target(
+                            target,
+                            BlockSectionRouter::ChannelActionEscalationPolicyInput,
+                            index,
+                        )
+                    }
+                }
+            },
         }
     }
 
     pub fn default_from_listener(listener: &ReceptionistListener) -> Self {
         match listener {
-            ReceptionistListener::SlackChannel { .. } => {
+            ReceptionistListener::SlackChannel { .. }
+            | ReceptionistListener::SlackChannels { .. }
+            | ReceptionistListener::SlackChannelPattern { .. }
+            | ReceptionistListener::AllPublicChannels
+            | ReceptionistListener::AppMention { .. }
+            | ReceptionistListener::DirectMessage { .. } => {
                 Self::ForMessage(MessageAction::AttachEmoji("".to_string()))
             }
+            ReceptionistListener::SlackCommandKeyword { .. } => {
+                Self::ForCommand(CommandAction::EphemeralReply("".to_string()))
+            }
+            ReceptionistListener::MemberJoinedChannel { .. } => {
+                Self::ForMember(MemberAction::EphemeralWelcome("".to_string()))
+            }
+            ReceptionistListener::Schedule { .. }
+            | ReceptionistListener::Webhook { .. }
+            | ReceptionistListener::PagerDutyIncident { .. } => {
+                Self::ForChannel(ChannelAction::ChannelMessage("".to_string()))
+            }
         }
     }
 
+    /// Actions are grouped by the kind of event that triggers them,
+    /// ex: a slash command has no message to attach an emoji to
+    pub fn is_for_listener(&self, listener: &ReceptionistListener) -> bool {
+        let discrim: ReceptionistActionDiscriminants = self.into();
+        let listener_discrim: ReceptionistActionDiscriminants =
+            Self::default_from_listener(listener).into();
+
+        discrim == listener_discrim
+    }
+
     pub fn default_blocks(
         listener: &ReceptionistListener,
         index: Option<usize>,
@@ -96,6 +214,9 @@ impl ReceptionistAction {
     pub fn to_choice_items(&self) -> Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> {
         match self {
             ReceptionistAction::ForMessage(..) => MessageAction::to_choice_items(),
+            ReceptionistAction::ForCommand(..) => CommandAction::to_choice_items(),
+            ReceptionistAction::ForMember(..) => MemberAction::to_choice_items(),
+            ReceptionistAction::ForChannel(..) => ChannelAction::to_choice_items(),
         }
     }
 
@@ -104,6 +225,98 @@ impl ReceptionistAction {
             ReceptionistAction::ForMessage(message_action) => {
                 message_action.to_editor_blocks(index)
             }
+            ReceptionistAction::ForCommand(command_action) => {
+                command_action.to_editor_blocks(index)
+            }
+            ReceptionistAction::ForMember(member_action) => member_action.to_editor_blocks(index),
+            ReceptionistAction::ForChannel(channel_action) => {
+                channel_action.to_editor_blocks(index)
+            }
+        }
+    }
+
+    /// Switch an oncall action's provider, the target is cleared since it means nothing to another provider
+    pub fn update_oncall_provider(&mut self, provider_str: &str) -> Result<()> {
+        let selected = OncallProviderKind::from_str(provider_str)?;
+
+        match self {
+            ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread {
+                target,
+                target_name,
+                ..
+            })
+            | ReceptionistAction::ForMessage(MessageAction::InviteOncallToChannel {
+                target,
+                target_name,
+                ..
+            })
+            | ReceptionistAction::ForChannel(ChannelAction::OncallMessage {
+                target,
+                target_name,
+                ..
+            })
+            | ReceptionistAction::ForChannel(ChannelAction::OncallTopic {
+                target,
+                target_name,
+                ..
+            }) => {
+                if target.provider() != selected {
+                    *target = OncallTarget::new(selected, "");
+                    target_name.clear();
+                }
+                Ok(())
+            }
+            _ => bail!("wrong action type for oncall provider selection"),
+        }
+    }
+
+    /// Escalation policy or schedule picked from the PagerDuty search, as the option's value and text
+    pub fn update_pagerduty_target(&mut self, selected: Option<(String, String)>) -> Result<()> {
+        match self {
+            ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread {
+                target,
+                target_name,
+                ..
+            })
+            | ReceptionistAction::ForMessage(MessageAction::InviteOncallToChannel {
+                target,
+                target_name,
+                ..
+            })
+            | ReceptionistAction::ForChannel(ChannelAction::OncallMessage {
+                target,
+                target_name,
+                ..
+            })
+            | ReceptionistAction::ForChannel(ChannelAction::OncallTopic {
+                target,
+                target_name,
+                ..
+            }) => {
+                let (value, name) = selected.unwrap_or_default();
+                *target = OncallTarget::new(OncallProviderKind::PagerDuty, &value);
+                *target_name = name;
+                Ok(())
+            }
+            _ => bail!("wrong action type for escalation policy selection"),
+        }
+    }
+
+    /// Switch an invite action between the oncall and a Slack user group
+    pub fn update_invitee(&mut self, invitee: &str) -> Result<()> {
+        match self {
+            ReceptionistAction::ForMessage(MessageAction::InviteOncallToChannel {
+                usergroup_id,
+                ..
+            }) => {
+                *usergroup_id = match invitee {
+                    INVITE_ONCALL => None,
+                    INVITE_USERGROUP => Some(usergroup_id.take().unwrap_or_default()),
+                    _ => bail!("invalid invitee: {invitee}"),
+                };
+                Ok(())
+            }
+            _ => bail!("wrong action type for invitee selection"),
         }
     }
 
@@ -134,37 +347,154 @@ impl ReceptionistAction {
                 let old_string = match message_action {
                     MessageAction::AttachEmoji(current)
                     | MessageAction::ThreadedMessage(current)
-                    | MessageAction::ChannelMessage(current) => current,
-                    MessageAction::MsgOncallInThread { message, .. } => message,
-                    MessageAction::ForwardMessageToChannel { msg_context, .. } => msg_context,
+                    | MessageAction::ChannelMessage(current)
+                    | MessageAction::EphemeralToSender(current)
+                    | MessageAction::DirectMessageToSender(current)
+                    | MessageAction::ThreadedBlocks(current)
+                    | MessageAction::ChannelBlocks(current) => std::mem::take(current),
+                    MessageAction::MsgOncallInThread { message, .. } => std::mem::take(message),
+                    MessageAction::InviteOncallToChannel { .. } => String::default(),
+                    MessageAction::ForwardMessageToChannel { msg_context, .. } => {
+                        std::mem::take(msg_context)
+                    }
+                    MessageAction::Webhook(webhook) => std::mem::take(&mut webhook.body),
+                    MessageAction::TriggerWorkflow(trigger) => {
+                        std::mem::take(&mut trigger.variables)
+                    }
+                    MessageAction::TriggerPagerDutyIncident(trigger) => {
+                        std::mem::take(&mut trigger.summary)
+                    }
                 };
 
                 *message_action = match new_action_discrim {
                     MessageActionDiscriminants::AttachEmoji => {
-                        MessageAction::AttachEmoji(std::mem::take(old_string))
+                        MessageAction::AttachEmoji(old_string)
                     }
                     MessageActionDiscriminants::ThreadedMessage => {
-                        MessageAction::ThreadedMessage(std::mem::take(old_string))
+                        MessageAction::ThreadedMessage(old_string)
                     }
                     MessageActionDiscriminants::ChannelMessage => {
-                        MessageAction::ChannelMessage(std::mem::take(old_string))
+                        MessageAction::ChannelMessage(old_string)
+                    }
+                    MessageActionDiscriminants::EphemeralToSender => {
+                        MessageAction::EphemeralToSender(old_string)
+                    }
+                    MessageActionDiscriminants::DirectMessageToSender => {
+                        MessageAction::DirectMessageToSender(old_string)
+                    }
+                    MessageActionDiscriminants::ThreadedBlocks => {
+                        MessageAction::ThreadedBlocks(into_blocks_json(old_string))
+                    }
+                    MessageActionDiscriminants::ChannelBlocks => {
+                        MessageAction::ChannelBlocks(into_blocks_json(old_string))
                     }
                     MessageActionDiscriminants::MsgOncallInThread => {
                         MessageAction::MsgOncallInThread {
-                            escalation_policy_id: String::default(),
-                            message: std::mem::take(old_string),
+                            target: OncallTarget::default(),
+                            target_name: String::default(),
+                            message: old_string,
+                            mention_level: None,
+                            unavailable_action: OncallUnavailableAction::default(),
                         }
                     }
                     MessageActionDiscriminants::ForwardMessageToChannel => {
                         MessageAction::ForwardMessageToChannel {
                             channel: String::default(),
-                            msg_context: std::mem::take(old_string),
+                            msg_context: old_string,
+                        }
+                    }
+                    // message text isn't an oncall target, start with the oncall of the default provider
+                    MessageActionDiscriminants::InviteOncallToChannel => {
+                        MessageAction::InviteOncallToChannel {
+                            target: OncallTarget::default(),
+                            target_name: String::default(),
+                            usergroup_id: None,
                         }
                     }
+                    // message text isn't a JSON body, start the request from scratch
+                    MessageActionDiscriminants::Webhook => {
+                        MessageAction::Webhook(WebhookRequest::new())
+                    }
+                    MessageActionDiscriminants::TriggerWorkflow => {
+                        MessageAction::TriggerWorkflow(WorkflowTrigger::new())
+                    }
+                    MessageActionDiscriminants::TriggerPagerDutyIncident => {
+                        MessageAction::TriggerPagerDutyIncident(PagerDutyIncidentTrigger {
+                            summary: old_string,
+                            ..PagerDutyIncidentTrigger::default()
+                        })
+                    }
                 };
 
                 // *message_action = new_action;
             }
+            Self::ForCommand(command_action) => {
+                let new_action = CommandAction::from_str(type_str)?;
+
+                // retain existing msg input when changing action types to save user retyping the message
+                let old_string = match command_action {
+                    CommandAction::EphemeralReply(current)
+                    | CommandAction::ChannelReply(current) => std::mem::take(current),
+                };
+
+                *command_action = match new_action {
+                    CommandAction::EphemeralReply(_) => CommandAction::EphemeralReply(old_string),
+                    CommandAction::ChannelReply(_) => CommandAction::ChannelReply(old_string),
+                };
+            }
+            Self::ForMember(member_action) => {
+                let new_action = MemberAction::from_str(type_str)?;
+
+                // retain existing msg input when changing action types to save user retyping the message
+                let old_string = match member_action {
+                    MemberAction::EphemeralWelcome(current)
+                    | MemberAction::DirectMessageWelcome(current) => std::mem::take(current),
+                };
+
+                *member_action = match new_action {
+                    MemberAction::EphemeralWelcome(_) => MemberAction::EphemeralWelcome(old_string),
+                    MemberAction::DirectMessageWelcome(_) => {
+                        MemberAction::DirectMessageWelcome(old_string)
+                    }
+                };
+            }
+            Self::ForChannel(channel_action) => {
+                let new_action = ChannelAction::from_str(type_str)?;
+
+                let discrim: ChannelActionDiscriminants = channel_action.clone().into();
+                let new_action_discrim: ChannelActionDiscriminants = new_action.into();
+                if discrim == new_action_discrim {
+                    return Ok(());
+                }
+
+                // retain existing msg input when changing action types to save user retyping the message
+                let old_string = match channel_action {
+                    ChannelAction::ChannelMessage(current)
+                    | ChannelAction::ChannelBlocks(current) => std::mem::take(current),
+                    ChannelAction::OncallMessage { message, .. } => std::mem::take(message),
+                    ChannelAction::OncallTopic { .. } => String::default(),
+                };
+
+                *channel_action = match new_action_discrim {
+                    ChannelActionDiscriminants::ChannelMessage => {
+                        ChannelAction::ChannelMessage(old_string)
+                    }
+                    ChannelActionDiscriminants::ChannelBlocks => {
+                        ChannelAction::ChannelBlocks(into_blocks_json(old_string))
+                    }
+                    ChannelActionDiscriminants::OncallMessage => ChannelAction::OncallMessage {
+                        target: OncallTarget::default(),
+                        target_name: String::default(),
+                        message: old_string,
+                    },
+                    ChannelActionDiscriminants::OncallTopic => ChannelAction::OncallTopic {
+                        target: OncallTarget::default(),
+                        target_name: String::default(),
+                        location: OncallTopicLocation::default(),
+                        label: DEFAULT_ONCALL_TOPIC_LABEL.to_string(),
+                    },
+                };
+            }
         };
         Ok(())
     }
@@ -189,15 +519,50 @@ pub enum MessageAction {
     ThreadedMessage(String),
     /// Send message to same channel that triggered message
     ChannelMessage(String),
+    /// Message in the channel that only the sender of the triggered message can see
+    EphemeralToSender(String),
+    /// Direct message from the bot to the sender of the triggered message
+    DirectMessageToSender(String),
+    /// Post a Block Kit layout (JSON) in thread of the triggered message
+    ThreadedBlocks(String),
+    /// Post a Block Kit layout (JSON) to same channel that triggered message
+    ChannelBlocks(String),
+    /// Tag the current oncall user in thread of the triggered message
     MsgOncallInThread {
-        escalation_policy_id: String,
+        #[serde(flatten)]
+        target: OncallTarget,
+        /// PagerDuty's name for the target, shown in the editor instead of its id
+        #[serde(default)]
+        target_name: String,
         message: String,
+        /// tag everyone oncall at this escalation level, only the first oncall user when `None`
+        #[serde(default)]
+        mention_level: Option<u8>,
+        /// checked against Slack's Do Not Disturb, presence and deactivation status
+        #[serde(default)]
+        unavailable_action: OncallUnavailableAction,
+    },
+    /// Invite the current oncall, or everyone in a Slack user group, to the channel and report how it went in thread
+    InviteOncallToChannel {
+        #[serde(flatten)]
+        target: OncallTarget,
+        #[serde(default)]
+        target_name: String,
+        /// invite this user group's members instead of the oncall, ex: `S0123ABCD`
+        #[serde(default)]
+        usergroup_id: Option<String>,
     },
     /// Forward the triggered message to a different channel
     ForwardMessageToChannel {
         channel: String,
         msg_context: String,
     },
+    /// Send a signed HTTP request to another service
+    Webhook(WebhookRequest),
+    /// Start a Slack Workflow Builder workflow through its webhook trigger
+    TriggerWorkflow(WorkflowTrigger),
+    /// Open a PagerDuty incident through the Events API v2 and link it in thread
+    TriggerPagerDutyIncident(PagerDutyIncidentTrigger),
 }
 
 impl MessageAction {
@@ -210,10 +575,18 @@ impl MessageAction {
             MessageAction::AttachEmoji(_) => "Attach Emoji to Message",
             MessageAction::ThreadedMessage(_) => "Reply with Threaded Message",
             MessageAction::ChannelMessage(_) => "Post Message to Same Channel",
+            MessageAction::EphemeralToSender(_) => "Reply only to the Sender (ephemeral)",
+            MessageAction::DirectMessageToSender(_) => "Reply to the Sender with a DM",
+            MessageAction::ThreadedBlocks(_) => "Reply with Threaded Block Kit Message",
+            MessageAction::ChannelBlocks(_) => "Post Block Kit Message to Same Channel",
             MessageAction::MsgOncallInThread { .. } => "Tag OnCall User in Thread",
+            MessageAction::InviteOncallToChannel { .. } => "Invite OnCall User to the Channel",
             MessageAction::ForwardMessageToChannel { .. } => {
                 "Forward detected message to a different channel"
             }
+            MessageAction::Webhook(_) => "Send a Webhook to another service",
+            MessageAction::TriggerWorkflow(_) => "Start a Slack Workflow",
+            MessageAction::TriggerPagerDutyIncident(_) => "Trigger a PagerDuty Incident",
         }
     }
 
@@ -264,16 +637,43 @@ impl MessageAction {
                 "Hey Channel..",
                 "Enter Message to Post in Channel (not thread)",
             ),
+            MessageAction::EphemeralToSender(msg) | MessageAction::DirectMessageToSender(msg) => [
+                slack_plain_text_input_block_for_view(
+                    BlockSectionRouter::SenderMsgInput,
+                    index,
+                    msg.to_owned(),
+                    "Hi <@{{user_id}}>, please file a ticket at..",
+                    "Enter the message only the sender will see",
+                ),
+                slack_blocks![some_into(SlackContextBlock::new(vec![md!(
+                    "_Template variables:_ `{{user_id}}` `{{channel_id}}` `{{message_text}}`"
+                )]))],
+            ]
+            .concat(),
+            MessageAction::Webhook(webhook) => webhook.to_editor_blocks(index),
+            MessageAction::TriggerWorkflow(trigger) => trigger.to_editor_blocks(index),
+            MessageAction::TriggerPagerDutyIncident(trigger) => trigger.to_editor_blocks(index),
+            MessageAction::ThreadedBlocks(blocks_json)
+            | MessageAction::ChannelBlocks(blocks_json) => block_kit_input_blocks(
+                blocks_json,
+                index,
+                "_Template variables:_ `{{user_id}}` `{{channel_id}}` `{{message_text}}`",
+            ),
             MessageAction::MsgOncallInThread {
-                escalation_policy_id,
+                target,
+                target_name,
                 message,
+                mention_level,
+                unavailable_action,
             } => [
-                slack_plain_text_input_block_for_view(
+                oncall_provider_select_blocks(target.provider(), index),
+                oncall_mention_level_select_blocks(*mention_level, index),
+                oncall_unavailable_select_blocks(*unavailable_action, index),
+                oncall_target_input_blocks(
+                    target,
+                    target_name,
                     BlockSectionRouter::PDEscalationPolicyInput,
                     index,
-                    escalation_policy_id.to_owned(),
-                    "Pxxxxxx",
-                    "Enter the escalation policy to query",
                 ),
                 slack_plain_text_input_block_for_view(
                     BlockSectionRouter::PDThreadedMsgInput,
@@ -284,6 +684,40 @@ impl MessageAction {
                 ),
             ]
             .concat(),
+            MessageAction::InviteOncallToChannel {
+                target,
+                target_name,
+                usergroup_id,
+            } => {
+                let invitee_blocks = match usergroup_id {
+                    Some(usergroup_id) => slack_plain_text_input_block_for_view(
+                        BlockSectionRouter::InviteUsergroupInput,
+                        index,
+                        usergroup_id.to_owned(),
+                        "S0123ABCD",
+                        "Enter the Slack user group ID",
+                    ),
+                    None => [
+                        oncall_provider_select_blocks(target.provider(), index),
+                        oncall_target_input_blocks(
+                            target,
+                            target_name,
+                            BlockSectionRouter::PDEscalationPolicyInput,
+                            index,
+                        ),
+                    ]
+                    .concat(),
+                };
+
+                [
+                    invitee_select_blocks(usergroup_id.is_some(), index),
+                    invitee_blocks,
+                    slack_blocks![some_into(SlackContextBlock::new(vec![md!(
+                        "Who was invited, already in the channel or couldn't be invited is posted in thread. Private channels need Receptionist as a member"
+                    )]))],
+                ]
+                .concat()
+            }
             MessageAction::ForwardMessageToChannel {
                 channel,
                 msg_context,
@@ -335,3 +769,1136 @@ impl MessageAction {
         .concat()
     }
 }
+
+/// HTTP methods for webhook actions
+#[derive(Debug, Serialize, Deserialize, PartialEq, EnumIter, EnumString, Display, Clone, Copy)]
+#[serde(rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum WebhookMethod {
+    Post,
+    Put,
+    Patch,
+}
+
+impl Default for WebhookMethod {
+    fn default() -> Self {
+        Self::Post
+    }
+}
+
+/// Static select values for whether a webhook's response is posted back to Slack
+pub const WEBHOOK_REPLY_VALUE: &str = "reply-in-thread";
+pub const WEBHOOK_NO_REPLY_VALUE: &str = "no-reply";
+
+/// A request to another service, signed the same way as inbound webhooks so the receiver can verify it
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct WebhookRequest {
+    pub url: String,
+    pub method: WebhookMethod,
+    /// one `Name: value` header per line
+    pub headers: String,
+    /// JSON body, `{{variables}}` are filled in inside its strings
+    pub body: String,
+    pub signing_secret: String,
+    /// post the text of the webhook's response in thread of the triggered message
+    pub reply_in_thread: bool,
+}
+
+impl WebhookRequest {
+    pub fn new() -> Self {
+        Self {
+            signing_secret: nanoid!(32),
+            ..Self::default()
+        }
+    }
+
+    pub fn validate(&self, index: Option<usize>) -> Option<SlackBlockValidationError> {
+        let error = |route: BlockSectionRouter, error_message: String| {
+            Some(SlackBlockValidationError {
+                block_id: route.to_block_id(index),
+                error_message,
+            })
+        };
+
+        match self.url.parse::<Uri>() {
+            Ok(uri) if matches!(uri.scheme_str(), Some("http") | Some("https")) => (),
+            _ => {
+                return error(
+                    BlockSectionRouter::WebhookUrlInput,
+                    "Enter an http(s) URL".to_string(),
+                )
+            }
+        }
+
+        if let Err(e) = parse_webhook_headers(&self.headers) {
+            return error(BlockSectionRouter::WebhookHeadersInput, e.to_string());
+        }
+
+        if !self.body.is_empty() {
+            if let Err(e) = serde_json::from_str::<serde_json::Value>(&self.body) {
+                return error(
+                    BlockSectionRouter::WebhookBodyInput,
+                    format!("body is not valid JSON: {e}"),
+                );
+            }
+        }
+
+        None
+    }
+
+    pub fn update_method(&mut self, method_str: &str) -> Result<()> {
+        self.method = WebhookMethod::from_str(method_str)?;
+        Ok(())
+    }
+
+    pub fn update_reply_in_thread(&mut self, selected_value: &str) -> Result<()> {
+        self.reply_in_thread = match selected_value {
+            WEBHOOK_REPLY_VALUE => true,
+            WEBHOOK_NO_REPLY_VALUE => false,
+            _ => bail!("unknown webhook reply option: {selected_value}"),
+        };
+        Ok(())
+    }
+
+    fn to_editor_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        let method_options: Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> =
+            WebhookMethod::iter()
+                .map(|method| {
+                    SlackBlockChoiceItem::new(pt!(method.to_string()), method.to_string())
+                })
+                .collect();
+        let initial_method =
+            SlackBlockChoiceItem::new(pt!(self.method.to_string()), self.method.to_string());
+
+        let no_reply_option =
+            SlackBlockChoiceItem::new(pt!("Don't reply"), WEBHOOK_NO_REPLY_VALUE.to_string());
+        let reply_option = SlackBlockChoiceItem::new(
+            pt!("Post the response text in thread"),
+            WEBHOOK_REPLY_VALUE.to_string(),
+        );
+        let initial_reply = if self.reply_in_thread {
+            reply_option.clone()
+        } else {
+            no_reply_option.clone()
+        };
+
+        let signing: SlackContextBlockElement = md!(
+            "_Requests are signed like inbound webhooks:_ `{}` _is the unix timestamp and_ `{}` _is_ `v0=` _+ hex HMAC-SHA256 of_ `v0:<timestamp>:<body>` _with the secret_ `{}`",
+            WEBHOOK_TIMESTAMP_HEADER,
+            WEBHOOK_SIGNATURE_HEADER,
+            self.signing_secret
+        );
+
+        [
+            slack_plain_text_input_block_for_view(
+                BlockSectionRouter::WebhookUrlInput,
+                index,
+                self.url.to_owned(),
+                "https://tickets.example.com/api/tickets",
+                "Enter the URL to send the webhook to",
+            ),
+            slack_blocks![some_into(
+                SlackInputBlock::new(
+                    pt!("Method"),
+                    SlackInputBlockElement::StaticSelect(
+                        SlackBlockStaticSelectElement::new(
+                            BlockSectionRouter::WebhookMethodSelected.to_action_id(index),
+                            pt!("select a method")
+                        )
+                        .with_options(method_options)
+                        .with_initial_option(initial_method)
+                    )
+                )
+                .with_block_id(BlockSectionRouter::WebhookMethodSelected.to_block_id(index))
+            )],
+            slack_multiline_input_block_for_view(
+                BlockSectionRouter::WebhookHeadersInput,
+                index,
+                self.headers.to_owned(),
+                "Authorization: Bearer ..",
+                "Headers, one Name: value per line (optional)",
+                true,
+            ),
+            slack_multiline_input_block_for_view(
+                BlockSectionRouter::WebhookBodyInput,
+                index,
+                self.body.to_owned(),
+                r#"{"summary": "Request from <@{{user_id}}>", "channel": "{{channel_id}}"}"#,
+                "JSON body (optional)",
+                true,
+            ),
+            slack_blocks![
+                some_into(SlackContextBlock::new(vec![
+                    md!("_Template variables:_ `{{user_id}}` `{{channel_id}}` `{{message_text}}` `{{message_ts}}`"),
+                    signing
+                ])),
+                some_into(
+                    SlackInputBlock::new(
+                        pt!("Response"),
+                        SlackInputBlockElement::StaticSelect(
+                            SlackBlockStaticSelectElement::new(
+                                BlockSectionRouter::WebhookReplySelected.to_action_id(index),
+                                pt!("select what to do with the response")
+                            )
+                            .with_options(vec![no_reply_option, reply_option])
+                            .with_initial_option(initial_reply)
+                        )
+                    )
+                    .with_block_id(BlockSectionRouter::WebhookReplySelected.to_block_id(index))
+                )
+            ],
+        ]
+        .concat()
+    }
+}
+
+/// Parse `Name: value` lines, blank lines are ignored
+pub fn parse_webhook_headers(headers: &str) -> Result<Vec<(String, String)>> {
+    headers
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("header `{line}` should be `Name: value`"))?;
+            let name = name.trim();
+
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                bail!("invalid header name `{name}`");
+            }
+
+            Ok((name.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Workflow Builder webhook trigger URLs, https://slack.com/help/articles/360041352714
+pub const WORKFLOW_TRIGGER_URL_PREFIX: &str = "https://hooks.slack.com/workflows/";
+
+/// Variables most workflows want from the triggering message, `workflow variable = {{template}}`
+const DEFAULT_WORKFLOW_VARIABLES: &str = "sender = {{user_id}}
+channel = {{channel_id}}
+text = {{message_text}}
+permalink = {{permalink}}";
+
+/// A Slack Workflow started with the triggering message as its inputs
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct WorkflowTrigger {
+    pub url: String,
+    /// one `workflow variable = {{template}}` per line, names must match the variables set up in Workflow Builder
+    pub variables: String,
+}
+
+impl WorkflowTrigger {
+    pub fn new() -> Self {
+        Self {
+            url: String::default(),
+            variables: DEFAULT_WORKFLOW_VARIABLES.to_string(),
+        }
+    }
+
+    pub fn validate(&self, index: Option<usize>) -> Option<SlackBlockValidationError> {
+        if !self.url.starts_with(WORKFLOW_TRIGGER_URL_PREFIX) {
+            return Some(SlackBlockValidationError {
+                block_id: BlockSectionRouter::WorkflowUrlInput.to_block_id(index),
+                error_message: format!(
+                    "Copy the webhook URL from Workflow Builder, it starts with {WORKFLOW_TRIGGER_URL_PREFIX}"
+                ),
+            });
+        }
+
+        parse_workflow_variables(&self.variables)
+            .err()
+            .map(|e| SlackBlockValidationError {
+                block_id: BlockSectionRouter::WorkflowVariablesInput.to_block_id(index),
+                error_message: e.to_string(),
+            })
+    }
+
+    /// Workflow Builder only accepts string values, so the body is a flat object of templates
+    pub fn to_webhook_request(&self) -> Result<WebhookRequest> {
+        let body: serde_json::Map<String, serde_json::Value> =
+            parse_workflow_variables(&self.variables)?
+                .into_iter()
+                .map(|(name, template)| (name, serde_json::Value::String(template)))
+                .collect();
+
+        Ok(WebhookRequest {
+            url: self.url.to_owned(),
+            body: serde_json::Value::Object(body).to_string(),
+            ..WebhookRequest::default()
+        })
+    }
+
+    fn to_editor_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        [
+            slack_plain_text_input_block_for_view(
+                BlockSectionRouter::WorkflowUrlInput,
+                index,
+                self.url.to_owned(),
+                "https://hooks.slack.com/workflows/..",
+                "Enter the workflow's webhook URL (Workflow Builder > Webhook trigger)",
+            ),
+            slack_multiline_input_block_for_view(
+                BlockSectionRouter::WorkflowVariablesInput,
+                index,
+                self.variables.to_owned(),
+                "sender = {{user_id}}",
+                "Workflow variables, one name = value per line",
+                true,
+            ),
+            slack_blocks![some_into(SlackContextBlock::new(vec![md!(
+                "_Template variables:_ `{{user_id}}` `{{channel_id}}` `{{message_text}}` `{{message_ts}}` `{{permalink}}`"
+            )]))],
+        ]
+        .concat()
+    }
+}
+
+/// Parse `name = {{template}}` lines, blank lines are ignored
+pub fn parse_workflow_variables(variables: &str) -> Result<Vec<(String, String)>> {
+    variables
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, template) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("variable `{line}` should be `name = value`"))?;
+            let name = name.trim();
+
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                bail!("invalid workflow variable name `{name}`");
+            }
+
+            Ok((name.to_string(), template.trim().to_string()))
+        })
+        .collect()
+}
+
+/// PagerDuty Events API v2 alert severities
+#[derive(Debug, Serialize, Deserialize, PartialEq, EnumIter, EnumString, Display, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum PagerDutySeverity {
+    Critical,
+    Error,
+    Warning,
+    Info,
+}
+
+impl Default for PagerDutySeverity {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+/// Events API v2 summaries are cut off after this many characters
+pub const PAGERDUTY_MAX_SUMMARY_LEN: usize = 1024;
+
+/// Alert sent to a PagerDuty service's Events API v2 integration
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct PagerDutyIncidentTrigger {
+    /// integration key of the service's Events API v2 integration
+    pub routing_key: String,
+    /// incident title, `{{variables}}` are filled in from the triggering message
+    pub summary: String,
+    pub severity: PagerDutySeverity,
+}
+
+impl PagerDutyIncidentTrigger {
+    pub fn validate(&self, index: Option<usize>) -> Option<SlackBlockValidationError> {
+        let routing_key = self.routing_key.trim();
+        if routing_key.len() != 32 || !routing_key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Some(SlackBlockValidationError {
+                block_id: BlockSectionRouter::PDRoutingKeyInput.to_block_id(index),
+                error_message:
+                    "Enter the 32 character integration key of an Events API v2 integration"
+                        .to_string(),
+            });
+        }
+
+        if self.summary.trim().is_empty() {
+            return Some(SlackBlockValidationError {
+                block_id: BlockSectionRouter::PDSummaryInput.to_block_id(index),
+                error_message: "summary is empty".to_string(),
+            });
+        }
+
+        None
+    }
+
+    pub fn update_severity(&mut self, severity_str: &str) -> Result<()> {
+        self.severity = PagerDutySeverity::from_str(severity_str)?;
+        Ok(())
+    }
+
+    fn to_editor_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        let severity_options: Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> =
+            PagerDutySeverity::iter()
+                .map(|severity| {
+                    SlackBlockChoiceItem::new(pt!(severity.to_string()), severity.to_string())
+                })
+                .collect();
+        let initial_severity =
+            SlackBlockChoiceItem::new(pt!(self.severity.to_string()), self.severity.to_string());
+
+        [
+            slack_plain_text_input_block_for_view(
+                BlockSectionRouter::PDRoutingKeyInput,
+                index,
+                self.routing_key.to_owned(),
+                "32 character integration key",
+                "Enter the routing key of the service's Events API v2 integration",
+            ),
+            slack_plain_text_input_block_for_view(
+                BlockSectionRouter::PDSummaryInput,
+                index,
+                self.summary.to_owned(),
+                "Reported in Slack: {{message_text}}",
+                "Enter the incident summary",
+            ),
+            slack_blocks![
+                some_into(
+                    SlackInputBlock::new(
+                        pt!("Severity"),
+                        SlackInputBlockElement::StaticSelect(
+                            SlackBlockStaticSelectElement::new(
+                                BlockSectionRouter::PDSeveritySelected.to_action_id(index),
+                                pt!("select a severity")
+                            )
+                            .with_options(severity_options)
+                            .with_initial_option(initial_severity)
+                        )
+                    )
+                    .with_block_id(BlockSectionRouter::PDSeveritySelected.to_block_id(index))
+                ),
+                some_into(SlackContextBlock::new(vec![md!(
+                    "_Template variables:_ `{{user_id}}` `{{channel_id}}` `{{message_text}}` `{{message_ts}}`\n_Retriggering from the same message updates the existing incident instead of opening a new one_"
+                )]))
+            ],
+        ]
+        .concat()
+    }
+}
+
+/// Actions for Responses triggered by a slash command keyword: `/rec-cmd <keyword> [args]`
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    EnumIter,
+    EnumString,
+    Display,
+    Clone,
+    EnumDiscriminants,
+)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
+#[strum(serialize_all = "kebab_case")]
+pub enum CommandAction {
+    /// Reply that only the user who ran the command can see
+    EphemeralReply(String),
+    /// Reply visible to everyone in the channel the command was run from
+    ChannelReply(String),
+}
+
+impl CommandAction {
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+
+    pub fn to_description(&self) -> &str {
+        match &self {
+            CommandAction::EphemeralReply(_) => "Reply only to the user (ephemeral)",
+            CommandAction::ChannelReply(_) => "Reply in the channel",
+        }
+    }
+
+    pub fn to_choice_items() -> Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> {
+        Self::iter()
+            .map(|variant| variant.to_choice_item())
+            .collect()
+    }
+
+    fn to_type_selector_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        slack_blocks![some_into(
+            SlackSectionBlock::new()
+                .with_text(md!(
+                    ":building_construction: Select how to reply to the command"
+                ))
+                .with_accessory(SlackSectionBlockElement::StaticSelect(
+                    SlackBlockStaticSelectElement::new(
+                        BlockSectionRouter::ActionTypeSelected.to_action_id(index),
+                        pt!("select action Type")
+                    )
+                    .with_options(Self::to_choice_items())
+                    .with_initial_option(self.to_choice_item())
+                ))
+                .with_block_id(BlockSectionRouter::ActionTypeSelected.to_block_id(index))
+        )]
+    }
+
+    fn to_value_input_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        let msg = match self {
+            CommandAction::EphemeralReply(msg) | CommandAction::ChannelReply(msg) => msg,
+        };
+
+        let context: SlackContextBlockElement = md!(
+            "_Template variables:_ `{{keyword}}` `{{args}}` `{{arg_1}}`.. `{{user_id}}` `{{channel_id}}`"
+        );
+
+        [
+            slack_plain_text_input_block_for_view(
+                BlockSectionRouter::CommandReplyMsgInput,
+                index,
+                msg.to_owned(),
+                "The oncall for {{args}} is..",
+                "Enter the reply to the command",
+            ),
+            slack_blocks![some_into(SlackContextBlock::new(vec![context]))],
+        ]
+        .concat()
+    }
+
+    pub fn to_editor_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        [
+            self.to_type_selector_blocks(index),
+            self.to_value_input_blocks(index),
+            vec![SlackDividerBlock::new().into()],
+        ]
+        .concat()
+    }
+}
+
+/// Actions for Responses triggered by a user joining a channel, sent to the joining user
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    EnumIter,
+    EnumString,
+    Display,
+    Clone,
+    EnumDiscriminants,
+)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
+#[strum(serialize_all = "kebab_case")]
+pub enum MemberAction {
+    /// Message in the channel that only the joining user can see
+    EphemeralWelcome(String),
+    /// Direct message from the bot to the joining user
+    DirectMessageWelcome(String),
+}
+
+impl MemberAction {
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+
+    pub fn to_description(&self) -> &str {
+        match &self {
+            MemberAction::EphemeralWelcome(_) => "Welcome the user in the channel (ephemeral)",
+            MemberAction::DirectMessageWelcome(_) => "Welcome the user with a DM",
+        }
+    }
+
+    pub fn to_choice_items() -> Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> {
+        Self::iter()
+            .map(|variant| variant.to_choice_item())
+            .collect()
+    }
+
+    fn to_type_selector_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        slack_blocks![some_into(
+            SlackSectionBlock::new()
+                .with_text(md!(":wave: Select how to welcome the user"))
+                .with_accessory(SlackSectionBlockElement::StaticSelect(
+                    SlackBlockStaticSelectElement::new(
+                        BlockSectionRouter::ActionTypeSelected.to_action_id(index),
+                        pt!("select action Type")
+                    )
+                    .with_options(Self::to_choice_items())
+                    .with_initial_option(self.to_choice_item())
+                ))
+                .with_block_id(BlockSectionRouter::ActionTypeSelected.to_block_id(index))
+        )]
+    }
+
+    fn to_value_input_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        let msg = match self {
+            MemberAction::EphemeralWelcome(msg) | MemberAction::DirectMessageWelcome(msg) => msg,
+        };
+
+        let context: SlackContextBlockElement =
+            md!("_Template variables:_ `{{user_id}}` `{{channel_id}}`");
+
+        [
+            slack_plain_text_input_block_for_view(
+                BlockSectionRouter::MemberWelcomeMsgInput,
+                index,
+                msg.to_owned(),
+                "Welcome <@{{user_id}}>! Post your question here and..",
+                "Enter the welcome message",
+            ),
+            slack_blocks![some_into(SlackContextBlock::new(vec![context]))],
+        ]
+        .concat()
+    }
+
+    pub fn to_editor_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        [
+            self.to_type_selector_blocks(index),
+            self.to_value_input_blocks(index),
+            vec![SlackDividerBlock::new().into()],
+        ]
+        .concat()
+    }
+}
+
+/// Actions for Responses that are not triggered by a Slack message (schedules, webhooks),
+/// posted to the channel configured on the listener
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    EnumIter,
+    EnumString,
+    Display,
+    Clone,
+    EnumDiscriminants,
+)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
+#[strum(serialize_all = "kebab_case")]
+pub enum ChannelAction {
+    ChannelMessage(String),
+    /// Post a Block Kit layout (JSON) to the channel
+    ChannelBlocks(String),
+    /// Post a message tagging the current oncall of a PagerDuty or Opsgenie oncall target
+    OncallMessage {
+        #[serde(flatten)]
+        target: OncallTarget,
+        #[serde(default)]
+        target_name: String,
+        message: String,
+    },
+    /// Keep the current oncall in the channel topic or a pinned message, the rest of its text is left alone
+    OncallTopic {
+        #[serde(flatten)]
+        target: OncallTarget,
+        #[serde(default)]
+        target_name: String,
+        #[serde(default)]
+        location: OncallTopicLocation,
+        /// written before the mentions, ex: `oncall` for `oncall: @name`
+        label: String,
+    },
+}
+
+/// Label of new oncall topic actions, matching the `oncall: @name` topics channels keep by hand
+const DEFAULT_ONCALL_TOPIC_LABEL: &str = "oncall";
+
+impl ChannelAction {
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+
+    pub fn to_description(&self) -> &str {
+        match &self {
+            ChannelAction::ChannelMessage(_) => "Post a Message to the Channel",
+            ChannelAction::ChannelBlocks(_) => "Post a Block Kit Message to the Channel",
+            ChannelAction::OncallMessage { .. } => "Post the OnCall User to the Channel",
+            ChannelAction::OncallTopic { .. } => "Set the OnCall User in the Topic or a Pin",
+        }
+    }
+
+    pub fn to_choice_items() -> Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> {
+        Self::iter()
+            .map(|variant| variant.to_choice_item())
+            .collect()
+    }
+
+    fn to_type_selector_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        slack_blocks![some_into(
+            SlackSectionBlock::new()
+                .with_text(md!(":mega: Select what to post to the channel"))
+                .with_accessory(SlackSectionBlockElement::StaticSelect(
+                    SlackBlockStaticSelectElement::new(
+                        BlockSectionRouter::ActionTypeSelected.to_action_id(index),
+                        pt!("select action Type")
+                    )
+                    .with_options(Self::to_choice_items())
+                    .with_initial_option(self.to_choice_item())
+                ))
+                .with_block_id(BlockSectionRouter::ActionTypeSelected.to_block_id(index))
+        )]
+    }
+
+    fn to_value_input_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        let context: SlackContextBlockElement = md!(
+            "_Template variables:_ `{{channel_id}}`, `{{scheduled_time}}` for schedules, payload fields like `{{alert.name}}` for webhooks"
+        );
+
+        let message_blocks = |msg: &str| {
+            [
+                slack_plain_text_input_block_for_view(
+                    BlockSectionRouter::ChannelActionMsgInput,
+                    index,
+                    msg.to_owned(),
+                    "Weekly reminder..",
+                    "Enter the message to post",
+                ),
+                slack_blocks![some_into(SlackContextBlock::new(vec![context.clone()]))],
+            ]
+            .concat()
+        };
+
+        match self {
+            ChannelAction::ChannelMessage(msg) => message_blocks(msg),
+            ChannelAction::ChannelBlocks(blocks_json) => block_kit_input_blocks(
+                blocks_json,
+                index,
+                "_Template variables:_ `{{channel_id}}`, `{{scheduled_time}}` for schedules, payload fields like `{{alert.name}}` for webhooks",
+            ),
+            ChannelAction::OncallMessage {
+                target,
+                target_name,
+                message,
+            } => [
+                oncall_provider_select_blocks(target.provider(), index),
+                oncall_target_input_blocks(
+                    target,
+                    target_name,
+                    BlockSectionRouter::ChannelActionEscalationPolicyInput,
+                    index,
+                ),
+                message_blocks(message),
+            ]
+            .concat(),
+            ChannelAction::OncallTopic {
+                target,
+                target_name,
+                location,
+                label,
+            } => [
+                oncall_provider_select_blocks(target.provider(), index),
+                oncall_target_input_blocks(
+                    target,
+                    target_name,
+                    BlockSectionRouter::ChannelActionEscalationPolicyInput,
+                    index,
+                ),
+                oncall_topic_location_select_blocks(*location, index),
+                slack_plain_text_input_block_for_view(
+                    BlockSectionRouter::OncallTopicLabelInput,
+                    index,
+                    label.to_owned(),
+                    DEFAULT_ONCALL_TOPIC_LABEL,
+                    "Enter the label before the oncall, ex: oncall for \"oncall: @name\"",
+                ),
+                slack_blocks![some_into(SlackContextBlock::new(vec![md!(
+                    "Only the `label: ...` part is replaced, the rest of the topic or pinned message is kept. Pair it with a schedule to keep it current"
+                )]))],
+            ]
+            .concat(),
+        }
+    }
+
+    pub fn to_editor_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        [
+            self.to_type_selector_blocks(index),
+            self.to_value_input_blocks(index),
+            vec![SlackDividerBlock::new().into()],
+        ]
+        .concat()
+    }
+}
+
+/// Select for which service an oncall action asks who is oncall
+fn oncall_provider_select_blocks(
+    provider: OncallProviderKind,
+    index: Option<usize>,
+) -> Vec<SlackBlock> {
+    slack_blocks![some_into(
+        SlackInputBlock::new(
+            pt!("Oncall provider"),
+            SlackInputBlockElement::StaticSelect(
+                SlackBlockStaticSelectElement::new(
+                    BlockSectionRouter::OncallProviderSelected.to_action_id(index),
+                    pt!("select an oncall provider")
+                )
+                .with_options(
+                    OncallProviderKind::iter()
+                        .map(|kind| kind.to_choice_item())
+                        .collect()
+                )
+                .with_initial_option(provider.to_choice_item())
+            )
+        )
+        .with_dispatch_action(true)
+        .with_block_id(BlockSectionRouter::OncallProviderSelected.to_block_id(index))
+    )]
+}
+
+/// PagerDuty targets are searched by name through the options load endpoint, other providers are typed in
+fn oncall_target_input_blocks(
+    target: &OncallTarget,
+    target_name: &str,
+    text_input_route: BlockSectionRouter,
+    index: Option<usize>,
+) -> Vec<SlackBlock> {
+    let provider = target.provider();
+    let pagerduty_target = match target.to_pagerduty_target() {
+        Some(pagerduty_target) => pagerduty_target,
+        None => {
+            return slack_plain_text_input_block_for_view(
+                text_input_route,
+                index,
+                target.id().to_owned(),
+                "payments-team",
+                provider.target_label(),
+            )
+        }
+    };
+
+    let mut target_select = SlackBlockExternalSelectElement::new(
+        BlockSectionRouter::PDEscalationPolicySelected.to_action_id(index),
+        pt!("Search by name"),
+    );
+
+    if !target.id().is_empty() {
+        // targets saved before their name was kept only have the id to show
+        let label = if target_name.is_empty() {
+            target.id()
+        } else {
+            target_name
+        };
+        target_select = target_select.with_initial_option(SlackBlockChoiceItem::new(
+            pt!(label.to_owned()),
+            pagerduty_target.to_string(),
+        ));
+    }
+
+    slack_blocks![some_into(
+        SlackInputBlock::new(
+            pt!(provider.target_label()),
+            SlackInputBlockElement::ExternalSelect(target_select)
+        )
+        .with_block_id(BlockSectionRouter::PDEscalationPolicySelected.to_block_id(index))
+    )]
+}
+
+fn validate_oncall_target(
+    target: &OncallTarget,
+    text_input_route: BlockSectionRouter,
+    index: Option<usize>,
+) -> Option<SlackBlockValidationError> {
+    if !target.id().trim().is_empty() {
+        return None;
+    }
+
+    let route = match target.provider() {
+        OncallProviderKind::PagerDuty => BlockSectionRouter::PDEscalationPolicySelected,
+        _ => text_input_route,
+    };
+    Some(SlackBlockValidationError {
+        block_id: route.to_block_id(index),
+        error_message: format!("no {} provided", target.kind_description()),
+    })
+}
+
+/// Highest escalation level offered when tagging everyone oncall at a level
+const MAX_MENTION_LEVEL: u8 = 5;
+const FIRST_ONCALL_MENTION: &str = "first";
+
+fn mention_level_choice_item(
+    mention_level: Option<u8>,
+) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+    match mention_level {
+        Some(level) => SlackBlockChoiceItem::new(
+            pt!(format!("Everyone oncall at escalation level {level}")),
+            level.to_string(),
+        ),
+        None => SlackBlockChoiceItem::new(
+            pt!("First oncall user only"),
+            FIRST_ONCALL_MENTION.to_string(),
+        ),
+    }
+}
+
+/// Selected value of the mention level input, `None` to tag only the first oncall user
+pub fn parse_mention_level(value: &str) -> Result<Option<u8>> {
+    if value == FIRST_ONCALL_MENTION {
+        return Ok(None);
+    }
+
+    match value.parse::<u8>() {
+        Ok(level) if (1..=MAX_MENTION_LEVEL).contains(&level) => Ok(Some(level)),
+        _ => bail!("invalid escalation level: {value}"),
+    }
+}
+
+fn oncall_mention_level_select_blocks(
+    mention_level: Option<u8>,
+    index: Option<usize>,
+) -> Vec<SlackBlock> {
+    slack_blocks![some_into(
+        SlackInputBlock::new(
+            pt!("Who to tag"),
+            SlackInputBlockElement::StaticSelect(
+                SlackBlockStaticSelectElement::new(
+                    BlockSectionRouter::OncallMentionLevelSelected.to_action_id(index),
+                    pt!("select who to tag")
+                )
+                .with_options(
+                    std::iter::once(None)
+                        .chain((1..=MAX_MENTION_LEVEL).map(Some))
+                        .map(mention_level_choice_item)
+                        .collect()
+                )
+                .with_initial_option(mention_level_choice_item(mention_level))
+            )
+        )
+        .with_block_id(BlockSectionRouter::OncallMentionLevelSelected.to_block_id(index))
+    )]
+}
+
+const INVITE_ONCALL: &str = "oncall";
+const INVITE_USERGROUP: &str = "usergroup";
+
+fn invitee_choice_item(usergroup: bool) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+    if usergroup {
+        SlackBlockChoiceItem::new(
+            pt!("Everyone in a Slack user group"),
+            INVITE_USERGROUP.into(),
+        )
+    } else {
+        SlackBlockChoiceItem::new(pt!("The current oncall user"), INVITE_ONCALL.into())
+    }
+}
+
+/// Dispatches on change so the inputs for the selected invitee are shown
+fn invitee_select_blocks(usergroup: bool, index: Option<usize>) -> Vec<SlackBlock> {
+    slack_blocks![some_into(
+        SlackInputBlock::new(
+            pt!("Who to invite"),
+            SlackInputBlockElement::StaticSelect(
+                SlackBlockStaticSelectElement::new(
+                    BlockSectionRouter::InviteeSelected.to_action_id(index),
+                    pt!("select who to invite")
+                )
+                .with_options(vec![invitee_choice_item(false), invitee_choice_item(true)])
+                .with_initial_option(invitee_choice_item(usergroup))
+            )
+        )
+        .with_dispatch_action(true)
+        .with_block_id(BlockSectionRouter::InviteeSelected.to_block_id(index))
+    )]
+}
+
+/// Slack user group ids look like `S0123ABCD`
+fn validate_usergroup_id(
+    usergroup_id: &str,
+    index: Option<usize>,
+) -> Option<SlackBlockValidationError> {
+    let is_valid = usergroup_id.starts_with('S')
+        && usergroup_id.len() > 1
+        && usergroup_id.chars().all(|c| c.is_ascii_alphanumeric());
+
+    (!is_valid).then(|| SlackBlockValidationError {
+        block_id: BlockSectionRouter::InviteUsergroupInput.to_block_id(index),
+        error_message: "enter a user group ID like S0123ABCD".to_string(),
+    })
+}
+
+fn oncall_topic_location_select_blocks(
+    location: OncallTopicLocation,
+    index: Option<usize>,
+) -> Vec<SlackBlock> {
+    slack_blocks![some_into(
+        SlackInputBlock::new(
+            pt!("Where to keep the oncall"),
+            SlackInputBlockElement::StaticSelect(
+                SlackBlockStaticSelectElement::new(
+                    BlockSectionRouter::OncallTopicLocationSelected.to_action_id(index),
+                    pt!("select topic or pinned message")
+                )
+                .with_options(
+                    OncallTopicLocation::iter()
+                        .map(|location| location.to_choice_item())
+                        .collect()
+                )
+                .with_initial_option(location.to_choice_item())
+            )
+        )
+        .with_block_id(BlockSectionRouter::OncallTopicLocationSelected.to_block_id(index))
+    )]
+}
+
+fn oncall_unavailable_select_blocks(
+    unavailable_action: OncallUnavailableAction,
+    index: Option<usize>,
+) -> Vec<SlackBlock> {
+    slack_blocks![some_into(
+        SlackInputBlock::new(
+            pt!("If they are deactivated, on Do Not Disturb or away in Slack"),
+            SlackInputBlockElement::StaticSelect(
+                SlackBlockStaticSelectElement::new(
+                    BlockSectionRouter::OncallUnavailableSelected.to_action_id(index),
+                    pt!("select what to do")
+                )
+                .with_options(
+                    OncallUnavailableAction::iter()
+                        .map(|action| action.to_choice_item())
+                        .collect()
+                )
+                .with_initial_option(unavailable_action.to_choice_item())
+            )
+        )
+        .with_block_id(BlockSectionRouter::OncallUnavailableSelected.to_block_id(index))
+    )]
+}
+
+/// Keep Block Kit layouts when switching between Block Kit actions, wrap plain text in a section otherwise
+fn into_blocks_json(text: String) -> String {
+    if text.is_empty() || validate_block_kit_json(&text).is_ok() {
+        text
+    } else {
+        blocks_json_from_text(&text)
+    }
+}
+
+fn validate_blocks_json(
+    blocks_json: &str,
+    index: Option<usize>,
+) -> Option<SlackBlockValidationError> {
+    let error_message = if blocks_json.is_empty() {
+        "layout is empty".to_string()
+    } else {
+        validate_block_kit_json(blocks_json).err()?.to_string()
+    };
+
+    Some(SlackBlockValidationError {
+        block_id: BlockSectionRouter::BlocksJsonInput.to_block_id(index),
+        error_message,
+    })
+}
+
+/// Block Kit JSON input with a link to preview the saved layout in the Block Kit Builder
+fn block_kit_input_blocks(
+    blocks_json: &str,
+    index: Option<usize>,
+    template_vars_text: &str,
+) -> Vec<SlackBlock> {
+    let preview_text = match block_kit_builder_url(blocks_json) {
+        Some(url) => format!(":eyes: <{url}|Preview in Block Kit Builder> (saved layout)"),
+        None if blocks_json.is_empty() => {
+            "Design a layout in the <https://app.slack.com/block-kit-builder|Block Kit Builder> and paste its JSON".to_string()
+        }
+        None => "_Layout is invalid or too large to preview_".to_string(),
+    };
+
+    let context: Vec<SlackContextBlockElement> = vec![md!(preview_text), md!(template_vars_text)];
+
+    [
+        slack_multiline_input_block_for_view(
+            BlockSectionRouter::BlocksJsonInput,
+            index,
+            blocks_json.to_owned(),
+            r#"[{"type": "section", "text": {"type": "mrkdwn", "text": "Hi <@{{user_id}}>"}}]"#,
+            "Enter the Block Kit blocks (JSON)",
+            false,
+        ),
+        slack_blocks![some_into(SlackContextBlock::new(context))],
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{to_value, Value};
+
+    fn pagerduty_oncall_message(target: &str) -> ReceptionistAction {
+        ReceptionistAction::ForChannel(ChannelAction::OncallMessage {
+            target: OncallTarget::new(OncallProviderKind::PagerDuty, target),
+            target_name: String::default(),
+            message: "please take a look".to_string(),
+        })
+    }
+
+    /// Initial option of the PagerDuty target search in the action's editor
+    fn target_select_initial_option(action: &ReceptionistAction) -> Value {
+        let blocks = to_value(action.to_editor_blocks(Some(0))).unwrap();
+        blocks
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|block| block["element"]["type"] == "external_select")
+            .map(|block| block["element"]["initial_option"].to_owned())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pagerduty_target_shows_name() {
+        let mut action = pagerduty_oncall_message("");
+        action
+            .update_pagerduty_target(Some((
+                "schedule:PXYZ".to_string(),
+                "Schedule: Payments".to_string(),
+            )))
+            .unwrap();
+
+        let initial_option = target_select_initial_option(&action);
+        assert_eq!(initial_option["text"]["text"], "Schedule: Payments");
+        assert_eq!(initial_option["value"], "schedule:PXYZ");
+
+        // switching providers leaves nothing of the old target behind
+        action
+            .update_oncall_provider(&OncallProviderKind::Opsgenie.to_string())
+            .unwrap();
+        assert_eq!(
+            action,
+            ReceptionistAction::ForChannel(ChannelAction::OncallMessage {
+                target: OncallTarget::OpsgenieSchedule(String::default()),
+                target_name: String::default(),
+                message: "please take a look".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_update_action_type_keeps_message() {
+        let mut action = ReceptionistAction::ForMessage(MessageAction::ThreadedMessage(
+            "please file a ticket".to_string(),
+        ));
+        action.update_action_type("channel-message").unwrap();
+        assert_eq!(
+            action,
+            ReceptionistAction::ForMessage(MessageAction::ChannelMessage(
+                "please file a ticket".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_update_action_type_from_invite_starts_with_empty_message() {
+        let mut action = ReceptionistAction::ForMessage(MessageAction::InviteOncallToChannel {
+            target: OncallTarget::PdEscalationPolicy("PABC123".to_string()),
+            target_name: "Payments".to_string(),
+            usergroup_id: None,
+        });
+        action.update_action_type("threaded-message").unwrap();
+        assert_eq!(
+            action,
+            ReceptionistAction::ForMessage(MessageAction::ThreadedMessage(String::default()))
+        );
+    }
+
+    #[test]
+    fn test_pagerduty_target_saved_without_name_shows_id() {
+        let initial_option = target_select_initial_option(&pagerduty_oncall_message("PABC123"));
+        assert_eq!(initial_option["text"]["text"], "PABC123");
+    }
+}
@@ -0,0 +1,229 @@
+//! Block Kit message layouts for actions, stored as the JSON a user would paste from the Block Kit Builder
+use crate::{percent_encode, render_json_template};
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use slack_morphism::prelude::*;
+use std::collections::HashMap;
+
+/// https://api.slack.com/reference/block-kit/blocks
+pub const MAX_BLOCKS_PER_MESSAGE: usize = 50;
+const MAX_BLOCK_ID_LEN: usize = 255;
+const MAX_SECTION_TEXT_LEN: usize = 3000;
+const MAX_SECTION_FIELDS: usize = 10;
+const MAX_SECTION_FIELD_TEXT_LEN: usize = 2000;
+const MAX_HEADER_TEXT_LEN: usize = 150;
+const MAX_CONTEXT_ELEMENTS: usize = 10;
+const MAX_ACTIONS_ELEMENTS: usize = 25;
+
+/// Links longer than this don't fit in a single mrkdwn text object of the editor
+const MAX_PREVIEW_URL_LEN: usize = 2900;
+const BLOCK_KIT_BUILDER_URL: &str = "https://app.slack.com/block-kit-builder";
+
+/// Accepts either a list of blocks or the `{"blocks": [..]}` object the Block Kit Builder exports
+pub fn parse_block_kit_json(blocks_json: &str) -> Result<Vec<Value>> {
+    let parsed: Value =
+        serde_json::from_str(blocks_json).map_err(|e| anyhow!("not valid JSON: {e}"))?;
+
+    let blocks = match parsed {
+        Value::Array(blocks) => blocks,
+        Value::Object(mut payload) => match payload.remove("blocks") {
+            Some(Value::Array(blocks)) => blocks,
+            _ => bail!("expected a list of blocks or an object with a `blocks` list"),
+        },
+        _ => bail!("expected a list of blocks or an object with a `blocks` list"),
+    };
+
+    Ok(blocks)
+}
+
+/// Check the layout against the Block Kit limits that Slack would otherwise reject the message for
+pub fn validate_block_kit_json(blocks_json: &str) -> Result<()> {
+    let blocks = parse_block_kit_json(blocks_json)?;
+
+    if blocks.is_empty() {
+        bail!("add at least one block");
+    }
+    if blocks.len() > MAX_BLOCKS_PER_MESSAGE {
+        bail!("messages can have at most {MAX_BLOCKS_PER_MESSAGE} blocks");
+    }
+
+    for (position, block) in blocks.iter().enumerate() {
+        validate_block(block).map_err(|e| anyhow!("block {}: {e}", position + 1))?;
+    }
+
+    serde_json::from_value::<Vec<SlackBlock>>(Value::Array(blocks))
+        .map_err(|e| anyhow!("not a valid Block Kit layout: {e}"))?;
+
+    Ok(())
+}
+
+fn validate_block(block: &Value) -> Result<()> {
+    let block_type = block
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing `type`"))?;
+
+    if let Some(block_id) = block.get("block_id").and_then(Value::as_str) {
+        if block_id.len() > MAX_BLOCK_ID_LEN {
+            bail!("`block_id` is longer than {MAX_BLOCK_ID_LEN} characters");
+        }
+    }
+
+    match block_type {
+        "section" => {
+            if let Some(text) = text_of(block.get("text")) {
+                check_len("text", text, MAX_SECTION_TEXT_LEN)?;
+            }
+
+            let fields = block
+                .get("fields")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if fields.len() > MAX_SECTION_FIELDS {
+                bail!("sections can have at most {MAX_SECTION_FIELDS} fields");
+            }
+            for field in fields {
+                if let Some(text) = text_of(Some(field)) {
+                    check_len("field", text, MAX_SECTION_FIELD_TEXT_LEN)?;
+                }
+            }
+
+            if block.get("text").is_none() && fields.is_empty() {
+                bail!("sections need `text` or `fields`");
+            }
+        }
+        "header" => {
+            let text = text_of(block.get("text")).ok_or_else(|| anyhow!("headers need `text`"))?;
+            check_len("header text", text, MAX_HEADER_TEXT_LEN)?;
+        }
+        "context" => check_elements(block, MAX_CONTEXT_ELEMENTS)?,
+        "actions" => check_elements(block, MAX_ACTIONS_ELEMENTS)?,
+        _ => (),
+    }
+
+    Ok(())
+}
+
+fn text_of(text_object: Option<&Value>) -> Option<&str> {
+    text_object?.get("text")?.as_str()
+}
+
+fn check_len(name: &str, text: &str, max_len: usize) -> Result<()> {
+    if text.chars().count() > max_len {
+        bail!("{name} is longer than {max_len} characters");
+    }
+    Ok(())
+}
+
+fn check_elements(block: &Value, max_elements: usize) -> Result<()> {
+    match block.get("elements").and_then(Value::as_array) {
+        Some(elements) if elements.is_empty() => bail!("`elements` is empty"),
+        Some(elements) if elements.len() > max_elements => {
+            bail!("at most {max_elements} elements are allowed")
+        }
+        Some(_) => Ok(()),
+        None => bail!("missing `elements`"),
+    }
+}
+
+/// Fill in `{{variable}}` placeholders inside the layout's strings
+pub fn render_block_kit_template(
+    blocks_json: &str,
+    vars: &HashMap<String, String>,
+) -> Result<Vec<SlackBlock>> {
+    let blocks = parse_block_kit_json(blocks_json)?
+        .into_iter()
+        .map(|block| render_json_template(block, vars))
+        .collect();
+
+    Ok(serde_json::from_value(Value::Array(blocks))?)
+}
+
+/// Notification and screen reader text for a Block Kit message: the first text found in its blocks
+pub fn block_kit_fallback_text(blocks: &[SlackBlock]) -> String {
+    serde_json::to_value(blocks)
+        .ok()
+        .and_then(|blocks| {
+            blocks.as_array()?.iter().find_map(|block| {
+                text_of(block.get("text"))
+                    .or_else(|| text_of(block.get("elements")?.get(0)))
+                    .map(str::to_string)
+            })
+        })
+        .unwrap_or_else(|| "Receptionist message".to_string())
+}
+
+/// A starting layout for users switching from a plain text action to a Block Kit action
+pub fn blocks_json_from_text(text: &str) -> String {
+    let blocks = json!([{
+        "type": "section",
+        "text": {"type": "mrkdwn", "text": text}
+    }]);
+
+    serde_json::to_string_pretty(&blocks).unwrap_or_default()
+}
+
+/// Link that opens the layout in Slack's Block Kit Builder, `None` if the layout is too large to link
+pub fn block_kit_builder_url(blocks_json: &str) -> Option<String> {
+    let blocks = parse_block_kit_json(blocks_json).ok()?;
+    let payload = json!({ "blocks": blocks }).to_string();
+
+    let url = format!("{BLOCK_KIT_BUILDER_URL}#{}", percent_encode(&payload));
+    if url.len() <= MAX_PREVIEW_URL_LEN {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_block_kit_json() {
+        let valid = r#"[
+            {"type": "header", "text": {"type": "plain_text", "text": "New request"}},
+            {"type": "section", "text": {"type": "mrkdwn", "text": "Hi <@{{user_id}}>"}},
+            {"type": "context", "elements": [{"type": "mrkdwn", "text": "via Receptionist"}]}
+        ]"#;
+        assert!(validate_block_kit_json(valid).is_ok());
+        assert!(validate_block_kit_json(&format!(r#"{{"blocks": {valid}}}"#)).is_ok());
+
+        assert!(validate_block_kit_json("not json").is_err());
+        assert!(validate_block_kit_json("[]").is_err());
+        assert!(validate_block_kit_json(r#"[{"text": "no type"}]"#).is_err());
+        assert!(validate_block_kit_json(r#"[{"type": "context", "elements": []}]"#).is_err());
+
+        let long_header = format!(
+            r#"[{{"type": "header", "text": {{"type": "plain_text", "text": "{}"}}}}]"#,
+            "a".repeat(MAX_HEADER_TEXT_LEN + 1)
+        );
+        assert!(validate_block_kit_json(&long_header).is_err());
+
+        let too_many_blocks = format!("[{}]", vec![r#"{"type": "divider"}"#; 51].join(","));
+        assert!(validate_block_kit_json(&too_many_blocks).is_err());
+    }
+
+    #[test]
+    fn test_render_block_kit_template_escapes_values() {
+        let vars = HashMap::from([("title".to_string(), r#"disk "full""#.to_string())]);
+        let blocks = render_block_kit_template(
+            r#"[{"type": "section", "text": {"type": "mrkdwn", "text": "*{{title}}*"}}]"#,
+            &vars,
+        )
+        .unwrap();
+
+        assert_eq!(block_kit_fallback_text(&blocks), r#"*disk "full"*"#);
+    }
+
+    #[test]
+    fn test_blocks_json_from_text() {
+        let blocks_json = blocks_json_from_text("hello \"there\"");
+        assert!(validate_block_kit_json(&blocks_json).is_ok());
+        assert!(block_kit_builder_url(&blocks_json)
+            .unwrap()
+            .starts_with("https://app.slack.com/block-kit-builder#%7B%22blocks%22"));
+    }
+}
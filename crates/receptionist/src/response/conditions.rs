@@ -2,6 +2,7 @@ use crate::{BlockSectionRouter, ReceptionistListener, SlackBlockValidationError}
 use anyhow::{anyhow, bail, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use slack_morphism::prelude::*;
 use std::str::FromStr;
 use strum::{EnumDiscriminants, EnumIter, EnumString, IntoEnumIterator};
@@ -12,20 +13,47 @@ use strum::{EnumDiscriminants, EnumIter, EnumString, IntoEnumIterator};
 #[strum(serialize_all = "kebab_case")]
 pub enum ReceptionistCondition {
     ForMessage(MessageCondition),
+    ForJson(JsonCondition),
 }
 
 impl ReceptionistCondition {
     pub fn is_valid(&self) -> bool {
         match self {
             ReceptionistCondition::ForMessage(message_condition) => message_condition.is_valid(),
+            ReceptionistCondition::ForJson(json_condition) => json_condition.is_valid(),
         }
     }
 
+    pub fn is_for_listener(&self, listener: &ReceptionistListener) -> bool {
+        let discrim: ReceptionistConditionDiscriminants = self.into();
+        let listener_discrim: ReceptionistConditionDiscriminants =
+            Self::default_from_listener(listener).into();
+
+        discrim == listener_discrim
+    }
+
     pub fn default_from_listener(listener: &ReceptionistListener) -> Self {
         match listener {
-            ReceptionistListener::SlackChannel { .. } => {
+            ReceptionistListener::SlackChannel { .. }
+            | ReceptionistListener::SlackChannels { .. }
+            | ReceptionistListener::SlackChannelPattern { .. }
+            | ReceptionistListener::AllPublicChannels
+            | ReceptionistListener::AppMention { .. }
+            | ReceptionistListener::DirectMessage { is_fallback: false } => {
                 Self::ForMessage(MessageCondition::MatchPhrase("".into()))
             }
+            // slash commands run for any args unless the user narrows it down
+            ReceptionistListener::SlackCommandKeyword { .. }
+            | ReceptionistListener::DirectMessage { is_fallback: true }
+            | ReceptionistListener::MemberJoinedChannel { .. }
+            | ReceptionistListener::Schedule { .. }
+            | ReceptionistListener::PagerDutyIncident { .. } => {
+                Self::ForMessage(MessageCondition::MatchRegex(".*".into()))
+            }
+            ReceptionistListener::Webhook { .. } => Self::ForJson(JsonCondition::FieldEquals {
+                path: String::default(),
+                value: String::default(),
+            }),
         }
     }
 
@@ -41,6 +69,9 @@ impl ReceptionistCondition {
             ReceptionistCondition::ForMessage(message_condition) => {
                 message_condition.to_editor_blocks(index)
             }
+            ReceptionistCondition::ForJson(json_condition) => {
+                json_condition.to_editor_blocks(index)
+            }
         }
     }
 
@@ -78,10 +109,46 @@ impl ReceptionistCondition {
                 };
                 *message_condition = new_variant;
             }
+            Self::ForJson(json_condition) => {
+                let new_variant = JsonCondition::from_str(type_str)?;
+
+                // keep the path and value the user already typed in
+                let (path, value) = json_condition.take_path_and_value();
+                *json_condition = match new_variant {
+                    JsonCondition::FieldEquals { .. } => JsonCondition::FieldEquals { path, value },
+                    JsonCondition::FieldMatchesRegex { .. } => JsonCondition::FieldMatchesRegex {
+                        path,
+                        pattern: value,
+                    },
+                };
+            }
         }
         Ok(())
     }
 
+    pub fn update_json_condition(
+        &mut self,
+        new_path: Option<String>,
+        new_value: Option<String>,
+    ) -> Result<()> {
+        match self {
+            ReceptionistCondition::ForJson(json_condition) => {
+                let (path, value) = match json_condition {
+                    JsonCondition::FieldEquals { path, value } => (path, value),
+                    JsonCondition::FieldMatchesRegex { path, pattern } => (path, pattern),
+                };
+                if let Some(new_path) = new_path {
+                    *path = new_path.trim().to_string();
+                }
+                if let Some(new_value) = new_value {
+                    *value = new_value;
+                }
+            }
+            _ => bail!("Not a JSON condition"),
+        };
+        Ok(())
+    }
+
     pub fn update_message_condition_string(&mut self, new_str: String) -> Result<()> {
         match self {
             ReceptionistCondition::ForMessage(message_condition) => {
@@ -115,6 +182,7 @@ impl ReceptionistCondition {
                     }),
                 },
             },
+            ReceptionistCondition::ForJson(json_condition) => json_condition.validate(index),
         }
     }
 }
@@ -255,3 +323,217 @@ impl MessageCondition {
         }
     }
 }
+
+/// Conditions on the fields of a JSON payload, ex: from an inbound webhook.
+/// Paths are dot separated and can index into arrays: `alerts.0.status`
+#[derive(Debug, Serialize, Deserialize, PartialEq, EnumIter, strum::Display, Clone, EnumString)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
+#[strum(serialize_all = "kebab_case")]
+pub enum JsonCondition {
+    FieldEquals { path: String, value: String },
+    FieldMatchesRegex { path: String, pattern: String },
+}
+
+impl JsonCondition {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            JsonCondition::FieldEquals { path, .. } => !path.is_empty(),
+            JsonCondition::FieldMatchesRegex { path, pattern } => {
+                !path.is_empty() && Regex::new(pattern).is_ok()
+            }
+        }
+    }
+
+    fn take_path_and_value(&mut self) -> (String, String) {
+        match self {
+            JsonCondition::FieldEquals { path, value } => {
+                (std::mem::take(path), std::mem::take(value))
+            }
+            JsonCondition::FieldMatchesRegex { path, pattern } => {
+                (std::mem::take(path), std::mem::take(pattern))
+            }
+        }
+    }
+
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+
+    fn to_description(&self) -> &str {
+        match &self {
+            JsonCondition::FieldEquals { .. } => "Field equals",
+            JsonCondition::FieldMatchesRegex { .. } => "Field matches Regex",
+        }
+    }
+
+    pub fn to_choice_items() -> Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> {
+        Self::iter()
+            .map(|variant| variant.to_choice_item())
+            .collect()
+    }
+
+    fn to_type_selector_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        slack_blocks![some_into(
+            SlackSectionBlock::new()
+                .with_text(md!(":clipboard: Select a JSON field condition type"))
+                .with_accessory(SlackSectionBlockElement::StaticSelect(
+                    SlackBlockStaticSelectElement::new(
+                        BlockSectionRouter::ConditionTypeSelected.to_action_id(index),
+                        pt!("select matching Type")
+                    )
+                    .with_options(Self::to_choice_items())
+                    .with_initial_option(self.to_choice_item())
+                ))
+                .with_block_id(BlockSectionRouter::ConditionTypeSelected.to_block_id(index),)
+        )]
+    }
+
+    fn to_value_input_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        let (path, value, value_label) = match self {
+            JsonCondition::FieldEquals { path, value } => (path, value, "Field equals this value:"),
+            JsonCondition::FieldMatchesRegex { path, pattern } => (
+                path,
+                pattern,
+                "Field contains a match to this Regex pattern:",
+            ),
+        };
+
+        let path_element = SlackBlockPlainTextInputElement::new(
+            BlockSectionRouter::JsonConditionPathInput.to_action_id(index),
+            pt!("alert.status"),
+        );
+        let path_element = if path.is_empty() {
+            path_element
+        } else {
+            path_element.with_initial_value(path.to_owned())
+        };
+
+        let value_element = SlackBlockPlainTextInputElement::new(
+            BlockSectionRouter::JsonConditionValueInput.to_action_id(index),
+            pt!("firing"),
+        );
+        let value_element = if value.is_empty() {
+            value_element
+        } else {
+            value_element.with_initial_value(value.to_owned())
+        };
+
+        let context: SlackContextBlockElement =
+            md!("_Tip:_ separate nested fields with a `.` and use numbers for arrays: `alerts.0.status`");
+
+        slack_blocks![
+            some_into(
+                SlackInputBlock::new(
+                    pt!("JSON field path:"),
+                    SlackInputBlockElement::PlainTextInput(path_element)
+                )
+                .with_block_id(BlockSectionRouter::JsonConditionPathInput.to_block_id(index))
+            ),
+            some_into(SlackContextBlock::new(vec![context])),
+            some_into(
+                SlackInputBlock::new(
+                    pt!(value_label),
+                    SlackInputBlockElement::PlainTextInput(value_element)
+                )
+                .with_block_id(BlockSectionRouter::JsonConditionValueInput.to_block_id(index))
+            )
+        ]
+    }
+
+    pub fn to_editor_blocks(&self, index: Option<usize>) -> Vec<SlackBlock> {
+        [
+            self.to_type_selector_blocks(index),
+            self.to_value_input_blocks(index),
+            vec![SlackDividerBlock::new().into()],
+        ]
+        .concat()
+    }
+
+    pub fn validate(&self, index: Option<usize>) -> Option<SlackBlockValidationError> {
+        let path = match self {
+            JsonCondition::FieldEquals { path, .. }
+            | JsonCondition::FieldMatchesRegex { path, .. } => path,
+        };
+
+        if path.is_empty() {
+            return Some(SlackBlockValidationError {
+                block_id: BlockSectionRouter::JsonConditionPathInput.to_block_id(index),
+                error_message: "input field is empty".to_string(),
+            });
+        }
+
+        match self {
+            JsonCondition::FieldEquals { .. } => None,
+            JsonCondition::FieldMatchesRegex { pattern, .. } => match Regex::new(pattern) {
+                Ok(_) => None,
+                Err(re_err) => Some(SlackBlockValidationError {
+                    block_id: BlockSectionRouter::JsonConditionValueInput.to_block_id(index),
+                    error_message: re_err.to_string(),
+                }),
+            },
+        }
+    }
+
+    pub fn should_trigger(&self, payload: &Value) -> bool {
+        match self {
+            JsonCondition::FieldEquals { path, value } => {
+                json_field_as_string(payload, path).map_or(false, |field| &field == value)
+            }
+            JsonCondition::FieldMatchesRegex { path, pattern } => {
+                match (json_field_as_string(payload, path), Regex::new(pattern)) {
+                    (Some(field), Ok(re)) => re.is_match(&field),
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+/// Look up a dot separated path in a JSON value, strings are returned without quotes
+pub fn json_field_as_string(payload: &Value, path: &str) -> Option<String> {
+    let pointer = format!("/{}", path.replace('.', "/"));
+
+    match payload.pointer(&pointer)? {
+        Value::Null => None,
+        Value::String(field) => Some(field.to_owned()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_conditions() {
+        let payload = json!({
+            "status": "firing",
+            "alerts": [{"labels": {"severity": "critical"}, "count": 3}]
+        });
+
+        let equals = JsonCondition::FieldEquals {
+            path: "status".into(),
+            value: "firing".into(),
+        };
+        assert!(equals.should_trigger(&payload));
+
+        let nested = JsonCondition::FieldMatchesRegex {
+            path: "alerts.0.labels.severity".into(),
+            pattern: "^(critical|high)$".into(),
+        };
+        assert!(nested.should_trigger(&payload));
+
+        let number = JsonCondition::FieldEquals {
+            path: "alerts.0.count".into(),
+            value: "3".into(),
+        };
+        assert!(number.should_trigger(&payload));
+
+        let missing = JsonCondition::FieldEquals {
+            path: "alerts.1.count".into(),
+            value: "3".into(),
+        };
+        assert!(!missing.should_trigger(&payload));
+    }
+}
@@ -1,14 +1,81 @@
-use crate::{BlockSectionRouter, SlackBlockValidationError};
+use crate::{
+    parse_cron, parse_timezone, response::utils::slack_plain_text_input_block_for_view,
+    BlockSectionRouter, SlackBlockValidationError, KEYWORD_COMMAND,
+};
+use anyhow::{bail, Result};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use slack_morphism::prelude::*;
-use strum::{EnumIter, EnumString};
+use std::str::FromStr;
+use strum::{EnumDiscriminants, EnumIter, EnumString, IntoEnumIterator};
 
-#[derive(Debug, Serialize, Deserialize, EnumString, PartialEq, EnumIter, Clone, strum::Display)]
+#[derive(
+    Debug,
+    Serialize,
+    Deserialize,
+    EnumString,
+    PartialEq,
+    EnumIter,
+    Clone,
+    strum::Display,
+    EnumDiscriminants,
+)]
 #[serde(tag = "listener_type", rename_all = "snake_case")]
 #[strum(serialize_all = "kebab_case")]
 pub enum ReceptionistListener {
-    SlackChannel { channel_id: String },
-    // SlackCommandKeyword { command: String, keyword: String },
+    SlackChannel {
+        channel_id: String,
+    },
+    /// Messages in any of a set of channels
+    SlackChannels {
+        channel_ids: Vec<String>,
+    },
+    /// Messages in any channel whose name matches a pattern, ex: `help-*`
+    SlackChannelPattern {
+        pattern: String,
+    },
+    /// Messages in every public channel the bot is a member of
+    AllPublicChannels,
+    /// Messages that mention the bot, ex: `@receptionist who is oncall?`.
+    /// Listens in a single channel, or across the workspace if no channel is set
+    AppMention {
+        channel_id: Option<String>,
+    },
+    /// Slash command with a keyword, ex: `/rec-cmd oncall payments`
+    SlackCommandKeyword {
+        command: String,
+        keyword: String,
+    },
+    /// Direct messages sent to the bot.
+    /// A fallback Response ignores its conditions and only runs when no other DM Response matched
+    DirectMessage {
+        is_fallback: bool,
+    },
+    /// A user joins a channel, ex: to welcome new members of an intake channel
+    MemberJoinedChannel {
+        channel_id: String,
+        /// only run the first time a user joins
+        once_per_user: bool,
+        /// don't run for multi-channel and single-channel guests
+        skip_guests: bool,
+    },
+    /// Runs on a cron schedule and posts to a channel, ex: `0 9 * * Mon` in `America/New_York`
+    Schedule {
+        cron: String,
+        timezone: String,
+        channel_id: String,
+    },
+    /// JSON posted by an external system to `/hooks/<response id>`, actions post to a channel
+    /// Its signing secret is stored apart from the Response, see `get_webhook_secret`
+    Webhook {
+        channel_id: String,
+    },
+    /// PagerDuty webhook `incident.*` events for a service.
+    /// Actions post to the channel when an incident triggers, acks and resolves are posted in that message's thread
+    PagerDutyIncident {
+        service_id: String,
+        channel_id: String,
+    },
 }
 
 impl Default for ReceptionistListener {
@@ -19,13 +86,138 @@ impl Default for ReceptionistListener {
     }
 }
 
+/// The channel an incoming message was posted in.
+/// Name and visibility are needed to resolve listeners that target more than one channel
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IncomingChannel {
+    pub id: String,
+    /// only looked up when a pattern listener needs it
+    pub name: Option<String>,
+    pub is_public: bool,
+}
+
 impl ReceptionistListener {
     pub fn matches_slack_channel_id(&self, incoming_channel: &str) -> bool {
+        self.matches(&ReceptionistListener::SlackChannel {
+            channel_id: incoming_channel.to_string(),
+        })
+    }
+
+    /// Check if an incoming event (described as a listener) should be handled by this listener.
+    /// Pattern and public channel listeners can't be resolved from a channel id alone,
+    /// they always match here and are narrowed down later with `matches_channel`
+    pub fn matches(&self, incoming: &ReceptionistListener) -> bool {
+        match (self, incoming) {
+            (
+                ReceptionistListener::SlackChannel { channel_id },
+                ReceptionistListener::SlackChannel {
+                    channel_id: incoming_channel,
+                },
+            ) => channel_id == incoming_channel,
+            (
+                ReceptionistListener::SlackChannels { channel_ids },
+                ReceptionistListener::SlackChannel {
+                    channel_id: incoming_channel,
+                },
+            ) => channel_ids.contains(incoming_channel),
+            (
+                ReceptionistListener::SlackChannelPattern { .. }
+                | ReceptionistListener::AllPublicChannels,
+                ReceptionistListener::SlackChannel { .. },
+            ) => true,
+            (
+                ReceptionistListener::AppMention { channel_id },
+                ReceptionistListener::AppMention {
+                    channel_id: incoming_channel,
+                },
+            ) => channel_id.is_none() || channel_id == incoming_channel,
+            (
+                ReceptionistListener::SlackCommandKeyword { command, keyword },
+                ReceptionistListener::SlackCommandKeyword {
+                    command: incoming_command,
+                    keyword: incoming_keyword,
+                },
+            ) => command == incoming_command && keyword.eq_ignore_ascii_case(incoming_keyword),
+            (
+                ReceptionistListener::DirectMessage { .. },
+                ReceptionistListener::DirectMessage { .. },
+            ) => true,
+            (
+                ReceptionistListener::MemberJoinedChannel { channel_id, .. },
+                ReceptionistListener::MemberJoinedChannel {
+                    channel_id: incoming_channel,
+                    ..
+                },
+            ) => channel_id == incoming_channel,
+            (ReceptionistListener::Schedule { .. }, ReceptionistListener::Schedule { .. }) => true,
+            (ReceptionistListener::Webhook { .. }, ReceptionistListener::Webhook { .. }) => true,
+            (
+                ReceptionistListener::PagerDutyIncident { service_id, .. },
+                ReceptionistListener::PagerDutyIncident {
+                    service_id: incoming_service,
+                    ..
+                },
+            ) => service_id == incoming_service,
+            _ => false,
+        }
+    }
+
+    /// Check if a message posted in this channel should be handled by this listener
+    pub fn matches_channel(&self, channel: &IncomingChannel) -> bool {
         match self {
-            ReceptionistListener::SlackChannel { channel_id } => channel_id == incoming_channel,
+            ReceptionistListener::SlackChannel { channel_id } => channel_id == &channel.id,
+            ReceptionistListener::SlackChannels { channel_ids } => {
+                channel_ids.contains(&channel.id)
+            }
+            ReceptionistListener::SlackChannelPattern { pattern } => channel
+                .name
+                .as_deref()
+                .map_or(false, |name| channel_pattern_matches(pattern, name)),
+            ReceptionistListener::AllPublicChannels => channel.is_public,
+            ReceptionistListener::AppMention { .. }
+            | ReceptionistListener::SlackCommandKeyword { .. }
+            | ReceptionistListener::DirectMessage { .. }
+            | ReceptionistListener::MemberJoinedChannel { .. }
+            | ReceptionistListener::Schedule { .. }
+            | ReceptionistListener::Webhook { .. }
+            | ReceptionistListener::PagerDutyIncident { .. } => false,
         }
     }
 
+    /// Listeners triggered by regular messages posted in channels
+    pub fn listens_to_channel_messages(&self) -> bool {
+        matches!(
+            self,
+            ReceptionistListener::SlackChannel { .. }
+                | ReceptionistListener::SlackChannels { .. }
+                | ReceptionistListener::SlackChannelPattern { .. }
+                | ReceptionistListener::AllPublicChannels
+        )
+    }
+
+    pub fn is_fallback(&self) -> bool {
+        matches!(
+            self,
+            ReceptionistListener::DirectMessage { is_fallback: true }
+        )
+    }
+
+    /// Listeners that run their actions without checking message conditions
+    pub fn ignores_conditions(&self) -> bool {
+        self.is_fallback()
+            || matches!(
+                self,
+                ReceptionistListener::MemberJoinedChannel { .. }
+                    | ReceptionistListener::Schedule { .. }
+                    | ReceptionistListener::PagerDutyIncident { .. }
+            )
+    }
+
+    /// Whether the incoming channel's name must be looked up before calling `matches_channel`
+    pub fn needs_channel_name(&self) -> bool {
+        matches!(self, ReceptionistListener::SlackChannelPattern { .. })
+    }
+
     pub fn validate(&self) -> Option<SlackBlockValidationError> {
         match self {
             ReceptionistListener::SlackChannel { channel_id } => {
@@ -38,56 +230,708 @@ impl ReceptionistListener {
                     None
                 }
             }
+            ReceptionistListener::SlackChannels { channel_ids } => {
+                channel_ids.is_empty().then(|| SlackBlockValidationError {
+                    block_id: BlockSectionRouter::ListenerMultiChannelSelected.to_block_id(None),
+                    error_message: "No channels selected".to_string(),
+                })
+            }
+            ReceptionistListener::SlackChannelPattern { pattern } => {
+                if pattern.is_empty() {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerChannelPatternInput.to_block_id(None),
+                        error_message: "input field is empty".to_string(),
+                    })
+                } else if pattern.contains(char::is_whitespace) {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerChannelPatternInput.to_block_id(None),
+                        error_message: "channel names can't contain spaces".to_string(),
+                    })
+                } else {
+                    None
+                }
+            }
+            ReceptionistListener::AllPublicChannels => None,
+            ReceptionistListener::AppMention { .. } => None,
+            ReceptionistListener::DirectMessage { .. } => None,
+            ReceptionistListener::MemberJoinedChannel { channel_id, .. } => {
+                channel_id.is_empty().then(|| SlackBlockValidationError {
+                    block_id: BlockSectionRouter::ListenerChannelSelected.to_block_id(None),
+                    error_message: "No channel selected".to_string(),
+                })
+            }
+            ReceptionistListener::Schedule {
+                cron,
+                timezone,
+                channel_id,
+            } => {
+                if let Err(e) = parse_cron(cron) {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerScheduleCronInput.to_block_id(None),
+                        error_message: e.to_string(),
+                    })
+                } else if let Err(e) = parse_timezone(timezone) {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerScheduleTimezoneInput
+                            .to_block_id(None),
+                        error_message: e.to_string(),
+                    })
+                } else if channel_id.is_empty() {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerChannelSelected.to_block_id(None),
+                        error_message: "No channel selected".to_string(),
+                    })
+                } else {
+                    None
+                }
+            }
+            ReceptionistListener::Webhook { channel_id, .. } => {
+                channel_id.is_empty().then(|| SlackBlockValidationError {
+                    block_id: BlockSectionRouter::ListenerChannelSelected.to_block_id(None),
+                    error_message: "No channel selected".to_string(),
+                })
+            }
+            ReceptionistListener::PagerDutyIncident {
+                service_id,
+                channel_id,
+            } => {
+                if service_id.is_empty() || service_id.contains(char::is_whitespace) {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerPdServiceInput.to_block_id(None),
+                        error_message: "Enter a PagerDuty service ID".to_string(),
+                    })
+                } else if channel_id.is_empty() {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerChannelSelected.to_block_id(None),
+                        error_message: "No channel selected".to_string(),
+                    })
+                } else {
+                    None
+                }
+            }
+            ReceptionistListener::SlackCommandKeyword { keyword, .. } => {
+                if keyword.is_empty() || keyword.contains(char::is_whitespace) {
+                    Some(SlackBlockValidationError {
+                        block_id: BlockSectionRouter::ListenerCommandKeywordInput.to_block_id(None),
+                        error_message: "Enter a single word keyword".to_string(),
+                    })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Set the channel this listener applies to, `None` clears it where the listener allows
+    pub fn update_channel(&mut self, conversation_id: Option<String>) {
+        match self {
+            ReceptionistListener::SlackChannel { channel_id }
+            | ReceptionistListener::MemberJoinedChannel { channel_id, .. }
+            | ReceptionistListener::Schedule { channel_id, .. }
+            | ReceptionistListener::Webhook { channel_id, .. }
+            | ReceptionistListener::PagerDutyIncident { channel_id, .. } => {
+                if let Some(conversation_id) = conversation_id {
+                    *channel_id = conversation_id
+                }
+            }
+            ReceptionistListener::AppMention { channel_id } => *channel_id = conversation_id,
+            ReceptionistListener::SlackChannels { .. }
+            | ReceptionistListener::SlackChannelPattern { .. }
+            | ReceptionistListener::AllPublicChannels
+            | ReceptionistListener::SlackCommandKeyword { .. }
+            | ReceptionistListener::DirectMessage { .. } => (),
+        }
+    }
+
+    pub fn update_channels(&mut self, conversation_ids: Vec<String>) -> Result<()> {
+        match self {
+            ReceptionistListener::SlackChannels { channel_ids } => {
+                *channel_ids = conversation_ids;
+                Ok(())
+            }
+            _ => bail!("Not a multi-channel listener"),
+        }
+    }
+
+    pub fn update_channel_pattern(&mut self, new_pattern: String) -> Result<()> {
+        match self {
+            ReceptionistListener::SlackChannelPattern { pattern } => {
+                *pattern = new_pattern.trim().trim_start_matches('#').to_lowercase();
+                Ok(())
+            }
+            _ => bail!("Not a channel pattern listener"),
+        }
+    }
+
+    pub fn update_dm_fallback(&mut self, fallback: bool) -> Result<()> {
+        match self {
+            ReceptionistListener::DirectMessage { is_fallback } => {
+                *is_fallback = fallback;
+                Ok(())
+            }
+            _ => bail!("Not a direct message listener"),
+        }
+    }
+
+    pub fn update_welcome_options(
+        &mut self,
+        new_once_per_user: Option<bool>,
+        new_skip_guests: Option<bool>,
+    ) -> Result<()> {
+        match self {
+            ReceptionistListener::MemberJoinedChannel {
+                once_per_user,
+                skip_guests,
+                ..
+            } => {
+                if let Some(new_once_per_user) = new_once_per_user {
+                    *once_per_user = new_once_per_user;
+                }
+                if let Some(new_skip_guests) = new_skip_guests {
+                    *skip_guests = new_skip_guests;
+                }
+                Ok(())
+            }
+            _ => bail!("Not a member joined channel listener"),
+        }
+    }
+
+    pub fn update_schedule_cron(&mut self, new_cron: String) -> Result<()> {
+        match self {
+            ReceptionistListener::Schedule { cron, .. } => {
+                *cron = new_cron.trim().to_string();
+                Ok(())
+            }
+            _ => bail!("Not a schedule listener"),
+        }
+    }
+
+    pub fn update_schedule_timezone(&mut self, new_timezone: String) -> Result<()> {
+        match self {
+            ReceptionistListener::Schedule { timezone, .. } => {
+                *timezone = new_timezone.trim().to_string();
+                Ok(())
+            }
+            _ => bail!("Not a schedule listener"),
+        }
+    }
+
+    pub fn update_pagerduty_service(&mut self, new_service_id: String) -> Result<()> {
+        match self {
+            ReceptionistListener::PagerDutyIncident { service_id, .. } => {
+                *service_id = new_service_id.trim().to_string();
+                Ok(())
+            }
+            _ => bail!("Not a PagerDuty incident listener"),
+        }
+    }
+
+    pub fn update_command_keyword(&mut self, new_keyword: String) -> Result<()> {
+        match self {
+            ReceptionistListener::SlackCommandKeyword { keyword, .. } => {
+                *keyword = new_keyword.trim().to_lowercase();
+                Ok(())
+            }
+            _ => bail!("Not a slash command listener"),
+        }
+    }
+
+    pub fn update_listener_type(&mut self, type_str: &str) -> Result<()> {
+        let new_listener = Self::from_str(type_str)?;
+
+        let discrim: ReceptionistListenerDiscriminants = self.clone().into();
+        let new_listener_discrim: ReceptionistListenerDiscriminants = new_listener.into();
+        if discrim == new_listener_discrim {
+            return Ok(());
+        }
+
+        // retain the selected channel when changing listener types
+        let current_channel = match self {
+            ReceptionistListener::SlackChannel { channel_id }
+            | ReceptionistListener::MemberJoinedChannel { channel_id, .. }
+            | ReceptionistListener::Schedule { channel_id, .. }
+            | ReceptionistListener::Webhook { channel_id, .. }
+            | ReceptionistListener::PagerDutyIncident { channel_id, .. } => {
+                Some(std::mem::take(channel_id)).filter(|c| !c.is_empty())
+            }
+            ReceptionistListener::SlackChannels { channel_ids } => channel_ids.first().cloned(),
+            ReceptionistListener::AppMention { channel_id } => channel_id.take(),
+            ReceptionistListener::SlackChannelPattern { .. }
+            | ReceptionistListener::AllPublicChannels
+            | ReceptionistListener::SlackCommandKeyword { .. }
+            | ReceptionistListener::DirectMessage { .. } => None,
+        };
+
+        *self = match new_listener_discrim {
+            ReceptionistListenerDiscriminants::SlackChannel => ReceptionistListener::SlackChannel {
+                channel_id: current_channel.unwrap_or_default(),
+            },
+            ReceptionistListenerDiscriminants::SlackChannels => {
+                ReceptionistListener::SlackChannels {
+                    channel_ids: current_channel.into_iter().collect(),
+                }
+            }
+            ReceptionistListenerDiscriminants::SlackChannelPattern => {
+                ReceptionistListener::SlackChannelPattern {
+                    pattern: String::default(),
+                }
+            }
+            ReceptionistListenerDiscriminants::AllPublicChannels => {
+                ReceptionistListener::AllPublicChannels
+            }
+            ReceptionistListenerDiscriminants::AppMention => ReceptionistListener::AppMention {
+                channel_id: current_channel,
+            },
+            ReceptionistListenerDiscriminants::SlackCommandKeyword => {
+                ReceptionistListener::SlackCommandKeyword {
+                    command: KEYWORD_COMMAND.to_string(),
+                    keyword: String::default(),
+                }
+            }
+            ReceptionistListenerDiscriminants::DirectMessage => {
+                ReceptionistListener::DirectMessage { is_fallback: false }
+            }
+            ReceptionistListenerDiscriminants::MemberJoinedChannel => {
+                ReceptionistListener::MemberJoinedChannel {
+                    channel_id: current_channel.unwrap_or_default(),
+                    once_per_user: true,
+                    skip_guests: false,
+                }
+            }
+            ReceptionistListenerDiscriminants::Schedule => ReceptionistListener::Schedule {
+                cron: String::default(),
+                timezone: DEFAULT_SCHEDULE_TIMEZONE.to_string(),
+                channel_id: current_channel.unwrap_or_default(),
+            },
+            ReceptionistListenerDiscriminants::Webhook => ReceptionistListener::Webhook {
+                channel_id: current_channel.unwrap_or_default(),
+            },
+            ReceptionistListenerDiscriminants::PagerDutyIncident => {
+                ReceptionistListener::PagerDutyIncident {
+                    service_id: String::default(),
+                    channel_id: current_channel.unwrap_or_default(),
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    pub fn to_description(&self) -> &str {
+        match self {
+            ReceptionistListener::SlackChannel { .. } => "Messages in a Channel",
+            ReceptionistListener::SlackChannels { .. } => "Messages in several Channels",
+            ReceptionistListener::SlackChannelPattern { .. } => {
+                "Messages in Channels matching a name pattern"
+            }
+            ReceptionistListener::AllPublicChannels => "Messages in all public Channels",
+            ReceptionistListener::AppMention { .. } => "Messages that @mention the bot",
+            ReceptionistListener::SlackCommandKeyword { .. } => "Slash command keyword",
+            ReceptionistListener::DirectMessage { .. } => "Direct Messages to the bot",
+            ReceptionistListener::MemberJoinedChannel { .. } => "Users joining a Channel",
+            ReceptionistListener::Schedule { .. } => "On a schedule (cron)",
+            ReceptionistListener::Webhook { .. } => "Inbound webhook (JSON)",
+            ReceptionistListener::PagerDutyIncident { .. } => "PagerDuty incidents on a service",
         }
     }
 
+    pub fn to_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
+        SlackBlockChoiceItem::new(pt!(self.to_description()), self.to_string())
+    }
+
+    pub fn to_choice_items() -> Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> {
+        Self::iter()
+            .map(|variant| variant.to_choice_item())
+            .collect()
+    }
+
     pub fn default_blocks() -> Vec<SlackBlock> {
-        slack_blocks![
-            some_into(
-                SlackSectionBlock::new()
-                    .with_text(md!(":slack: Select a Channel"))
-                    .with_accessory(SlackSectionBlockElement::ConversationsSelect(
-                        SlackBlockConversationsSelectElement::new(
-                            BlockSectionRouter::ListenerChannelSelected.to_action_id(None),
-                            pt!("#my-channel"),
-                        ),
-                    ))
-                    .with_block_id(BlockSectionRouter::ListenerChannelSelected.to_block_id(None))
-            ),
-            some_into(SlackDividerBlock::new())
-        ]
+        Self::default().to_editor_blocks()
     }
 
-    pub fn to_editor_blocks(&self) -> Vec<SlackBlock> {
+    fn to_type_selector_blocks(&self) -> Vec<SlackBlock> {
+        slack_blocks![some_into(
+            SlackSectionBlock::new()
+                .with_text(md!(":ear: Select what this Response listens to"))
+                .with_accessory(SlackSectionBlockElement::StaticSelect(
+                    SlackBlockStaticSelectElement::new(
+                        BlockSectionRouter::ListenerTypeSelected.to_action_id(None),
+                        pt!("select listener Type")
+                    )
+                    .with_options(Self::to_choice_items())
+                    .with_initial_option(self.to_choice_item())
+                ))
+                .with_block_id(BlockSectionRouter::ListenerTypeSelected.to_block_id(None))
+        )]
+    }
+
+    fn to_value_input_blocks(&self) -> Vec<SlackBlock> {
         match self {
-            ReceptionistListener::SlackChannel { channel_id } => {
-                let conversations_select_element = SlackBlockConversationsSelectElement::new(
-                    BlockSectionRouter::ListenerChannelSelected.to_action_id(None),
-                    pt!("#my-channel"),
+            ReceptionistListener::SlackChannel { channel_id } => channel_select_blocks(
+                channel_id,
+                ":slack: Select a Channel                   :point_right:",
+            ),
+            ReceptionistListener::SlackChannels { channel_ids } => {
+                let multi_select_element = SlackBlockMultiConversationsSelectElement::new(
+                    BlockSectionRouter::ListenerMultiChannelSelected.to_action_id(None),
+                    pt!("#my-channels"),
                 );
 
-                let conversations_select_element = if !channel_id.is_empty() {
-                    conversations_select_element.with_initial_conversation(channel_id.into())
+                let multi_select_element = if channel_ids.is_empty() {
+                    multi_select_element
                 } else {
-                    conversations_select_element
+                    multi_select_element.with_initial_conversations(
+                        channel_ids.iter().map(|c| c.to_owned().into()).collect(),
+                    )
                 };
 
-                slack_blocks![
-                    some_into(
-                        SlackSectionBlock::new()
-                            .with_text(md!(
-                                ":slack: Select a Channel                   :point_right:"
-                            ))
-                            .with_accessory(SlackSectionBlockElement::ConversationsSelect(
-                                conversations_select_element
-                            ))
-                            .with_block_id(
-                                BlockSectionRouter::ListenerChannelSelected.to_block_id(None)
-                            )
+                slack_blocks![some_into(
+                    SlackSectionBlock::new()
+                        .with_text(md!(":slack: Select Channels"))
+                        .with_accessory(SlackSectionBlockElement::MultiConversationsSelect(
+                            multi_select_element
+                        ))
+                        .with_block_id(
+                            BlockSectionRouter::ListenerMultiChannelSelected.to_block_id(None)
+                        )
+                )]
+            }
+            ReceptionistListener::SlackChannelPattern { pattern } => {
+                let context: SlackContextBlockElement =
+                    md!("_Use_ `*` _as a wildcard, ex:_ `help-*` _matches_ `#help-payments`");
+
+                [
+                    slack_plain_text_input_block_for_view(
+                        BlockSectionRouter::ListenerChannelPatternInput,
+                        None,
+                        pattern.to_owned(),
+                        "help-*",
+                        "Enter a channel name pattern",
+                    ),
+                    slack_blocks![some_into(SlackContextBlock::new(vec![context]))],
+                ]
+                .concat()
+            }
+            ReceptionistListener::AllPublicChannels => {
+                let context: SlackContextBlockElement =
+                    md!("_Invite the bot to a public channel to start listening there_");
+
+                slack_blocks![some_into(SlackContextBlock::new(vec![context]))]
+            }
+            ReceptionistListener::AppMention { channel_id } => {
+                let context: SlackContextBlockElement =
+                    md!("_Leave empty to answer mentions in any channel the bot is in_");
+
+                [
+                    channel_select_blocks(
+                        channel_id.as_deref().unwrap_or_default(),
+                        ":slack: Only answer mentions in this Channel",
+                    ),
+                    slack_blocks![some_into(SlackContextBlock::new(vec![context]))],
+                ]
+                .concat()
+            }
+            ReceptionistListener::SlackCommandKeyword { command, keyword } => {
+                let context: SlackContextBlockElement = md!(
+                    "_Runs when someone types_ `{} <keyword> [args]`, _conditions are checked against the args_",
+                    command
+                );
+
+                [
+                    slack_plain_text_input_block_for_view(
+                        BlockSectionRouter::ListenerCommandKeywordInput,
+                        None,
+                        keyword.to_owned(),
+                        "oncall",
+                        "Enter the command keyword",
                     ),
-                    some_into(SlackDividerBlock::new())
+                    slack_blocks![some_into(SlackContextBlock::new(vec![context]))],
                 ]
+                .concat()
+            }
+            ReceptionistListener::DirectMessage { is_fallback } => two_option_select_blocks(
+                BlockSectionRouter::ListenerDmFallbackSelected,
+                ":speech_balloon: Run this Response",
+                ("When its conditions match", DM_CONDITIONS_VALUE),
+                ("When no other DM Response matches", DM_FALLBACK_VALUE),
+                *is_fallback,
+            ),
+            ReceptionistListener::MemberJoinedChannel {
+                channel_id,
+                once_per_user,
+                skip_guests,
+            } => [
+                channel_select_blocks(
+                    channel_id,
+                    ":slack: Select a Channel                   :point_right:",
+                ),
+                two_option_select_blocks(
+                    BlockSectionRouter::ListenerWelcomeFrequencySelected,
+                    ":repeat: Run for a user",
+                    ("Every time they join", WELCOME_EVERY_TIME_VALUE),
+                    ("Only the first time they join", WELCOME_ONCE_VALUE),
+                    *once_per_user,
+                ),
+                two_option_select_blocks(
+                    BlockSectionRouter::ListenerWelcomeGuestsSelected,
+                    ":busts_in_silhouette: Guest users",
+                    ("Include guests", WELCOME_INCLUDE_GUESTS_VALUE),
+                    ("Skip guests", WELCOME_SKIP_GUESTS_VALUE),
+                    *skip_guests,
+                ),
+            ]
+            .concat(),
+            ReceptionistListener::Schedule {
+                cron,
+                timezone,
+                channel_id,
+            } => {
+                let context: SlackContextBlockElement = md!(
+                    "_Cron format:_ `minute hour day-of-month month day-of-week`_, ex:_ `0 9 * * Mon` _is every Monday at 9am_"
+                );
+
+                [
+                    slack_plain_text_input_block_for_view(
+                        BlockSectionRouter::ListenerScheduleCronInput,
+                        None,
+                        cron.to_owned(),
+                        "0 9 * * Mon",
+                        "Enter a cron schedule",
+                    ),
+                    slack_blocks![some_into(SlackContextBlock::new(vec![context]))],
+                    slack_plain_text_input_block_for_view(
+                        BlockSectionRouter::ListenerScheduleTimezoneInput,
+                        None,
+                        timezone.to_owned(),
+                        DEFAULT_SCHEDULE_TIMEZONE,
+                        "Enter the schedule's timezone",
+                    ),
+                    channel_select_blocks(
+                        channel_id,
+                        ":slack: Select a Channel to post in        :point_right:",
+                    ),
+                ]
+                .concat()
+            }
+            ReceptionistListener::Webhook { channel_id, .. } => channel_select_blocks(
+                channel_id,
+                ":slack: Select a Channel to post in        :point_right:",
+            ),
+            ReceptionistListener::PagerDutyIncident {
+                service_id,
+                channel_id,
+            } => {
+                let context: SlackContextBlockElement = md!(
+                    "_Actions run when an incident triggers, acknowledgements and resolutions are posted in its thread. Template variables:_ `{{incident_title}}` `{{incident_number}}` `{{incident_url}}` `{{incident_urgency}}` `{{service_name}}`"
+                );
+
+                [
+                    slack_plain_text_input_block_for_view(
+                        BlockSectionRouter::ListenerPdServiceInput,
+                        None,
+                        service_id.to_owned(),
+                        "P1234567",
+                        "Enter the PagerDuty service ID",
+                    ),
+                    slack_blocks![some_into(SlackContextBlock::new(vec![context]))],
+                    channel_select_blocks(
+                        channel_id,
+                        ":slack: Select a Channel to post in        :point_right:",
+                    ),
+                ]
+                .concat()
             }
         }
     }
+
+    pub fn to_editor_blocks(&self) -> Vec<SlackBlock> {
+        [
+            self.to_type_selector_blocks(),
+            self.to_value_input_blocks(),
+            vec![SlackDividerBlock::new().into()],
+        ]
+        .concat()
+    }
+}
+
+/// Static select between two `(label, value)` options, the second is selected when `second_selected`
+fn two_option_select_blocks(
+    route: BlockSectionRouter,
+    text: &str,
+    first: (&str, &str),
+    second: (&str, &str),
+    second_selected: bool,
+) -> Vec<SlackBlock> {
+    let first_option = SlackBlockChoiceItem::new(pt!(first.0), first.1.to_string());
+    let second_option = SlackBlockChoiceItem::new(pt!(second.0), second.1.to_string());
+    let initial_option = if second_selected {
+        second_option.clone()
+    } else {
+        first_option.clone()
+    };
+
+    slack_blocks![some_into(
+        SlackSectionBlock::new()
+            .with_text(md!(text))
+            .with_accessory(SlackSectionBlockElement::StaticSelect(
+                SlackBlockStaticSelectElement::new(route.to_action_id(None), pt!("select one"))
+                    .with_options(vec![first_option, second_option])
+                    .with_initial_option(initial_option)
+            ))
+            .with_block_id(route.to_block_id(None))
+    )]
+}
+
+fn channel_select_blocks(channel_id: &str, text: &str) -> Vec<SlackBlock> {
+    let conversations_select_element = SlackBlockConversationsSelectElement::new(
+        BlockSectionRouter::ListenerChannelSelected.to_action_id(None),
+        pt!("#my-channel"),
+    );
+
+    let conversations_select_element = if !channel_id.is_empty() {
+        conversations_select_element.with_initial_conversation(channel_id.into())
+    } else {
+        conversations_select_element
+    };
+
+    slack_blocks![some_into(
+        SlackSectionBlock::new()
+            .with_text(md!(text))
+            .with_accessory(SlackSectionBlockElement::ConversationsSelect(
+                conversations_select_element
+            ))
+            .with_block_id(BlockSectionRouter::ListenerChannelSelected.to_block_id(None))
+    )]
+}
+
+/// Static select values for choosing whether a DM Response is the fallback
+pub const DM_CONDITIONS_VALUE: &str = "conditions";
+pub const DM_FALLBACK_VALUE: &str = "fallback";
+/// Timezone new schedules start with, any IANA timezone name is accepted
+pub const DEFAULT_SCHEDULE_TIMEZONE: &str = "UTC";
+/// Static select values for the member joined channel options
+pub const WELCOME_EVERY_TIME_VALUE: &str = "every-time";
+pub const WELCOME_ONCE_VALUE: &str = "once-per-user";
+pub const WELCOME_INCLUDE_GUESTS_VALUE: &str = "include-guests";
+pub const WELCOME_SKIP_GUESTS_VALUE: &str = "skip-guests";
+
+/// Match a channel name against a pattern where `*` matches any characters
+fn channel_pattern_matches(pattern: &str, channel_name: &str) -> bool {
+    let pattern_regex = pattern
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<String>>()
+        .join(".*");
+
+    match Regex::new(&format!("^{pattern_regex}$")) {
+        Ok(re) => re.is_match(&channel_name.to_lowercase()),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_mention_matches_channel_or_workspace() {
+        let in_channel = ReceptionistListener::AppMention {
+            channel_id: Some("C123".into()),
+        };
+        let workspace_wide = ReceptionistListener::AppMention { channel_id: None };
+
+        let incoming = ReceptionistListener::AppMention {
+            channel_id: Some("C123".into()),
+        };
+        let incoming_elsewhere = ReceptionistListener::AppMention {
+            channel_id: Some("C999".into()),
+        };
+
+        assert!(in_channel.matches(&incoming));
+        assert!(!in_channel.matches(&incoming_elsewhere));
+        assert!(workspace_wide.matches(&incoming_elsewhere));
+        assert!(
+            !workspace_wide.matches(&ReceptionistListener::SlackChannel {
+                channel_id: "C123".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_multi_channel_listeners_match_channel() {
+        let help_channel = IncomingChannel {
+            id: "C123".into(),
+            name: Some("help-payments".into()),
+            is_public: true,
+        };
+        let private_channel = IncomingChannel {
+            id: "G456".into(),
+            name: Some("payments-team".into()),
+            is_public: false,
+        };
+
+        let channels = ReceptionistListener::SlackChannels {
+            channel_ids: vec!["C123".into(), "C789".into()],
+        };
+        assert!(channels.matches_channel(&help_channel));
+        assert!(!channels.matches_channel(&private_channel));
+
+        let pattern = ReceptionistListener::SlackChannelPattern {
+            pattern: "help-*".into(),
+        };
+        assert!(pattern.matches_channel(&help_channel));
+        assert!(!pattern.matches_channel(&private_channel));
+        assert!(!pattern.matches_channel(&IncomingChannel {
+            name: None,
+            ..help_channel.clone()
+        }));
+
+        assert!(ReceptionistListener::AllPublicChannels.matches_channel(&help_channel));
+        assert!(!ReceptionistListener::AllPublicChannels.matches_channel(&private_channel));
+    }
+
+    #[test]
+    fn test_channel_pattern_matches() {
+        assert!(channel_pattern_matches("help-*", "help-payments"));
+        assert!(channel_pattern_matches("*-oncall", "payments-oncall"));
+        assert!(channel_pattern_matches("help.me", "help.me"));
+        assert!(!channel_pattern_matches("help.me", "helpxme"));
+        assert!(!channel_pattern_matches("help-*", "payments-help-desk"));
+    }
+
+    #[test]
+    fn test_direct_message_listener_matches() {
+        let fallback = ReceptionistListener::DirectMessage { is_fallback: true };
+        let incoming = ReceptionistListener::DirectMessage { is_fallback: false };
+
+        assert!(fallback.matches(&incoming));
+        assert!(fallback.is_fallback());
+        assert!(!incoming.is_fallback());
+        assert!(!fallback.matches_slack_channel_id("D123"));
+    }
+
+    #[test]
+    fn test_member_joined_channel_listener_matches() {
+        let welcome = ReceptionistListener::MemberJoinedChannel {
+            channel_id: "C123".into(),
+            once_per_user: true,
+            skip_guests: true,
+        };
+        let incoming = ReceptionistListener::MemberJoinedChannel {
+            channel_id: "C123".into(),
+            once_per_user: false,
+            skip_guests: false,
+        };
+
+        assert!(welcome.matches(&incoming));
+        assert!(welcome.ignores_conditions());
+        assert!(!welcome.matches_slack_channel_id("C123"));
+        assert!(
+            !welcome.matches(&ReceptionistListener::MemberJoinedChannel {
+                channel_id: "C456".into(),
+                once_per_user: false,
+                skip_guests: false,
+            })
+        );
+    }
 }
@@ -1,10 +1,27 @@
 mod actions;
+mod block_kit;
 mod conditions;
 mod listeners;
 mod responses;
+mod template;
 mod utils;
 
-pub use actions::{MessageAction, ReceptionistAction};
-pub use conditions::{MessageCondition, ReceptionistCondition};
-pub use listeners::ReceptionistListener;
+pub use actions::{
+    parse_mention_level, parse_webhook_headers, ChannelAction, CommandAction, MemberAction,
+    MessageAction, PagerDutyIncidentTrigger, PagerDutySeverity, ReceptionistAction, WebhookMethod,
+    WebhookRequest, WorkflowTrigger, PAGERDUTY_MAX_SUMMARY_LEN, WEBHOOK_NO_REPLY_VALUE,
+    WEBHOOK_REPLY_VALUE,
+};
+pub use block_kit::{
+    block_kit_builder_url, block_kit_fallback_text, render_block_kit_template,
+    validate_block_kit_json, MAX_BLOCKS_PER_MESSAGE,
+};
+pub use conditions::{
+    json_field_as_string, JsonCondition, MessageCondition, ReceptionistCondition,
+};
+pub use listeners::{
+    IncomingChannel, ReceptionistListener, DM_CONDITIONS_VALUE, DM_FALLBACK_VALUE,
+    WELCOME_ONCE_VALUE, WELCOME_SKIP_GUESTS_VALUE,
+};
 pub use responses::*;
+pub use template::{render_json_template, render_template};
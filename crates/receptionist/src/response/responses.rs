@@ -1,10 +1,13 @@
 use crate::{
-    add_emoji_colons, BlockSectionRouter, MessageAction, ReceptionistAction, ReceptionistCondition,
-    ReceptionistListener, SlackBlockValidationError,
+    add_emoji_colons, BlockSectionRouter, ChannelAction, CommandAction, MemberAction,
+    MessageAction, ReceptionistAction, ReceptionistCondition, ReceptionistListener,
+    SlackBlockValidationError, DM_FALLBACK_VALUE, WEBHOOK_SIGNATURE_HEADER,
+    WEBHOOK_TIMESTAMP_HEADER, WELCOME_ONCE_VALUE, WELCOME_SKIP_GUESTS_VALUE,
 };
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, Result};
 use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use slack_morphism::prelude::*;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -15,8 +18,15 @@ pub struct ReceptionistResponse {
     pub conditions: Vec<ReceptionistCondition>,
     pub actions: Vec<ReceptionistAction>,
     pub collaborators: Vec<String>,
+    /// Also check messages when they are edited, ex: someone adds "urgent" after posting
+    #[serde(default)]
+    pub run_on_edits: bool,
 }
 
+/// Static select values for the edited messages setting
+pub const RUN_ON_EDITS_VALUE: &str = "run-on-edits";
+pub const IGNORE_EDITS_VALUE: &str = "ignore-edits";
+
 impl Default for ReceptionistResponse {
     fn default() -> Self {
         let listener = ReceptionistListener::default();
@@ -26,6 +36,7 @@ impl Default for ReceptionistResponse {
             conditions: vec![ReceptionistCondition::default_from_listener(&listener)],
             collaborators: vec![],
             listener,
+            run_on_edits: false,
         }
     }
 }
@@ -47,6 +58,7 @@ impl ReceptionistResponse {
             collaborators,
             actions,
             conditions,
+            run_on_edits: false,
         }
     }
 
@@ -60,11 +72,23 @@ impl ReceptionistResponse {
                         return true;
                     }
                 }
+                ReceptionistCondition::ForJson(_) => (),
             }
         }
         false
     }
 
+    /// Check a JSON payload against this response's conditions, any match fires all actions.
+    /// Message conditions are checked against the raw JSON text
+    pub fn check_for_json_match(&self, payload: &Value) -> bool {
+        self.conditions.iter().any(|condition| match condition {
+            ReceptionistCondition::ForJson(json_trigger) => json_trigger.should_trigger(payload),
+            ReceptionistCondition::ForMessage(msg_trigger) => {
+                msg_trigger.should_trigger(&payload.to_string())
+            }
+        })
+    }
+
     pub fn get_action_mut(&mut self, index: usize) -> Result<&mut ReceptionistAction> {
         self.actions
             .get_mut(index)
@@ -72,14 +96,21 @@ impl ReceptionistResponse {
     }
 
     pub fn to_editor_blocks(&self) -> Vec<SlackBlock> {
-        let listener_blocks = self.listener.to_editor_blocks();
+        let listener_blocks = [
+            self.listener.to_editor_blocks(),
+            self.build_webhook_info_blocks(),
+        ]
+        .concat();
 
-        let conditions_blocks: Vec<SlackBlock> = self
-            .conditions
-            .iter()
-            .enumerate()
-            .flat_map(|(index, condition)| condition.to_editor_blocks(Some(index)))
-            .collect();
+        let conditions_blocks: Vec<SlackBlock> = if self.listener.ignores_conditions() {
+            vec![]
+        } else {
+            self.conditions
+                .iter()
+                .enumerate()
+                .flat_map(|(index, condition)| condition.to_editor_blocks(Some(index)))
+                .collect()
+        };
 
         let actions_blocks: Vec<SlackBlock> = self
             .actions
@@ -90,9 +121,16 @@ impl ReceptionistResponse {
 
         // TODO collaborator blocks
 
+        let edited_messages_blocks = if self.listener.listens_to_channel_messages() {
+            self.build_edited_messages_editor_blocks()
+        } else {
+            vec![]
+        };
+
         [
             self.build_collaborators_editor_blocks(),
             listener_blocks,
+            edited_messages_blocks,
             conditions_blocks,
             actions_blocks,
         ]
@@ -117,17 +155,87 @@ impl ReceptionistResponse {
         action.update_action_type(type_str)
     }
 
-    pub fn update_slack_channel(&mut self, conversation_id: String) -> Result<()> {
-        match &self.listener {
-            ReceptionistListener::SlackChannel { .. } => {
-                self.listener = ReceptionistListener::SlackChannel {
-                    channel_id: conversation_id,
-                };
-                Ok(())
+    pub fn update_slack_channel(&mut self, conversation_id: Option<String>) -> Result<()> {
+        self.listener.update_channel(conversation_id);
+        Ok(())
+    }
+
+    pub fn update_listener_type(&mut self, type_str: &str) -> Result<()> {
+        self.listener.update_listener_type(type_str)?;
+
+        // actions are specific to a kind of listener, replace any the new listener can't run
+        for action in self.actions.iter_mut() {
+            if !action.is_for_listener(&self.listener) {
+                *action = ReceptionistAction::default_from_listener(&self.listener);
+            }
+        }
+
+        // same for conditions, ex: webhooks check JSON fields instead of message text
+        for condition in self.conditions.iter_mut() {
+            if !condition.is_for_listener(&self.listener) {
+                *condition = ReceptionistCondition::default_from_listener(&self.listener);
             }
-            #[allow(unreachable_patterns)]
-            _ => bail!("Not a slack channel listener"),
         }
+
+        Ok(())
+    }
+
+    pub fn update_pagerduty_service(&mut self, service_id: String) -> Result<()> {
+        self.listener.update_pagerduty_service(service_id)
+    }
+
+    pub fn update_command_keyword(&mut self, keyword: String) -> Result<()> {
+        self.listener.update_command_keyword(keyword)
+    }
+
+    pub fn update_dm_fallback(&mut self, selected_value: &str) -> Result<()> {
+        self.listener
+            .update_dm_fallback(selected_value == DM_FALLBACK_VALUE)
+    }
+
+    pub fn update_welcome_frequency(&mut self, selected_value: &str) -> Result<()> {
+        self.listener
+            .update_welcome_options(Some(selected_value == WELCOME_ONCE_VALUE), None)
+    }
+
+    pub fn update_welcome_guests(&mut self, selected_value: &str) -> Result<()> {
+        self.listener
+            .update_welcome_options(None, Some(selected_value == WELCOME_SKIP_GUESTS_VALUE))
+    }
+
+    pub fn update_schedule_cron(&mut self, cron: String) -> Result<()> {
+        self.listener.update_schedule_cron(cron)
+    }
+
+    pub fn update_schedule_timezone(&mut self, timezone: String) -> Result<()> {
+        self.listener.update_schedule_timezone(timezone)
+    }
+
+    pub fn update_run_on_edits(&mut self, selected_value: &str) -> Result<()> {
+        self.run_on_edits = selected_value == RUN_ON_EDITS_VALUE;
+        Ok(())
+    }
+
+    pub fn update_slack_channels(&mut self, conversation_ids: Vec<String>) -> Result<()> {
+        self.listener.update_channels(conversation_ids)
+    }
+
+    pub fn update_channel_pattern(&mut self, pattern: String) -> Result<()> {
+        self.listener.update_channel_pattern(pattern)
+    }
+
+    pub fn update_json_condition_path(&mut self, path: String, index: usize) -> Result<()> {
+        self.conditions
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("condition not found"))?
+            .update_json_condition(Some(path), None)
+    }
+
+    pub fn update_json_condition_value(&mut self, value: String, index: usize) -> Result<()> {
+        self.conditions
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("condition not found"))?
+            .update_json_condition(None, Some(value))
     }
 
     pub fn update_message_condition_string(&mut self, new_str: String, index: usize) -> Result<()> {
@@ -146,9 +254,12 @@ impl ReceptionistResponse {
             validation_errors.push(validation_err)
         }
 
-        for (index, condition) in self.conditions.iter().enumerate() {
-            if let Some(validation_err) = condition.validate(Some(index)) {
-                validation_errors.push(validation_err)
+        // fallback and member joined Responses never check their conditions
+        if !self.listener.ignores_conditions() {
+            for (index, condition) in self.conditions.iter().enumerate() {
+                if let Some(validation_err) = condition.validate(Some(index)) {
+                    validation_errors.push(validation_err)
+                }
             }
         }
 
@@ -172,6 +283,65 @@ impl ReceptionistResponse {
         }
     }
 
+    /// How to call a webhook Response, it needs the Response ID so it can't live with the listener blocks
+    fn build_webhook_info_blocks(&self) -> Vec<SlackBlock> {
+        match &self.listener {
+            ReceptionistListener::Webhook { .. } => {
+                let route: SlackContextBlockElement = md!(
+                    "`POST /hooks/{}` _with a JSON body. Sign it like Slack does: header_ `{}` _is the unix timestamp and_ `{}` _is_ `v0=` _+ hex HMAC-SHA256 of_ `v0:<timestamp>:<body>` _with the signing secret, which is only shown when the Response is first saved as a webhook or the secret is rotated_",
+                    self.id,
+                    WEBHOOK_TIMESTAMP_HEADER,
+                    WEBHOOK_SIGNATURE_HEADER
+                );
+                let rotate_button = SlackBlockButtonElement::new(
+                    BlockSectionRouter::WebhookSecretRotateClicked.to_action_id(None),
+                    pt!("Rotate signing secret"),
+                );
+
+                slack_blocks![
+                    some_into(SlackContextBlock::new(vec![route])),
+                    some_into(
+                        SlackActionsBlock::new(vec![SlackActionBlockElement::Button(
+                            rotate_button
+                        )])
+                        .with_block_id(
+                            BlockSectionRouter::WebhookSecretRotateClicked.to_block_id(None)
+                        )
+                    )
+                ]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn build_edited_messages_editor_blocks(&self) -> Vec<SlackBlock> {
+        let ignore_option =
+            SlackBlockChoiceItem::new(pt!("Ignore edits"), IGNORE_EDITS_VALUE.to_string());
+        let run_option = SlackBlockChoiceItem::new(
+            pt!("Check edited messages too"),
+            RUN_ON_EDITS_VALUE.to_string(),
+        );
+        let initial_option = if self.run_on_edits {
+            run_option.clone()
+        } else {
+            ignore_option.clone()
+        };
+
+        slack_blocks![some_into(
+            SlackSectionBlock::new()
+                .with_text(md!(":pencil2: Edited messages"))
+                .with_accessory(SlackSectionBlockElement::StaticSelect(
+                    SlackBlockStaticSelectElement::new(
+                        BlockSectionRouter::EditedMessagesSelected.to_action_id(None),
+                        pt!("select how to handle edits")
+                    )
+                    .with_options(vec![ignore_option, run_option])
+                    .with_initial_option(initial_option)
+                ))
+                .with_block_id(BlockSectionRouter::EditedMessagesSelected.to_block_id(None))
+        )]
+    }
+
     fn build_collaborators_editor_blocks(&self) -> Vec<SlackBlock> {
         let multi_users_select_element = SlackBlockMultiUsersSelectElement::new(
             BlockSectionRouter::CollaboratorSelection.to_action_id(None),
@@ -197,6 +367,40 @@ impl ReceptionistResponse {
     pub fn to_response_choice_item(&self) -> SlackBlockChoiceItem<SlackBlockPlainTextOnly> {
         let listener = match &self.listener {
             ReceptionistListener::SlackChannel { channel_id } => format!("#<#{channel_id}>"),
+            ReceptionistListener::SlackChannels { channel_ids } => channel_ids
+                .iter()
+                .map(|channel_id| format!("#<#{channel_id}>"))
+                .collect::<Vec<String>>()
+                .join(", "),
+            ReceptionistListener::SlackChannelPattern { pattern } => format!("#{pattern}"),
+            ReceptionistListener::AllPublicChannels => "all public channels".to_string(),
+            ReceptionistListener::AppMention { channel_id } => match channel_id {
+                Some(channel_id) => format!("@mention in #<#{channel_id}>"),
+                None => "@mention anywhere".to_string(),
+            },
+            ReceptionistListener::SlackCommandKeyword { command, keyword } => {
+                format!("{command} {keyword}")
+            }
+            ReceptionistListener::MemberJoinedChannel { channel_id, .. } => {
+                format!("joined #<#{channel_id}>")
+            }
+            ReceptionistListener::Schedule {
+                cron, channel_id, ..
+            } => format!("`{cron}` in #<#{channel_id}>"),
+            ReceptionistListener::Webhook { channel_id, .. } => {
+                format!("webhook to #<#{channel_id}>")
+            }
+            ReceptionistListener::PagerDutyIncident {
+                service_id,
+                channel_id,
+            } => format!("PD {service_id} incidents to #<#{channel_id}>"),
+            ReceptionistListener::DirectMessage { is_fallback } => {
+                if *is_fallback {
+                    "DM fallback".to_string()
+                } else {
+                    "DM".to_string()
+                }
+            }
         };
 
         let actions: String = self
@@ -207,11 +411,31 @@ impl ReceptionistResponse {
                     MessageAction::AttachEmoji(emoji) => add_emoji_colons(emoji),
                     MessageAction::ThreadedMessage(msg) => msg.to_owned(),
                     MessageAction::ChannelMessage(msg) => msg.to_owned(),
+                    MessageAction::Webhook(webhook) => {
+                        format!("Webhook: {} {}", webhook.method, webhook.url)
+                    }
+                    MessageAction::TriggerWorkflow(_) => "Start Slack Workflow".to_string(),
+                    MessageAction::TriggerPagerDutyIncident(trigger) => {
+                        format!("Page PagerDuty: {}", trigger.summary)
+                    }
+                    MessageAction::EphemeralToSender(msg)
+                    | MessageAction::DirectMessageToSender(msg) => msg.to_owned(),
+                    MessageAction::ThreadedBlocks(_) | MessageAction::ChannelBlocks(_) => {
+                        "Block Kit message".to_string()
+                    }
+                    MessageAction::InviteOncallToChannel {
+                        usergroup_id: Some(usergroup_id),
+                        ..
+                    } => format!("Invite User Group: {usergroup_id}"),
+                    MessageAction::InviteOncallToChannel {
+                        target,
+                        usergroup_id: None,
+                        ..
+                    } => format!("Invite Oncall: {target}"),
                     MessageAction::MsgOncallInThread {
-                        escalation_policy_id,
-                        message,
+                        target, message, ..
                     } => format!(
-                        "Tag Oncall: {escalation_policy_id} - {}..",
+                        "Tag Oncall: {target} - {}..",
                         message.chars().take(10).collect::<String>()
                     ),
                     MessageAction::ForwardMessageToChannel {
@@ -222,6 +446,28 @@ impl ReceptionistResponse {
                         msg_context.chars().take(10).collect::<String>()
                     ),
                 },
+                ReceptionistAction::ForCommand(cmd_act) => match cmd_act {
+                    CommandAction::EphemeralReply(msg) | CommandAction::ChannelReply(msg) => {
+                        msg.to_owned()
+                    }
+                },
+                ReceptionistAction::ForChannel(channel_act) => match channel_act {
+                    ChannelAction::ChannelMessage(msg) => msg.to_owned(),
+                    ChannelAction::ChannelBlocks(_) => "Block Kit message".to_string(),
+                    ChannelAction::OncallMessage {
+                        target, message, ..
+                    } => format!(
+                        "Post Oncall: {target} - {}..",
+                        message.chars().take(10).collect::<String>()
+                    ),
+                    ChannelAction::OncallTopic {
+                        target, location, ..
+                    } => format!("Oncall in {}: {target}", location.to_description()),
+                },
+                ReceptionistAction::ForMember(member_act) => match member_act {
+                    MemberAction::EphemeralWelcome(msg)
+                    | MemberAction::DirectMessageWelcome(msg) => msg.to_owned(),
+                },
             })
             .collect();
 
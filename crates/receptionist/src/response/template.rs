@@ -0,0 +1,77 @@
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Replace `{{variable}}` placeholders in an action's text with values from the triggering event.
+/// Unknown variables are left untouched so that typos are visible in the posted message
+/// # Examples
+/// ```rust
+/// use receptionist::render_template;
+/// use std::collections::HashMap;
+///
+/// let vars = HashMap::from([("args".to_string(), "payments".to_string())]);
+/// assert_eq!("oncall for payments", render_template("oncall for {{args}}", &vars));
+/// assert_eq!("hi {{nobody}}", render_template("hi {{nobody}}", &vars));
+/// ```
+pub fn render_template(template: &str, vars: &HashMap<String, String>) -> String {
+    let re = Regex::new(r"\{\{\s*([\w.\-]+)\s*\}\}").expect("Unable to compile template regex");
+
+    re.replace_all(template, |caps: &Captures| match vars.get(&caps[1]) {
+        Some(value) => value.to_owned(),
+        None => caps[0].to_string(),
+    })
+    .into_owned()
+}
+
+/// `render_template` for every string inside a JSON value, so values containing quotes can't break the JSON
+pub fn render_json_template(value: Value, vars: &HashMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => Value::String(render_template(&s, vars)),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|v| render_json_template(v, vars))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, render_json_template(v, vars)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let vars = HashMap::from([
+            ("keyword".to_string(), "oncall".to_string()),
+            ("arg_1".to_string(), "payments".to_string()),
+        ]);
+
+        assert_eq!(
+            "oncall: payments",
+            render_template("{{keyword}}: {{ arg_1 }}", &vars)
+        );
+        assert_eq!("no variables", render_template("no variables", &vars));
+        assert_eq!("{{missing}}", render_template("{{missing}}", &vars));
+    }
+
+    #[test]
+    fn test_render_json_template() {
+        let vars = HashMap::from([("title".to_string(), r#"say "hi""#.to_string())]);
+        let rendered = render_json_template(
+            serde_json::json!({"summary": "{{title}}", "tags": ["{{title}}"], "count": 1}),
+            &vars,
+        );
+
+        assert_eq!(
+            rendered,
+            serde_json::json!({"summary": r#"say "hi""#, "tags": [r#"say "hi""#], "count": 1})
+        );
+    }
+}
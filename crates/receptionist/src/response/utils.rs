@@ -27,3 +27,31 @@ pub fn slack_plain_text_input_block_for_view(
         .with_block_id(router_variant.to_block_id(index),)
     )]
 }
+
+/// Same as `slack_plain_text_input_block_for_view` but with room for longer text, ex: Block Kit JSON
+pub fn slack_multiline_input_block_for_view(
+    router_variant: BlockSectionRouter,
+    index: Option<usize>,
+    existing_value: String,
+    placeholder: &str,
+    label: &str,
+    optional: bool,
+) -> Vec<SlackBlock> {
+    let input_element =
+        SlackBlockPlainTextInputElement::new(router_variant.to_action_id(index), pt!(placeholder))
+            .with_multiline(true);
+    let input_element = if existing_value.is_empty() {
+        input_element
+    } else {
+        input_element.with_initial_value(existing_value)
+    };
+
+    slack_blocks![some_into(
+        SlackInputBlock::new(
+            pt!(label),
+            SlackInputBlockElement::PlainTextInput(input_element)
+        )
+        .with_optional(optional)
+        .with_block_id(router_variant.to_block_id(index),)
+    )]
+}
@@ -0,0 +1,374 @@
+#[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+use crate::database::{
+    claim_scheduled_run, complete_scheduled_run, get_last_scheduled_run, get_responses_for_listener,
+};
+use crate::{run_response_actions, ActionContext, ReceptionistListener, SlackStateWorkaround};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use std::{collections::HashMap, str::FromStr};
+use tracing::{error, info};
+
+/// How often `rec_server` checks for due schedules
+pub const SCHEDULER_TICK_SECS: u64 = 60;
+
+/// Runs missed while the scheduler was down are only caught up on if they are this recent,
+/// so a long outage doesn't post a backlog of stale messages
+const CATCH_UP_WINDOW_MINUTES: i64 = 60;
+
+/// A claimed run that hasn't finished after this long is assumed lost with its scheduler instance,
+/// it is claimed and run again while it is still inside the catch up window
+const CLAIM_TIMEOUT_MINUTES: i64 = 10;
+
+/// Crontab day-of-week numbers (0 and 7 are Sunday) as names, the `cron` crate numbers Sunday as 1
+const CRONTAB_WEEKDAYS: [&str; 8] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Parse a cron expression. Standard 5 field expressions (`min hour day month weekday`)
+/// are accepted alongside the 6 or 7 field format with seconds
+/// # Examples
+/// ```rust
+/// use receptionist::parse_cron;
+///
+/// assert!(parse_cron("0 9 * * Mon").is_ok());
+/// assert!(parse_cron("0 9 * * 1-5").is_ok());
+/// assert!(parse_cron("0 0 9 * * Mon").is_ok());
+/// assert!(parse_cron("every monday").is_err());
+/// ```
+pub fn parse_cron(cron_expression: &str) -> Result<Schedule> {
+    let cron_expression = cron_expression.trim();
+    let fields: Vec<&str> = cron_expression.split_whitespace().collect();
+
+    let with_seconds = if fields.len() == 5 {
+        format!(
+            "0 {} {}",
+            fields[..4].join(" "),
+            crontab_weekdays_to_names(fields[4])?
+        )
+    } else {
+        cron_expression.to_string()
+    };
+
+    Schedule::from_str(&with_seconds).map_err(|e| anyhow!("Invalid cron expression: {}", e))
+}
+
+/// Numeric crontab day-of-week values (`1`, `1-5`, `0,6`, `*/2`) as day names, names and `*` are kept
+fn crontab_weekdays_to_names(weekday_field: &str) -> Result<String> {
+    let weekday = |value: &str| -> Result<Option<usize>> {
+        match value.parse::<usize>() {
+            Ok(day) if day < CRONTAB_WEEKDAYS.len() => Ok(Some(day)),
+            Ok(day) => Err(anyhow!(
+                "Invalid cron expression: day of week {day} is not between 0 and 7"
+            )),
+            Err(_) => Ok(None),
+        }
+    };
+
+    let items = weekday_field
+        .split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+
+            let names = match range.split_once('-') {
+                Some((start, end)) => match (weekday(start)?, weekday(end)?) {
+                    (Some(7), Some(7)) => "Sun".to_string(),
+                    // crontab allows ending a range on Sunday as 7, day names can't wrap around
+                    (Some(start), Some(7)) if start > 0 => match step {
+                        Some(_) => {
+                            return Err(anyhow!(
+                                "Invalid cron expression: list stepped days of the week ending on Sunday by name, ex: Fri,Sun"
+                            ))
+                        }
+                        None => format!("{}-Sat,Sun", CRONTAB_WEEKDAYS[start]),
+                    },
+                    (Some(start), Some(end)) => {
+                        format!(
+                            "{}-{}",
+                            CRONTAB_WEEKDAYS[start],
+                            CRONTAB_WEEKDAYS[end.min(6)]
+                        )
+                    }
+                    _ => range.to_string(),
+                },
+                None => match weekday(range)? {
+                    Some(day) => CRONTAB_WEEKDAYS[day].to_string(),
+                    None => range.to_string(),
+                },
+            };
+
+            Ok(match step {
+                Some(step) => format!("{names}/{step}"),
+                None => names,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(items.join(","))
+}
+
+/// Parse an IANA timezone name, ex: `America/Los_Angeles`
+pub fn parse_timezone(timezone: &str) -> Result<Tz> {
+    timezone
+        .trim()
+        .parse::<Tz>()
+        .map_err(|e| anyhow!("Invalid timezone: {}", e))
+}
+
+/// The most recent scheduled time that is due at `now` and hasn't run yet, if any
+pub fn due_run(
+    cron_expression: &str,
+    timezone: &str,
+    last_run: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let schedule = parse_cron(cron_expression)?;
+    let tz = parse_timezone(timezone)?;
+
+    let catch_up_start = now - Duration::minutes(CATCH_UP_WINDOW_MINUTES);
+    let window_start = match last_run {
+        Some(last_run) if last_run > catch_up_start => last_run,
+        _ => catch_up_start,
+    };
+
+    let mut due = None;
+    for occurrence in schedule.after(&window_start.with_timezone(&tz)) {
+        let occurrence = occurrence.with_timezone(&Utc);
+        if occurrence > now {
+            break;
+        }
+        due = Some(occurrence);
+    }
+
+    Ok(due)
+}
+
+/// Run the actions of every scheduled Response that is due.
+/// Each run is claimed in the database before its actions run and completed after, so that
+/// multiple scheduler instances don't run the same scheduled time twice, and a run lost
+/// to a restart is retried
+pub async fn run_due_schedules(
+    slack_state: &SlackStateWorkaround,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let scheduled_responses = get_responses_for_listener(ReceptionistListener::Schedule {
+        cron: String::default(),
+        timezone: String::default(),
+        channel_id: String::default(),
+    })
+    .await?;
+
+    let slack_session = slack_state.open_session();
+
+    for rec_response in &scheduled_responses {
+        let (cron_expression, timezone, channel_id) = match &rec_response.listener {
+            ReceptionistListener::Schedule {
+                cron,
+                timezone,
+                channel_id,
+            } => (cron, timezone, channel_id),
+            _ => continue,
+        };
+
+        let last_run = match get_last_scheduled_run(&rec_response.id).await {
+            Ok(last_run) => last_run.map(|timestamp| Utc.timestamp(timestamp, 0)),
+            Err(e) => {
+                error!(
+                    "Unable to get last run of response {}: {}",
+                    rec_response.id, e
+                );
+                continue;
+            }
+        };
+
+        let scheduled_time = match due_run(cron_expression, timezone, last_run, now) {
+            Ok(Some(scheduled_time)) => scheduled_time,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("Invalid schedule for response {}: {}", rec_response.id, e);
+                continue;
+            }
+        };
+
+        match claim_scheduled_run(
+            &rec_response.id,
+            scheduled_time.timestamp(),
+            now.timestamp(),
+            (now - Duration::minutes(CLAIM_TIMEOUT_MINUTES)).timestamp(),
+        )
+        .await
+        {
+            Ok(true) => (),
+            Ok(false) => {
+                info!(
+                    "Scheduled run of {} at {} was already claimed",
+                    rec_response.id, scheduled_time
+                );
+                continue;
+            }
+            Err(e) => {
+                // unclaimed runs are retried on the next tick while still in the catch up window
+                error!(
+                    "Unable to claim scheduled run of {} at {}: {}",
+                    rec_response.id, scheduled_time, e
+                );
+                continue;
+            }
+        }
+
+        let context = ActionContext {
+            channel_id: channel_id.to_owned().into(),
+            message_ts: None,
+            sender: String::default(),
+            template_vars: HashMap::from([
+                ("channel_id".to_string(), channel_id.to_owned()),
+                ("scheduled_time".to_string(), scheduled_time.to_rfc3339()),
+            ]),
+        };
+
+        run_response_actions(&slack_session, rec_response, &context).await;
+
+        if let Err(e) = complete_scheduled_run(&rec_response.id, scheduled_time.timestamp()).await {
+            error!(
+                "Unable to complete scheduled run of {} at {}: {}",
+                rec_response.id, scheduled_time, e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Weekday};
+
+    #[test]
+    fn test_due_run() {
+        // Monday 2022-03-07 09:00 in Los Angeles is 17:00 UTC
+        let every_monday_9am = "0 9 * * Mon";
+        let timezone = "America/Los_Angeles";
+        let scheduled_time = Utc.ymd(2022, 3, 7).and_hms(17, 0, 0);
+
+        // not due yet
+        assert_eq!(
+            None,
+            due_run(
+                every_monday_9am,
+                timezone,
+                None,
+                scheduled_time - Duration::minutes(1)
+            )
+            .unwrap()
+        );
+
+        // due
+        assert_eq!(
+            Some(scheduled_time),
+            due_run(
+                every_monday_9am,
+                timezone,
+                None,
+                scheduled_time + Duration::minutes(1)
+            )
+            .unwrap()
+        );
+
+        // already ran
+        assert_eq!(
+            None,
+            due_run(
+                every_monday_9am,
+                timezone,
+                Some(scheduled_time),
+                scheduled_time + Duration::minutes(1)
+            )
+            .unwrap()
+        );
+
+        // missed for longer than the catch up window
+        assert_eq!(
+            None,
+            due_run(
+                every_monday_9am,
+                timezone,
+                None,
+                scheduled_time + Duration::hours(2)
+            )
+            .unwrap()
+        );
+    }
+
+    /// A claim blocks other instances until it completes or goes stale,
+    /// a claim that never completed is taken over once it is stale
+    #[cfg(feature = "tempdb")]
+    #[tokio::test]
+    async fn test_claim_scheduled_run() {
+        let run_at = Utc.ymd(2022, 3, 7).and_hms(17, 0, 0);
+        let claim = |now: DateTime<Utc>| {
+            claim_scheduled_run(
+                "schedule-claim-test",
+                run_at.timestamp(),
+                now.timestamp(),
+                (now - Duration::minutes(CLAIM_TIMEOUT_MINUTES)).timestamp(),
+            )
+        };
+
+        assert!(claim(run_at).await.unwrap());
+        assert!(!claim(run_at + Duration::minutes(1)).await.unwrap());
+        // the first claim never completed
+        assert!(claim(run_at + Duration::minutes(CLAIM_TIMEOUT_MINUTES + 1))
+            .await
+            .unwrap());
+        assert_eq!(
+            get_last_scheduled_run("schedule-claim-test").await.unwrap(),
+            None
+        );
+
+        complete_scheduled_run("schedule-claim-test", run_at.timestamp())
+            .await
+            .unwrap();
+        assert_eq!(
+            get_last_scheduled_run("schedule-claim-test").await.unwrap(),
+            Some(run_at.timestamp())
+        );
+        assert!(!claim(run_at + Duration::minutes(30)).await.unwrap());
+    }
+
+    #[test]
+    fn test_parse_schedule_inputs() {
+        assert!(parse_cron("*/15 * * * *").is_ok());
+        assert!(parse_cron("not a cron").is_err());
+        assert!(parse_timezone("Europe/London").is_ok());
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_crontab_weekday_numbers() {
+        let next_weekday = |cron_expression: &str| {
+            parse_cron(cron_expression)
+                .unwrap()
+                .after(&Utc.ymd(2022, 3, 6).and_hms(12, 0, 0))
+                .next()
+                .unwrap()
+                .weekday()
+        };
+
+        // 2022-03-06 was a Sunday
+        assert_eq!(next_weekday("0 9 * * 1"), Weekday::Mon);
+        assert_eq!(next_weekday("0 9 * * 0"), Weekday::Sun);
+        assert_eq!(next_weekday("0 9 * * 7"), Weekday::Sun);
+        assert_eq!(next_weekday("0 9 * * 5-7"), Weekday::Fri);
+        assert_eq!(next_weekday("0 9 * * 2,4"), Weekday::Tue);
+
+        assert_eq!(crontab_weekdays_to_names("1-5").unwrap(), "Mon-Fri");
+        assert_eq!(crontab_weekdays_to_names("5-7").unwrap(), "Fri-Sat,Sun");
+        assert_eq!(crontab_weekdays_to_names("*/2").unwrap(), "*/2");
+        assert_eq!(crontab_weekdays_to_names("Mon").unwrap(), "Mon");
+        assert_eq!(crontab_weekdays_to_names("7-7").unwrap(), "Sun");
+        assert!(parse_cron("0 9 * * 8").is_err());
+    }
+}
@@ -0,0 +1,963 @@
+use crate::{
+    block_kit_fallback_text,
+    config::get_or_init_app_config,
+    format_forwarded_message, get_or_init_oncall_cache, has_oncall_segment, next_escalation_level,
+    oncall_users_to_mention,
+    pagerduty::models::{EventLink, EventPayload, EventV2},
+    render_block_kit_template, render_template,
+    response::{
+        ChannelAction, CommandAction, MemberAction, MessageAction, PagerDutyIncidentTrigger,
+        ReceptionistAction, ReceptionistResponse, PAGERDUTY_MAX_SUMMARY_LEN,
+    },
+    slack::api_calls::{
+        auth_test_user_id, chat_update_text, conversations_invite, conversations_set_topic,
+        conversations_topic, dnd_info, pins_add, pins_list, reactions_add, usergroups_users_list,
+        users_get_presence, users_info_status, ChannelInviteOutcome,
+    },
+    unavailable_note,
+    webhooks::outbound::send_webhook,
+    with_oncall_segment, OncallCache, OncallFallback, OncallTarget, OncallTopicLocation,
+    OncallUnavailableAction, OncallUser, PagerDutyEvents, SlackUnavailability,
+};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use slack_morphism::prelude::*;
+use slack_morphism_hyper::SlackClientHyperHttpsConnector;
+use std::collections::HashMap;
+use tracing::{error, warn};
+
+/// Details about the Slack event that triggered a Response, shared by all of its actions
+#[derive(Debug, Clone)]
+pub struct ActionContext {
+    pub channel_id: SlackChannelId,
+    /// Timestamp of the triggering message, `None` when the trigger was not a message (ex: slash commands)
+    pub message_ts: Option<SlackTs>,
+    /// Slack user id (or bot id/username) of whoever triggered the Response
+    pub sender: String,
+    /// Values that action text can reference as `{{name}}`
+    pub template_vars: HashMap<String, String>,
+}
+
+/// Run every action of a Response whose conditions have already matched.
+/// Failures are logged per action so that one failing action does not prevent the others from running.
+/// Returns the timestamps of messages posted by channel actions, so callers can follow up in their threads
+pub async fn run_response_actions(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    rec_response: &ReceptionistResponse,
+    context: &ActionContext,
+) -> Vec<SlackTs> {
+    let mut posted_messages = Vec::new();
+
+    for action in &rec_response.actions {
+        match action {
+            ReceptionistAction::ForMessage(message_action) => {
+                run_message_action(slack_session, message_action, context).await
+            }
+            ReceptionistAction::ForCommand(command_action) => {
+                run_command_action(slack_session, command_action, context).await
+            }
+            ReceptionistAction::ForMember(member_action) => {
+                run_member_action(slack_session, member_action, context).await
+            }
+            ReceptionistAction::ForChannel(channel_action) => {
+                if let Some(message_ts) =
+                    run_channel_action(slack_session, channel_action, context).await
+                {
+                    posted_messages.push(message_ts)
+                }
+            }
+        }
+    }
+
+    posted_messages
+}
+
+async fn run_channel_action(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel_action: &ChannelAction,
+    context: &ActionContext,
+) -> Option<SlackTs> {
+    let content = match channel_action {
+        ChannelAction::ChannelMessage(msg) => {
+            SlackMessageContent::new().with_text(render_template(msg, &context.template_vars))
+        }
+        ChannelAction::ChannelBlocks(blocks_json) => {
+            block_kit_content(blocks_json, &context.template_vars)?
+        }
+        ChannelAction::OncallMessage {
+            target, message, ..
+        } => {
+            let mentions = find_oncall_mentions(
+                slack_session,
+                target,
+                None,
+                OncallUnavailableAction::TagAnyway,
+            )
+            .await?;
+
+            SlackMessageContent::new().with_text(format!(
+                "{mentions} - {}",
+                render_template(message, &context.template_vars)
+            ))
+        }
+        ChannelAction::OncallTopic {
+            target,
+            location,
+            label,
+            ..
+        } => {
+            let mentions = find_oncall_mentions(
+                slack_session,
+                target,
+                None,
+                OncallUnavailableAction::TagAnyway,
+            )
+            .await?;
+
+            if let Err(e) = set_oncall_topic(
+                slack_session,
+                &context.channel_id,
+                *location,
+                label,
+                &mentions,
+            )
+            .await
+            {
+                error!(
+                    "Unable to set the oncall in {}: {}",
+                    location.to_description(),
+                    e
+                );
+            }
+            // nothing was posted for other actions to follow up on
+            return None;
+        }
+    };
+
+    match slack_session
+        .chat_post_message(&SlackApiChatPostMessageRequest::new(
+            context.channel_id.to_owned(),
+            content,
+        ))
+        .await
+    {
+        Ok(posted) => Some(posted.ts),
+        Err(slack_err) => {
+            error!("Unable to post message to channel: {}", slack_err);
+            None
+        }
+    }
+}
+
+/// Replace the `label: ...` part of the channel topic or of the message the bot pinned, leaving the rest of the text.
+/// The first run of a pinned message location posts and pins the message
+async fn set_oncall_topic(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel_id: &SlackChannelId,
+    location: OncallTopicLocation,
+    label: &str,
+    mentions: &str,
+) -> Result<()> {
+    let separator = location.separator();
+
+    match location {
+        OncallTopicLocation::Topic => {
+            let current = conversations_topic(slack_session, channel_id)
+                .await
+                .map_err(|slack_err| anyhow!("Unable to get channel topic: {}", slack_err))?
+                .channel
+                .topic
+                .map(|topic| topic.value)
+                .unwrap_or_default();
+
+            let topic = with_oncall_segment(&current, label, mentions, separator);
+            if topic != current {
+                conversations_set_topic(slack_session, channel_id, &topic)
+                    .await
+                    .map_err(|slack_err| anyhow!("Unable to set channel topic: {}", slack_err))?;
+            }
+        }
+        OncallTopicLocation::PinnedMessage => {
+            let bot_user_id = auth_test_user_id(slack_session)
+                .await
+                .map_err(|slack_err| anyhow!("Unable to get the bot user: {}", slack_err))?
+                .user_id;
+
+            let pinned = pins_list(slack_session, channel_id)
+                .await
+                .map_err(|slack_err| anyhow!("Unable to list pinned messages: {}", slack_err))?
+                .items
+                .into_iter()
+                .filter_map(|item| item.message)
+                .find(|message| {
+                    message.user.as_ref() == Some(&bot_user_id)
+                        && has_oncall_segment(&message.text, label, separator)
+                });
+
+            match pinned {
+                Some(message) => {
+                    let text = with_oncall_segment(&message.text, label, mentions, separator);
+                    if text != message.text {
+                        chat_update_text(slack_session, channel_id, &message.ts, &text)
+                            .await
+                            .map_err(|slack_err| {
+                                anyhow!("Unable to update pinned message: {}", slack_err)
+                            })?;
+                    }
+                }
+                None => {
+                    let posted = slack_session
+                        .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                            channel_id.to_owned(),
+                            SlackMessageContent::new()
+                                .with_text(with_oncall_segment("", label, mentions, separator)),
+                        ))
+                        .await
+                        .map_err(|slack_err| {
+                            anyhow!("Unable to post oncall message: {}", slack_err)
+                        })?;
+
+                    pins_add(slack_session, channel_id, &posted.ts)
+                        .await
+                        .map_err(|slack_err| {
+                            anyhow!("Unable to pin oncall message: {}", slack_err)
+                        })?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Message content for a Block Kit action, with the first text of the layout as the notification text.
+/// Invalid layouts are logged and result in `None`
+fn block_kit_content(
+    blocks_json: &str,
+    template_vars: &HashMap<String, String>,
+) -> Option<SlackMessageContent> {
+    match render_block_kit_template(blocks_json, template_vars) {
+        Ok(blocks) => Some(
+            SlackMessageContent::new()
+                .with_text(block_kit_fallback_text(&blocks))
+                .with_blocks(blocks),
+        ),
+        Err(e) => {
+            error!("Unable to render Block Kit layout: {}", e);
+            None
+        }
+    }
+}
+
+/// Message events don't carry template variables, fill in the ones every message has
+fn message_template_vars(context: &ActionContext) -> HashMap<String, String> {
+    let mut template_vars = context.template_vars.clone();
+    template_vars
+        .entry("channel_id".to_string())
+        .or_insert_with(|| context.channel_id.to_string());
+    template_vars
+        .entry("user_id".to_string())
+        .or_insert_with(|| context.sender.to_owned());
+    if let Some(message_ts) = &context.message_ts {
+        template_vars
+            .entry("message_ts".to_string())
+            .or_insert_with(|| message_ts.to_string());
+    }
+    template_vars
+}
+
+/// Mentions for whoever is oncall for an escalation policy or schedule (PagerDuty), schedule (Opsgenie) or rotation:
+/// the first oncall user, or everyone at `mention_level`. When none of them are in Slack, the configured
+/// `OncallFallback`s are tried in order. When they can't be reached in Slack, `unavailable_action` decides who gets tagged.
+/// Failures are logged and result in `None`
+async fn find_oncall_mentions(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    target: &OncallTarget,
+    mention_level: Option<u8>,
+    unavailable_action: OncallUnavailableAction,
+) -> Option<String> {
+    let fallback = &get_or_init_app_config().await.oncall_fallback;
+    let oncall_cache = get_or_init_oncall_cache().await;
+    let oncall_users = fetch_oncall_users(target).await?;
+
+    let to_mention = oncall_users_to_mention(&oncall_users, mention_level);
+    let tried_level = mention_level.or_else(|| to_mention.first().map(|u| u.escalation_level));
+    let slack_user_ids = find_slack_users(slack_session, &to_mention, fallback, oncall_cache).await;
+    if !slack_user_ids.is_empty() {
+        if unavailable_action == OncallUnavailableAction::TagAnyway {
+            return Some(format_user_mentions(&slack_user_ids));
+        }
+
+        let next_users = tried_level
+            .and_then(|level| next_escalation_level(&oncall_users, level))
+            .map(|next_level| oncall_users_to_mention(&oncall_users, Some(next_level)))
+            .unwrap_or_default();
+        return Some(
+            mentions_for_available_users(
+                slack_session,
+                slack_user_ids,
+                &next_users,
+                unavailable_action,
+                fallback,
+                oncall_cache,
+            )
+            .await,
+        );
+    }
+
+    warn!(
+        "No Slack users found for the oncall of {} at level {:?}, trying fallbacks",
+        target, tried_level
+    );
+
+    if fallback.next_escalation_level {
+        if let Some(next_level) =
+            tried_level.and_then(|level| next_escalation_level(&oncall_users, level))
+        {
+            let next_users = oncall_users_to_mention(&oncall_users, Some(next_level));
+            let slack_user_ids =
+                find_slack_users(slack_session, &next_users, fallback, oncall_cache).await;
+            if !slack_user_ids.is_empty() {
+                return Some(format_user_mentions(&slack_user_ids));
+            }
+        }
+    }
+
+    if let Some(usergroup_id) = &fallback.usergroup_id {
+        return Some(format!("<!subteam^{usergroup_id}>"));
+    }
+
+    if fallback.name_and_profile && !to_mention.is_empty() {
+        return Some(
+            to_mention
+                .iter()
+                .map(|oncall_user| oncall_user.to_fallback_text())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+
+    error!(
+        "No fallback left for the oncall of {}, nobody will be tagged",
+        target
+    );
+    None
+}
+
+/// Oncall users from the provider (or the cache), failures are logged and result in `None`
+async fn fetch_oncall_users(target: &OncallTarget) -> Option<Vec<OncallUser>> {
+    let provider = target.provider();
+    let oncall_provider = match get_or_init_app_config().await.oncall_provider(provider) {
+        Some(oncall_provider) => oncall_provider,
+        None => {
+            error!(
+                "No {} credentials configured, unable to find oncall user",
+                provider.to_description()
+            );
+            return None;
+        }
+    };
+
+    let oncall_cache = get_or_init_oncall_cache().await;
+    // clears made through another instance
+    oncall_cache.sync_invalidations().await;
+
+    match oncall_cache.get_oncall_users(oncall_provider, target).await {
+        Ok(oncall_users) => Some(oncall_users),
+        Err(err) => {
+            error!("Error fetching oncalls for {} - {}", target, err);
+            None
+        }
+    }
+}
+
+/// Slack users for the first oncall user, or the next escalation level when they aren't in Slack
+/// and that fallback is enabled
+async fn find_oncall_slack_users(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    target: &OncallTarget,
+) -> Option<Vec<SlackUserId>> {
+    let fallback = &get_or_init_app_config().await.oncall_fallback;
+    let oncall_cache = get_or_init_oncall_cache().await;
+    let oncall_users = fetch_oncall_users(target).await?;
+
+    let first_oncall = oncall_users_to_mention(&oncall_users, None);
+    let slack_user_ids =
+        find_slack_users(slack_session, &first_oncall, fallback, oncall_cache).await;
+    if !slack_user_ids.is_empty() || !fallback.next_escalation_level {
+        return Some(slack_user_ids);
+    }
+
+    let next_users = first_oncall
+        .first()
+        .and_then(|first| next_escalation_level(&oncall_users, first.escalation_level))
+        .map(|next_level| oncall_users_to_mention(&oncall_users, Some(next_level)))
+        .unwrap_or_default();
+    Some(find_slack_users(slack_session, &next_users, fallback, oncall_cache).await)
+}
+
+/// Invite each user on their own so one failure doesn't stop the others, and report every outcome
+async fn invite_to_channel(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel_id: &SlackChannelId,
+    slack_user_ids: &[SlackUserId],
+) -> String {
+    let mut report_lines = Vec::new();
+    for slack_user_id in slack_user_ids {
+        let outcome = ChannelInviteOutcome::from_result(
+            conversations_invite(slack_session, channel_id, slack_user_id).await,
+        );
+        if let ChannelInviteOutcome::Failed(reason) = &outcome {
+            warn!("Unable to invite {slack_user_id} to {channel_id} - {reason}");
+        }
+        report_lines.push(outcome.to_report_line(slack_user_id));
+    }
+    report_lines.join("\n")
+}
+
+/// Mentions after checking whether the oncall Slack users can be reached right now.
+/// `next_users` are only looked up when escalating because nobody at the tagged level is available
+async fn mentions_for_available_users(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    slack_user_ids: Vec<SlackUserId>,
+    next_users: &[&OncallUser],
+    unavailable_action: OncallUnavailableAction,
+    fallback: &OncallFallback,
+    oncall_cache: &OncallCache,
+) -> String {
+    let mut available = Vec::new();
+    let mut unavailable = Vec::new();
+    for slack_user_id in slack_user_ids {
+        match find_slack_unavailability(slack_session, &slack_user_id).await {
+            Some(reason) => unavailable.push((slack_user_id, reason)),
+            None => available.push(slack_user_id),
+        }
+    }
+
+    if unavailable.is_empty() {
+        return format_user_mentions(&available);
+    }
+
+    let note = unavailable_note(&unavailable);
+    let unavailable_ids = unavailable
+        .into_iter()
+        .map(|(slack_user_id, _)| slack_user_id)
+        .collect::<Vec<_>>();
+
+    match unavailable_action {
+        OncallUnavailableAction::Escalate if !available.is_empty() => {
+            format!("{} ({note})", format_user_mentions(&available))
+        }
+        OncallUnavailableAction::Escalate => {
+            let next_ids =
+                find_slack_users(slack_session, next_users, fallback, oncall_cache).await;
+            if next_ids.is_empty() {
+                warn!("No next escalation level to escalate to, tagging unavailable oncall users");
+                format!("{} ({note})", format_user_mentions(&unavailable_ids))
+            } else {
+                format!(
+                    "{} ({note}, escalated to the next level)",
+                    format_user_mentions(&next_ids)
+                )
+            }
+        }
+        OncallUnavailableAction::PostNote | OncallUnavailableAction::TagAnyway => {
+            let all_ids = [available, unavailable_ids].concat();
+            format!("{} ({note})", format_user_mentions(&all_ids))
+        }
+    }
+}
+
+/// Why a Slack user won't see a mention soon, `None` when they're available or Slack couldn't be asked
+async fn find_slack_unavailability(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    slack_user_id: &SlackUserId,
+) -> Option<SlackUnavailability> {
+    match users_info_status(slack_session, slack_user_id).await {
+        Ok(info) if info.user.deleted => return Some(SlackUnavailability::Deactivated),
+        Ok(_) => (),
+        Err(slack_err) => warn!("Unable to get Slack status of {slack_user_id} - {slack_err}"),
+    }
+
+    match dnd_info(slack_session, slack_user_id).await {
+        Ok(dnd) if dnd.is_active_at(Utc::now().timestamp()) => {
+            return Some(SlackUnavailability::DoNotDisturb)
+        }
+        Ok(_) => (),
+        Err(slack_err) => warn!("Unable to get Do Not Disturb of {slack_user_id} - {slack_err}"),
+    }
+
+    match users_get_presence(slack_session, slack_user_id).await {
+        Ok(presence) if presence.presence == "away" => Some(SlackUnavailability::Away),
+        Ok(_) => None,
+        Err(slack_err) => {
+            warn!("Unable to get presence of {slack_user_id} - {slack_err}");
+            None
+        }
+    }
+}
+
+/// Slack users matching the oncall users, users that can't be found are skipped
+async fn find_slack_users(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    oncall_users: &[&OncallUser],
+    fallback: &OncallFallback,
+    oncall_cache: &OncallCache,
+) -> Vec<SlackUserId> {
+    let mut slack_user_ids = Vec::new();
+    for oncall_user in oncall_users {
+        if let Some(slack_user_id) =
+            find_slack_user(slack_session, oncall_user, fallback, oncall_cache).await
+        {
+            if !slack_user_ids.contains(&slack_user_id) {
+                slack_user_ids.push(slack_user_id)
+            }
+        }
+    }
+    slack_user_ids
+}
+
+fn format_user_mentions(slack_user_ids: &[SlackUserId]) -> String {
+    slack_user_ids
+        .iter()
+        .map(|slack_user_id| format!("<@{slack_user_id}>"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Slack user matching an oncall user, by the provider's Slack id or by email (and its rewritten domain)
+async fn find_slack_user(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    oncall_user: &OncallUser,
+    fallback: &OncallFallback,
+    oncall_cache: &OncallCache,
+) -> Option<SlackUserId> {
+    if let Some(slack_user_id) = &oncall_user.slack_user_id {
+        return Some(slack_user_id.clone().into());
+    }
+
+    let provider_email = oncall_user.email.as_ref()?;
+    if let Some(slack_user_id) = oncall_cache.get_slack_user(provider_email) {
+        return Some(slack_user_id.into());
+    }
+
+    for email in fallback.candidate_emails(provider_email) {
+        match slack_session
+            .users_lookup_by_email(&SlackApiUsersLookupByEmailRequest::new(
+                email.clone().into(),
+            ))
+            .await
+        {
+            Ok(slack_profile) => {
+                oncall_cache.insert_slack_user(provider_email, slack_profile.user.id.to_string());
+                return Some(slack_profile.user.id);
+            }
+            Err(slack_err) => {
+                warn!("Unable to get slack profile for oncall user {email} - {slack_err}")
+            }
+        }
+    }
+    None
+}
+
+/// Link to the triggering message, failures are logged and result in `None`
+async fn message_permalink(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    context: &ActionContext,
+    message_ts: &SlackTs,
+) -> Option<String> {
+    match slack_session
+        .chat_get_permalink(&SlackApiChatGetPermalinkRequest::new(
+            context.channel_id.to_owned(),
+            message_ts.to_owned(),
+        ))
+        .await
+    {
+        Ok(permalink_resp) => Some(permalink_resp.permalink.to_string()),
+        Err(slack_err) => {
+            error!("Failed to get permalink for message: {}", slack_err);
+            None
+        }
+    }
+}
+
+/// Send the alert and return the text to post in thread.
+/// PagerDuty creates the incident asynchronously, it is linked in the thread
+/// when its `incident.triggered` webhook arrives, see `pagerduty::webhook`
+async fn trigger_pagerduty_incident(
+    trigger: &PagerDutyIncidentTrigger,
+    context: &ActionContext,
+    message_ts: &SlackTs,
+    permalink: Option<String>,
+) -> Result<String> {
+    let config = get_or_init_app_config().await;
+    let dedup_key =
+        PagerDutyEvents::message_dedup_key(context.channel_id.as_ref(), message_ts.as_ref());
+
+    let summary: String = render_template(&trigger.summary, &message_template_vars(context))
+        .chars()
+        .take(PAGERDUTY_MAX_SUMMARY_LEN)
+        .collect();
+
+    let event = EventV2 {
+        routing_key: trigger.routing_key.to_owned(),
+        event_action: "trigger".to_string(),
+        dedup_key: Some(dedup_key.clone()),
+        payload: Some(EventPayload {
+            summary,
+            source: format!("Slack channel {}", context.channel_id),
+            severity: trigger.severity.to_string(),
+            custom_details: Some(serde_json::json!({ "reported_by": context.sender })),
+        }),
+        links: permalink
+            .into_iter()
+            .map(|href| EventLink {
+                href,
+                text: "Slack message".to_string(),
+            })
+            .collect(),
+    };
+
+    let event_response = config.pagerduty_events.send_event(&event).await?;
+    let dedup_key = event_response.dedup_key.unwrap_or(dedup_key);
+
+    Ok(format!(
+        ":rotating_light: Triggered a PagerDuty alert (dedup key `{dedup_key}`), the incident is linked here once PagerDuty opens it"
+    ))
+}
+
+async fn run_command_action(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    command_action: &CommandAction,
+    context: &ActionContext,
+) {
+    match command_action {
+        // sent in the slash command's response body instead, see `command_ephemeral_replies`
+        CommandAction::EphemeralReply(_) => (),
+        CommandAction::ChannelReply(msg) => {
+            if let Err(slack_err) = slack_session
+                .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                    context.channel_id.to_owned(),
+                    SlackMessageContent::new()
+                        .with_text(render_template(msg, &context.template_vars)),
+                ))
+                .await
+            {
+                error!("Unable to reply to command: {}", slack_err);
+            }
+        }
+    }
+}
+
+async fn run_member_action(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    member_action: &MemberAction,
+    context: &ActionContext,
+) {
+    match member_action {
+        MemberAction::EphemeralWelcome(msg) => {
+            if let Err(slack_err) = slack_session
+                .chat_post_ephemeral(&SlackApiChatPostEphemeralRequest::new(
+                    context.channel_id.to_owned(),
+                    context.sender.to_owned().into(),
+                    SlackMessageContent::new()
+                        .with_text(render_template(msg, &context.template_vars)),
+                ))
+                .await
+            {
+                error!("Unable to welcome user in channel: {}", slack_err);
+            }
+        }
+        MemberAction::DirectMessageWelcome(msg) => {
+            // posting to a user id opens a DM between the bot and the user
+            if let Err(slack_err) = slack_session
+                .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                    context.sender.to_owned().into(),
+                    SlackMessageContent::new()
+                        .with_text(render_template(msg, &context.template_vars)),
+                ))
+                .await
+            {
+                error!("Unable to welcome user with a DM: {}", slack_err);
+            }
+        }
+    }
+}
+
+async fn run_message_action(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    message_action: &MessageAction,
+    context: &ActionContext,
+) {
+    let message_ts = match &context.message_ts {
+        Some(message_ts) => message_ts,
+        None => {
+            error!(
+                "Message actions require a triggering message: {:?}",
+                message_action
+            );
+            return;
+        }
+    };
+
+    match message_action {
+        MessageAction::AttachEmoji(name) => {
+            if let Err(slack_err) = reactions_add(
+                slack_session,
+                context.channel_id.as_ref(),
+                message_ts.as_ref(),
+                name,
+            )
+            .await
+            {
+                error!("{}", slack_err);
+            }
+        }
+        MessageAction::ThreadedMessage(msg) => {
+            if let Err(slack_err) = slack_session
+                .chat_post_message(
+                    &SlackApiChatPostMessageRequest::new(
+                        context.channel_id.to_owned(),
+                        SlackMessageContent::new().with_text(msg.to_owned()),
+                    )
+                    .with_thread_ts(message_ts.to_owned()),
+                )
+                .await
+            {
+                error!("{}", slack_err);
+            }
+        }
+        MessageAction::ChannelMessage(msg) => {
+            if let Err(slack_err) = slack_session
+                .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                    context.channel_id.to_owned(),
+                    SlackMessageContent::new().with_text(msg.to_owned()),
+                ))
+                .await
+            {
+                error!("{}", slack_err);
+            }
+        }
+        MessageAction::EphemeralToSender(msg) => {
+            if let Err(slack_err) = slack_session
+                .chat_post_ephemeral(&SlackApiChatPostEphemeralRequest::new(
+                    context.channel_id.to_owned(),
+                    context.sender.to_owned().into(),
+                    SlackMessageContent::new()
+                        .with_text(render_template(msg, &message_template_vars(context))),
+                ))
+                .await
+            {
+                error!("Unable to reply to sender: {}", slack_err);
+            }
+        }
+        MessageAction::DirectMessageToSender(msg) => {
+            // posting to a user id opens a DM between the bot and the user
+            if let Err(slack_err) = slack_session
+                .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                    context.sender.to_owned().into(),
+                    SlackMessageContent::new()
+                        .with_text(render_template(msg, &message_template_vars(context))),
+                ))
+                .await
+            {
+                error!("Unable to DM sender: {}", slack_err);
+            }
+        }
+        MessageAction::ThreadedBlocks(blocks_json) => {
+            if let Some(content) = block_kit_content(blocks_json, &message_template_vars(context)) {
+                if let Err(slack_err) = slack_session
+                    .chat_post_message(
+                        &SlackApiChatPostMessageRequest::new(
+                            context.channel_id.to_owned(),
+                            content,
+                        )
+                        .with_thread_ts(message_ts.to_owned()),
+                    )
+                    .await
+                {
+                    error!("{}", slack_err);
+                }
+            }
+        }
+        MessageAction::ChannelBlocks(blocks_json) => {
+            if let Some(content) = block_kit_content(blocks_json, &message_template_vars(context)) {
+                if let Err(slack_err) = slack_session
+                    .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                        context.channel_id.to_owned(),
+                        content,
+                    ))
+                    .await
+                {
+                    error!("{}", slack_err);
+                }
+            }
+        }
+        MessageAction::MsgOncallInThread {
+            target,
+            message,
+            mention_level,
+            unavailable_action,
+            ..
+        } => {
+            if let Some(mentions) =
+                find_oncall_mentions(slack_session, target, *mention_level, *unavailable_action)
+                    .await
+            {
+                if let Err(slack_err) = slack_session
+                    .chat_post_message(
+                        &SlackApiChatPostMessageRequest::new(
+                            context.channel_id.to_owned(),
+                            SlackMessageContent::new().with_text(format!("{mentions} - {message}")),
+                        )
+                        .with_thread_ts(message_ts.to_owned()),
+                    )
+                    .await
+                {
+                    error!("Error posting to thread: {}", slack_err)
+                }
+            }
+        }
+        MessageAction::InviteOncallToChannel {
+            target,
+            usergroup_id,
+            ..
+        } => {
+            let invitees = match usergroup_id {
+                Some(usergroup_id) => {
+                    match usergroups_users_list(slack_session, usergroup_id).await {
+                        Ok(usergroup) => Some(usergroup.users),
+                        Err(slack_err) => {
+                            error!(
+                                "Unable to list members of user group {usergroup_id} - {slack_err}"
+                            );
+                            None
+                        }
+                    }
+                }
+                None => find_oncall_slack_users(slack_session, target).await,
+            };
+
+            let report = match invitees {
+                Some(invitees) if !invitees.is_empty() => {
+                    invite_to_channel(slack_session, &context.channel_id, &invitees).await
+                }
+                Some(_) => {
+                    "Nobody to invite, the oncall or user group has no Slack users".to_string()
+                }
+                None => "Unable to find who to invite to this channel".to_string(),
+            };
+
+            if let Err(slack_err) = slack_session
+                .chat_post_message(
+                    &SlackApiChatPostMessageRequest::new(
+                        context.channel_id.to_owned(),
+                        SlackMessageContent::new().with_text(report),
+                    )
+                    .with_thread_ts(message_ts.to_owned()),
+                )
+                .await
+            {
+                error!("Unable to post invite report to thread: {}", slack_err);
+            }
+        }
+        MessageAction::Webhook(webhook) => {
+            let http_client = &get_or_init_app_config().await.http_client;
+
+            match send_webhook(http_client, webhook, &message_template_vars(context)).await {
+                Ok(Some(reply)) if webhook.reply_in_thread => {
+                    if let Err(slack_err) = slack_session
+                        .chat_post_message(
+                            &SlackApiChatPostMessageRequest::new(
+                                context.channel_id.to_owned(),
+                                SlackMessageContent::new().with_text(reply),
+                            )
+                            .with_thread_ts(message_ts.to_owned()),
+                        )
+                        .await
+                    {
+                        error!("Unable to post webhook response to thread: {}", slack_err);
+                    }
+                }
+                Ok(_) => (),
+                Err(e) => error!("{}", e),
+            }
+        }
+        MessageAction::TriggerWorkflow(trigger) => {
+            let webhook = match trigger.to_webhook_request() {
+                Ok(webhook) => webhook,
+                Err(e) => {
+                    error!("Invalid workflow trigger: {}", e);
+                    return;
+                }
+            };
+
+            let mut template_vars = message_template_vars(context);
+            if let Some(permalink) = message_permalink(slack_session, context, message_ts).await {
+                template_vars.insert("permalink".to_string(), permalink);
+            }
+
+            let http_client = &get_or_init_app_config().await.http_client;
+            if let Err(e) = send_webhook(http_client, &webhook, &template_vars).await {
+                error!("Unable to start Slack Workflow: {}", e);
+            }
+        }
+        MessageAction::TriggerPagerDutyIncident(trigger) => {
+            let permalink = message_permalink(slack_session, context, message_ts).await;
+
+            let reply =
+                match trigger_pagerduty_incident(trigger, context, message_ts, permalink).await {
+                    Ok(reply) => reply,
+                    Err(e) => {
+                        error!("Unable to trigger PagerDuty incident: {}", e);
+                        format!(":warning: Unable to trigger a PagerDuty incident: {e}")
+                    }
+                };
+
+            if let Err(slack_err) = slack_session
+                .chat_post_message(
+                    &SlackApiChatPostMessageRequest::new(
+                        context.channel_id.to_owned(),
+                        SlackMessageContent::new().with_text(reply),
+                    )
+                    .with_thread_ts(message_ts.to_owned()),
+                )
+                .await
+            {
+                error!("Unable to post PagerDuty incident to thread: {}", slack_err);
+            }
+        }
+        MessageAction::ForwardMessageToChannel {
+            channel,
+            msg_context,
+        } => {
+            match slack_session
+                .chat_get_permalink(&SlackApiChatGetPermalinkRequest::new(
+                    context.channel_id.to_owned(),
+                    message_ts.to_owned(),
+                ))
+                .await
+            {
+                Ok(permalink_resp) => {
+                    let permalink = permalink_resp.permalink;
+                    if let Err(slack_err) = slack_session
+                        .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                            channel.into(),
+                            SlackMessageContent::new().with_text(format_forwarded_message(
+                                context.channel_id.as_ref(),
+                                &context.sender,
+                                &permalink.to_string(),
+                                msg_context,
+                            )),
+                        ))
+                        .await
+                    {
+                        error!("Failed to forward message {}", slack_err);
+                    }
+                }
+                Err(slack_err) => {
+                    error!("Failed to get permalink to forward message: {}", slack_err)
+                }
+            };
+        }
+    }
+}
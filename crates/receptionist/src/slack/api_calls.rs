@@ -1,9 +1,9 @@
 /// These should be merged upstream to slack-morphism if possible
 ///
 use serde::{Deserialize, Serialize};
-use slack_morphism::{ClientResult, SlackClientSession};
+use slack_morphism::{errors::SlackClientError, ClientResult, SlackClientSession};
 use slack_morphism_hyper::SlackClientHyperHttpsConnector;
-use slack_morphism_models::{SlackChannelId, SlackTs};
+use slack_morphism_models::{SlackChannelId, SlackTs, SlackUserId};
 
 pub async fn reactions_add(
     slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
@@ -36,3 +36,490 @@ pub struct SlackApiReactionsAddRequest {
 // #[skip_serializing_none]
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct SlackApiReactionsAddResponse {}
+
+pub async fn dnd_info(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    user: &SlackUserId,
+) -> ClientResult<SlackApiDndInfoResponse> {
+    slack_session
+        .http_session_api
+        .http_get("dnd.info", &vec![("user", Some(&user.to_string()))], None)
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiDndInfoResponse {
+    /// the user has a Do Not Disturb schedule, not that it is currently on
+    #[serde(default)]
+    pub dnd_enabled: bool,
+    pub next_dnd_start_ts: Option<i64>,
+    pub next_dnd_end_ts: Option<i64>,
+    /// snoozed by hand, on top of the schedule
+    #[serde(default)]
+    pub snooze_enabled: bool,
+    pub snooze_endtime: Option<i64>,
+}
+
+impl SlackApiDndInfoResponse {
+    /// Whether notifications are paused at `now` (unix seconds), by a snooze or the DND schedule
+    pub fn is_active_at(&self, now: i64) -> bool {
+        let snoozed = self.snooze_enabled && self.snooze_endtime.map_or(true, |end| now < end);
+        let scheduled = self.dnd_enabled
+            && matches!(
+                (self.next_dnd_start_ts, self.next_dnd_end_ts),
+                (Some(start), Some(end)) if start <= now && now < end
+            );
+        snoozed || scheduled
+    }
+}
+
+pub async fn users_get_presence(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    user: &SlackUserId,
+) -> ClientResult<SlackApiUsersGetPresenceResponse> {
+    slack_session
+        .http_session_api
+        .http_get(
+            "users.getPresence",
+            &vec![("user", Some(&user.to_string()))],
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiUsersGetPresenceResponse {
+    /// `active` or `away`
+    pub presence: String,
+}
+
+/// Only the fields of `users.info` needed to tell whether a user was deactivated
+pub async fn users_info_status(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    user: &SlackUserId,
+) -> ClientResult<SlackApiUsersInfoStatusResponse> {
+    slack_session
+        .http_session_api
+        .http_get("users.info", &vec![("user", Some(&user.to_string()))], None)
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiUsersInfoStatusResponse {
+    pub user: SlackUserStatus,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackUserStatus {
+    pub id: SlackUserId,
+    /// deactivated users stay in `users.info` with this flag set
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+/// Only the topic out of `conversations.info`
+pub async fn conversations_topic(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+) -> ClientResult<SlackApiConversationsTopicResponse> {
+    slack_session
+        .http_session_api
+        .http_get(
+            "conversations.info",
+            &vec![("channel", Some(&channel.to_string()))],
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiConversationsTopicResponse {
+    pub channel: SlackChannelTopic,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackChannelTopic {
+    pub id: SlackChannelId,
+    /// missing for DMs
+    pub topic: Option<SlackTopicValue>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackTopicValue {
+    pub value: String,
+}
+
+pub async fn conversations_set_topic(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+    topic: &str,
+) -> ClientResult<SlackApiConversationsSetTopicResponse> {
+    slack_session
+        .http_session_api
+        .http_post(
+            "conversations.setTopic",
+            &SlackApiConversationsSetTopicRequest {
+                channel: channel.to_owned(),
+                topic: topic.to_owned(),
+            },
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiConversationsSetTopicRequest {
+    pub channel: SlackChannelId,
+    pub topic: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiConversationsSetTopicResponse {}
+
+pub async fn pins_list(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+) -> ClientResult<SlackApiPinsListResponse> {
+    slack_session
+        .http_session_api
+        .http_get(
+            "pins.list",
+            &vec![("channel", Some(&channel.to_string()))],
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiPinsListResponse {
+    #[serde(default)]
+    pub items: Vec<SlackPinnedItem>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackPinnedItem {
+    /// `None` for pinned files
+    pub message: Option<SlackPinnedMessage>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackPinnedMessage {
+    pub ts: SlackTs,
+    /// bot messages are posted as the bot's user
+    pub user: Option<SlackUserId>,
+    #[serde(default)]
+    pub text: String,
+}
+
+pub async fn pins_add(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+    timestamp: &SlackTs,
+) -> ClientResult<SlackApiPinsAddResponse> {
+    slack_session
+        .http_session_api
+        .http_post(
+            "pins.add",
+            &SlackApiPinsAddRequest {
+                channel: channel.to_owned(),
+                timestamp: timestamp.to_owned(),
+            },
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiPinsAddRequest {
+    pub channel: SlackChannelId,
+    pub timestamp: SlackTs,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiPinsAddResponse {}
+
+/// Replace the text of a message the bot posted
+pub async fn chat_update_text(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+    ts: &SlackTs,
+    text: &str,
+) -> ClientResult<SlackApiChatUpdateTextResponse> {
+    slack_session
+        .http_session_api
+        .http_post(
+            "chat.update",
+            &SlackApiChatUpdateTextRequest {
+                channel: channel.to_owned(),
+                ts: ts.to_owned(),
+                text: text.to_owned(),
+            },
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiChatUpdateTextRequest {
+    pub channel: SlackChannelId,
+    pub ts: SlackTs,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiChatUpdateTextResponse {}
+
+/// The bot's own user id, to recognise the messages it pinned
+pub async fn auth_test_user_id(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+) -> ClientResult<SlackApiAuthTestUserResponse> {
+    slack_session
+        .http_session_api
+        .http_post("auth.test", &SlackApiAuthTestUserRequest {}, None)
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiAuthTestUserRequest {}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiAuthTestUserResponse {
+    pub user_id: SlackUserId,
+}
+
+/// `thread_ts` of a message, `conversations.replies` lists the thread's parent first
+pub async fn message_thread_ts(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+    ts: &SlackTs,
+) -> ClientResult<Option<SlackTs>> {
+    let replies: SlackApiConversationsRepliesResponse = slack_session
+        .http_session_api
+        .http_get(
+            "conversations.replies",
+            &vec![
+                ("channel", Some(&channel.to_string())),
+                ("ts", Some(&ts.to_string())),
+                ("limit", Some(&"1".to_string())),
+            ],
+            None,
+        )
+        .await?;
+
+    Ok(replies
+        .messages
+        .into_iter()
+        .next()
+        .and_then(|message| message.thread_ts))
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiConversationsRepliesResponse {
+    #[serde(default)]
+    pub messages: Vec<SlackThreadMessage>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackThreadMessage {
+    pub ts: SlackTs,
+    /// missing for messages without replies
+    pub thread_ts: Option<SlackTs>,
+    /// missing for bot messages
+    pub user: Option<SlackUserId>,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// A single message by its ts, thread replies included.
+/// `conversations.replies` lists the thread's parent first, so the parent is skipped when looking up a reply
+pub async fn conversations_message(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+    ts: &SlackTs,
+) -> ClientResult<Option<SlackThreadMessage>> {
+    let replies: SlackApiConversationsRepliesResponse = slack_session
+        .http_session_api
+        .http_get(
+            "conversations.replies",
+            &vec![
+                ("channel", Some(&channel.to_string())),
+                ("ts", Some(&ts.to_string())),
+                ("oldest", Some(&ts.to_string())),
+                ("latest", Some(&ts.to_string())),
+                ("inclusive", Some(&"true".to_string())),
+            ],
+            None,
+        )
+        .await?;
+
+    Ok(replies
+        .messages
+        .into_iter()
+        .find(|message| &message.ts == ts))
+}
+
+pub async fn usergroups_users_list(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    usergroup: &str,
+) -> ClientResult<SlackApiUsergroupsUsersListResponse> {
+    slack_session
+        .http_session_api
+        .http_get(
+            "usergroups.users.list",
+            &vec![("usergroup", Some(&usergroup.to_owned()))],
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiUsergroupsUsersListResponse {
+    #[serde(default)]
+    pub users: Vec<SlackUserId>,
+}
+
+pub async fn conversations_invite(
+    slack_session: &SlackClientSession<'_, SlackClientHyperHttpsConnector>,
+    channel: &SlackChannelId,
+    user: &SlackUserId,
+) -> ClientResult<SlackApiConversationsInviteResponse> {
+    slack_session
+        .http_session_api
+        .http_post(
+            "conversations.invite",
+            &SlackApiConversationsInviteRequest {
+                channel: channel.to_owned(),
+                users: user.to_string(),
+            },
+            None,
+        )
+        .await
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiConversationsInviteRequest {
+    pub channel: SlackChannelId,
+    /// comma separated user ids
+    pub users: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlackApiConversationsInviteResponse {}
+
+/// How inviting one user to a channel went
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChannelInviteOutcome {
+    Invited,
+    AlreadyInChannel,
+    /// private channels can only be invited to by members, and the bot isn't one
+    BotNotInChannel,
+    /// Slack's error code, ex: `user_is_restricted`
+    Failed(String),
+}
+
+impl ChannelInviteOutcome {
+    pub fn from_result(result: ClientResult<SlackApiConversationsInviteResponse>) -> Self {
+        match result {
+            Ok(_) => ChannelInviteOutcome::Invited,
+            Err(SlackClientError::ApiError(api_err)) => match api_err.code.as_str() {
+                "already_in_channel" => ChannelInviteOutcome::AlreadyInChannel,
+                "not_in_channel" | "channel_not_found" => ChannelInviteOutcome::BotNotInChannel,
+                code => ChannelInviteOutcome::Failed(code.to_owned()),
+            },
+            Err(slack_err) => ChannelInviteOutcome::Failed(slack_err.to_string()),
+        }
+    }
+
+    /// One line of the report posted in thread
+    pub fn to_report_line(&self, user: &SlackUserId) -> String {
+        match self {
+            ChannelInviteOutcome::Invited => format!("Invited <@{user}> to this channel"),
+            ChannelInviteOutcome::AlreadyInChannel => {
+                format!("<@{user}> is already in this channel")
+            }
+            ChannelInviteOutcome::BotNotInChannel => {
+                format!("Unable to invite <@{user}>, add me to this private channel first")
+            }
+            ChannelInviteOutcome::Failed(reason) => {
+                format!("Unable to invite <@{user}> - {reason}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use slack_morphism::errors::SlackClientApiError;
+
+    #[test]
+    fn should_only_treat_current_dnd_as_active() {
+        let dnd: SlackApiDndInfoResponse = serde_json::from_value(json!({
+            "ok": true,
+            "dnd_enabled": true,
+            "next_dnd_start_ts": 1000,
+            "next_dnd_end_ts": 2000,
+            "snooze_enabled": false
+        }))
+        .unwrap();
+
+        assert!(!dnd.is_active_at(999));
+        assert!(dnd.is_active_at(1500));
+        assert!(!dnd.is_active_at(2000));
+
+        let snoozed = SlackApiDndInfoResponse {
+            snooze_enabled: true,
+            snooze_endtime: Some(3000),
+            ..dnd
+        };
+        assert!(snoozed.is_active_at(2500));
+        assert!(!snoozed.is_active_at(3000));
+    }
+
+    #[test]
+    fn should_report_channel_invite_outcomes() {
+        let api_error = |code: &str| {
+            Err(SlackClientError::ApiError(SlackClientApiError::new(
+                code.to_string(),
+            )))
+        };
+        let user: SlackUserId = "U1".into();
+
+        assert_eq!(
+            ChannelInviteOutcome::from_result(Ok(SlackApiConversationsInviteResponse {}))
+                .to_report_line(&user),
+            "Invited <@U1> to this channel"
+        );
+        assert_eq!(
+            ChannelInviteOutcome::from_result(api_error("already_in_channel")),
+            ChannelInviteOutcome::AlreadyInChannel
+        );
+        assert_eq!(
+            ChannelInviteOutcome::from_result(api_error("channel_not_found")),
+            ChannelInviteOutcome::BotNotInChannel
+        );
+        assert_eq!(
+            ChannelInviteOutcome::from_result(api_error("user_is_restricted"))
+                .to_report_line(&user),
+            "Unable to invite <@U1> - user_is_restricted"
+        );
+    }
+
+    #[test]
+    fn should_parse_pinned_messages_and_files() {
+        let pins: SlackApiPinsListResponse = serde_json::from_value(json!({
+            "ok": true,
+            "items": [
+                {"type": "file", "file": {"id": "F1"}},
+                {"type": "message", "message": {"ts": "1.2", "user": "UBOT", "text": "oncall: <@U1>"}}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(pins.items[0].message, None);
+        let message = pins.items[1].message.as_ref().unwrap();
+        assert_eq!(message.user, Some("UBOT".into()));
+        assert_eq!(message.text, "oncall: <@U1>");
+    }
+}
@@ -1,15 +1,35 @@
 use super::SlackStateWorkaround;
-use crate::manager_ui::{new_manager_view, ManagerViewModes, MetaForManagerView};
+#[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+use crate::database::{
+    get_responses_for_collaborator, get_responses_for_listener, get_rotation_by_name,
+};
+use crate::{
+    get_or_init_oncall_cache,
+    manager_ui::{ManagerViewModes, MetaForManagerView},
+    render_template, run_response_actions, ActionContext, CommandAction, ReceptionistAction,
+    ReceptionistListener, ReceptionistResponse, Rotation,
+};
 use axum::{
     extract::{Extension, Form},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
-use serde_json::{to_value, Value};
+use chrono::Utc;
+use serde_json::{json, to_value, Value};
 use slack_morphism::prelude::*;
-use std::sync::Arc;
-use tracing::error;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{error, info};
+
+/// Slash command that dispatches to Responses by keyword: `/rec-cmd <keyword> [args]`
+pub const KEYWORD_COMMAND: &str = "/rec-cmd";
+
+/// Built-in keyword answering who is oncall for a Receptionist rotation: `/rec-cmd oncall <rotation>`
+pub const ROTATION_ONCALL_KEYWORD: &str = "oncall";
+
+/// Built-in keyword to see and clear cached oncall lookups: `/rec-cmd oncall-cache [clear [target]]`.
+/// Clearing is limited to Response collaborators and workspace admins
+pub const ONCALL_CACHE_KEYWORD: &str = "oncall-cache";
 
 pub async fn axum_handler_handle_slack_commands_api(
     Extension(slack_state): Extension<Arc<SlackStateWorkaround>>,
@@ -23,18 +43,15 @@ pub async fn handle_slack_command(
     slack_state: &SlackStateWorkaround,
     payload: SlackCommandEvent,
 ) -> (StatusCode, Value) {
-    let view = new_manager_view(&MetaForManagerView::new(
-        ManagerViewModes::Home,
-        payload.user_id.to_string(),
-    ))
-    .await;
+    if payload.command.as_ref() == KEYWORD_COMMAND {
+        return handle_keyword_command(slack_state, payload).await;
+    }
 
     if let Err(message) = slack_state
-        .open_session()
-        .views_open(&SlackApiViewsOpenRequest {
-            trigger_id: payload.trigger_id,
-            view,
-        })
+        .open_manager_modal_view(
+            payload.trigger_id,
+            &MetaForManagerView::new(ManagerViewModes::Home, payload.user_id.to_string()),
+        )
         .await
     {
         error!("{}", message);
@@ -42,3 +59,293 @@ pub async fn handle_slack_command(
 
     (StatusCode::OK, to_value("test").unwrap())
 }
+
+/// Run the actions of every Response listening for the command's keyword
+async fn handle_keyword_command(
+    slack_state: &SlackStateWorkaround,
+    payload: SlackCommandEvent,
+) -> (StatusCode, Value) {
+    let command_text = payload.text.clone().unwrap_or_default();
+
+    let (keyword, args) = match parse_command_keyword(&command_text) {
+        Some(keyword_and_args) => keyword_and_args,
+        None => {
+            return (
+                StatusCode::OK,
+                ephemeral_command_reply(&format!("Usage: `{KEYWORD_COMMAND} <keyword> [args]`")),
+            )
+        }
+    };
+
+    if keyword == ONCALL_CACHE_KEYWORD {
+        return (
+            StatusCode::OK,
+            oncall_cache_reply(slack_state, &payload.user_id, &args).await,
+        );
+    }
+
+    // rotations take priority, other args fall through to Responses using the keyword
+    if keyword == ROTATION_ONCALL_KEYWORD && !args.is_empty() {
+        match get_rotation_by_name(&args).await {
+            Ok(Some(rotation)) => return (StatusCode::OK, rotation_oncall_reply(&rotation)),
+            Ok(None) => (),
+            Err(e) => error!("unable to get rotation {args}: {e}"),
+        }
+    }
+
+    let responses_for_keyword =
+        match get_responses_for_listener(ReceptionistListener::SlackCommandKeyword {
+            command: KEYWORD_COMMAND.to_string(),
+            keyword: keyword.to_owned(),
+        })
+        .await
+        {
+            Ok(responses) => responses,
+            Err(e) => {
+                error!("unable to get responses for command keyword {keyword}: {e}");
+                return (
+                    StatusCode::OK,
+                    ephemeral_command_reply("Something went wrong, please try again"),
+                );
+            }
+        };
+
+    let matching_responses: Vec<_> = responses_for_keyword
+        .iter()
+        .filter(|r| r.check_for_match(&args))
+        .collect();
+
+    if matching_responses.is_empty() {
+        return (
+            StatusCode::OK,
+            ephemeral_command_reply(&format!("No Receptionist Responses found for `{keyword}`")),
+        );
+    }
+
+    let context = ActionContext {
+        channel_id: payload.channel_id.to_owned(),
+        message_ts: None,
+        sender: payload.user_id.to_string(),
+        template_vars: command_template_vars(&keyword, &args, &payload),
+    };
+
+    let mut ephemeral_replies = Vec::new();
+    let slack_session = slack_state.open_session();
+    for rec_response in matching_responses {
+        ephemeral_replies.extend(command_ephemeral_replies(rec_response, &context));
+        run_response_actions(&slack_session, rec_response, &context).await;
+    }
+
+    if ephemeral_replies.is_empty() {
+        (StatusCode::OK, Value::default())
+    } else {
+        (
+            StatusCode::OK,
+            ephemeral_command_reply(&ephemeral_replies.join("\n")),
+        )
+    }
+}
+
+/// Replies only the user who ran the command should see. They go in the command's response body,
+/// which works in any conversation, unlike `chat.postEphemeral` that needs the bot in the channel
+fn command_ephemeral_replies(
+    rec_response: &ReceptionistResponse,
+    context: &ActionContext,
+) -> Vec<String> {
+    rec_response
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            ReceptionistAction::ForCommand(CommandAction::EphemeralReply(msg)) => {
+                Some(render_template(msg, &context.template_vars))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split command text into its (lowercased) keyword and the remaining args
+fn parse_command_keyword(text: &str) -> Option<(String, String)> {
+    let text = text.trim();
+    let (keyword, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+
+    if keyword.is_empty() {
+        None
+    } else {
+        Some((keyword.to_lowercase(), args.trim().to_string()))
+    }
+}
+
+fn command_template_vars(
+    keyword: &str,
+    args: &str,
+    payload: &SlackCommandEvent,
+) -> HashMap<String, String> {
+    let mut vars = HashMap::from([
+        ("keyword".to_string(), keyword.to_string()),
+        ("args".to_string(), args.to_string()),
+        ("user_id".to_string(), payload.user_id.to_string()),
+        ("channel_id".to_string(), payload.channel_id.to_string()),
+    ]);
+
+    for (index, arg) in args.split_whitespace().enumerate() {
+        vars.insert(format!("arg_{}", index + 1), arg.to_string());
+    }
+
+    vars
+}
+
+fn rotation_oncall_reply(rotation: &Rotation) -> Value {
+    match rotation.current_shift(Utc::now()) {
+        Ok(Some(shift)) => in_channel_command_reply(&rotation.describe_shift(&shift)),
+        Ok(None) => in_channel_command_reply(&format!(
+            "No one is oncall for *{}*, the rotation has no members",
+            rotation.name
+        )),
+        Err(e) => {
+            error!(
+                "unable to get current shift for rotation {}: {e}",
+                rotation.id
+            );
+            ephemeral_command_reply(&format!(
+                "The rotation *{}* is misconfigured, please check it in the manager",
+                rotation.name
+            ))
+        }
+    }
+}
+
+async fn oncall_cache_reply(
+    slack_state: &SlackStateWorkaround,
+    user_id: &SlackUserId,
+    args: &str,
+) -> Value {
+    let oncall_cache = get_or_init_oncall_cache().await;
+
+    let (target, clear_description) = match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [] => {
+            return ephemeral_command_reply(&format!(
+                "This instance's cache: {}",
+                oncall_cache.describe_metrics()
+            ))
+        }
+        ["clear"] => (None, "cached oncall lookups".to_string()),
+        ["clear", target] => (
+            Some(*target),
+            format!("cached oncall lookups for `{target}`"),
+        ),
+        _ => {
+            return ephemeral_command_reply(&format!(
+                "Usage: `{KEYWORD_COMMAND} {ONCALL_CACHE_KEYWORD} [clear [target]]`"
+            ))
+        }
+    };
+
+    if !can_clear_oncall_cache(slack_state, user_id).await {
+        return ephemeral_command_reply(
+            "Only Response collaborators and workspace admins can clear the oncall cache",
+        );
+    }
+
+    let reply = match oncall_cache.invalidate_everywhere(target).await {
+        Ok(removed) => format!(
+            "Cleared {removed} {clear_description} here, other instances clear theirs the next time they look up an oncall"
+        ),
+        Err(e) => {
+            error!("unable to record oncall cache clear: {e}");
+            "Unable to clear the oncall cache of every instance, please try again".to_string()
+        }
+    };
+
+    info!("{ONCALL_CACHE_KEYWORD} {args} by {user_id}: {reply}");
+    ephemeral_command_reply(&reply)
+}
+
+/// Collaborators of any Response, or workspace admins and owners
+async fn can_clear_oncall_cache(slack_state: &SlackStateWorkaround, user_id: &SlackUserId) -> bool {
+    match get_responses_for_collaborator(&user_id.to_string()).await {
+        Ok(responses) if !responses.is_empty() => return true,
+        Ok(_) => (),
+        Err(e) => error!("unable to get responses for collaborator {user_id}: {e}"),
+    }
+
+    match slack_state
+        .open_session()
+        .users_info(&SlackApiUsersInfoRequest::new(user_id.to_owned()))
+        .await
+    {
+        Ok(user_info) => {
+            user_info.user.flags.is_admin.unwrap_or_default()
+                || user_info.user.flags.is_owner.unwrap_or_default()
+        }
+        Err(slack_err) => {
+            error!("Unable to look up user {user_id}: {slack_err}");
+            false
+        }
+    }
+}
+
+/// Immediate reply to a slash command that everyone in the channel can see
+fn in_channel_command_reply(text: &str) -> Value {
+    json!({
+        "response_type": "in_channel",
+        "text": text,
+    })
+}
+
+/// Immediate reply to a slash command that only the user who ran it can see
+fn ephemeral_command_reply(text: &str) -> Value {
+    json!({
+        "response_type": "ephemeral",
+        "text": text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_keyword() {
+        assert_eq!(
+            Some(("oncall".to_string(), "payments team".to_string())),
+            parse_command_keyword(" Oncall  payments team ")
+        );
+        assert_eq!(
+            Some(("faq".to_string(), "".to_string())),
+            parse_command_keyword("faq")
+        );
+        assert_eq!(None, parse_command_keyword("   "));
+    }
+
+    #[test]
+    fn test_command_ephemeral_replies() {
+        let rec_response = ReceptionistResponse::new(
+            vec!["U0001".to_string()],
+            ReceptionistListener::SlackCommandKeyword {
+                command: KEYWORD_COMMAND.to_string(),
+                keyword: "faq".to_string(),
+            },
+            vec![
+                ReceptionistAction::ForCommand(CommandAction::ChannelReply(
+                    "posted to the channel".to_string(),
+                )),
+                ReceptionistAction::ForCommand(CommandAction::EphemeralReply(
+                    "see the faq for {{args}}".to_string(),
+                )),
+            ],
+            vec![],
+        );
+        let context = ActionContext {
+            channel_id: "C0001".into(),
+            message_ts: None,
+            sender: "U0001".to_string(),
+            template_vars: HashMap::from([("args".to_string(), "deploys".to_string())]),
+        };
+
+        assert_eq!(
+            command_ephemeral_replies(&rec_response, &context),
+            vec!["see the faq for deploys".to_string()]
+        );
+    }
+}
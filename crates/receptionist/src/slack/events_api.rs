@@ -1,24 +1,39 @@
 use super::SlackStateWorkaround;
 
 #[cfg(any(feature = "tempdb", feature = "dynamodb"))]
-use crate::database::get_responses_for_listener;
+use crate::database::{
+    claim_delivered_event, get_fired_response_ids, get_responses_for_listener, has_welcomed_member,
+    record_fired_responses, record_welcomed_member,
+};
 use crate::{
-    config::get_or_init_app_config,
-    format_forwarded_message, get_sender,
-    response::{MessageAction, ReceptionistAction, ReceptionistCondition, ReceptionistResponse},
-    slack::api_calls::reactions_add,
-    MessageHelpers, ReceptionistListener,
+    get_sender, is_thread_reply,
+    response::{ReceptionistCondition, ReceptionistResponse},
+    run_response_actions,
+    slack::api_calls::message_thread_ts,
+    strip_app_mention, ActionContext, DeliveredEvent, IncomingChannel, MessageHelpers,
+    ReceptionistListener,
 };
 use axum::{extract::Extension, http::StatusCode, response::IntoResponse, Json};
 use serde_json::{to_value, Value};
 use slack_morphism::prelude::*;
-use std::sync::Arc;
-use tracing::error;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{debug, error, info};
+
+/// `channel_type` of messages posted in public channels
+const PUBLIC_CHANNEL_TYPE: &str = "channel";
+/// `channel_type` of direct messages to the bot
+const DIRECT_MESSAGE_CHANNEL_TYPE: &str = "im";
 
 pub async fn axum_handler_slack_events_api(
     Extension(slack_state): Extension<Arc<SlackStateWorkaround>>,
     Json(payload): Json<SlackPushEvent>,
 ) -> impl IntoResponse {
+    // Slack redelivers events that aren't acked within 3 seconds, so their actions run after acking
+    if matches!(payload, SlackPushEvent::EventCallback(_)) {
+        tokio::spawn(async move { handle_slack_event(&*slack_state, payload).await });
+        return (StatusCode::OK, Json(Value::default()));
+    }
+
     let response = handle_slack_event(&*slack_state, payload).await;
     (response.0, Json(response.1))
 }
@@ -29,6 +44,10 @@ pub async fn handle_slack_event(
 ) -> (StatusCode, Value) {
     match payload {
         SlackPushEvent::EventCallback(event_req) => {
+            if !is_first_delivery(&event_req.event_id).await {
+                return (StatusCode::OK, Value::default());
+            }
+
             let response_body =
                 process_event_callback_for_receptionist(event_req, slack_state).await;
             (StatusCode::OK, response_body)
@@ -43,6 +62,23 @@ pub async fn handle_slack_event(
     }
 }
 
+/// Whether this is the first time Slack delivered the event,
+/// later deliveries are acked without running anything
+async fn is_first_delivery(event_id: &SlackEventId) -> bool {
+    match claim_delivered_event(&DeliveredEvent::Slack(event_id.to_string())).await {
+        Ok(true) => true,
+        Ok(false) => {
+            info!("ignoring redelivered Slack event {event_id}");
+            false
+        }
+        Err(e) => {
+            // better to risk running twice than to lose the event
+            error!("unable to check if Slack event {event_id} was delivered before: {e}");
+            true
+        }
+    }
+}
+
 pub async fn process_event_callback_for_receptionist(
     event_req: SlackPushEventCallback,
     // slack_client: Arc<SlackStateWorkaround>,
@@ -51,6 +87,11 @@ pub async fn process_event_callback_for_receptionist(
     let default_event_response = Value::default();
     match event_req.event {
         SlackEventCallbackBody::Message(event) => {
+            if matches!(event.subtype, Some(SlackMessageEventType::MessageChanged)) {
+                handle_message_edit(slack_client, event).await;
+                return default_event_response;
+            }
+
             if [
                 event.is_bot_message(),
                 event.is_hidden(),
@@ -67,164 +108,490 @@ pub async fn process_event_callback_for_receptionist(
                 .origin
                 .channel
                 .unwrap_or_else(|| SlackChannelId("".to_string()));
+            let channel_type = event
+                .origin
+                .channel_type
+                .map(|channel_type| channel_type.0)
+                .unwrap_or_default();
+
+            if channel_type == DIRECT_MESSAGE_CHANNEL_TYPE {
+                let context = ActionContext {
+                    channel_id: event_channel_id,
+                    message_ts: Some(event.origin.ts),
+                    sender: get_sender(&event.sender),
+                    template_vars: message_event_template_vars(&message_content),
+                };
+
+                handle_direct_message(slack_client, &message_content, &context).await;
+                return default_event_response;
+            }
 
             let responses_for_channel_id =
-                get_responses_for_listener(ReceptionistListener::SlackChannel {
-                    channel_id: event_channel_id.to_string(),
-                })
-                .await
-                .expect("unable to get responses for channel");
+                get_responses_for_channel(slack_client, &event_channel_id, &channel_type).await;
 
-            let responses_for_message_type: Vec<ReceptionistResponse> = responses_for_channel_id
+            let edit_response_ids: Vec<String> = responses_for_channel_id
                 .iter()
-                .filter(|r| {
-                    r.conditions
-                        .iter()
-                        .any(|t_type| matches!(&t_type, &ReceptionistCondition::ForMessage(_)))
-                })
-                .map(|r| r.to_owned())
+                .filter(|r| r.run_on_edits)
+                .map(|r| r.id.to_owned())
                 .collect();
 
-            let slack_session = slack_client.open_session();
-
-            for rec_response in responses_for_message_type {
-                if rec_response.check_for_match(&message_content) {
-                    for action in &rec_response.actions {
-                        match &action {
-                            ReceptionistAction::ForMessage(message_action) => {
-                                match message_action {
-                                    MessageAction::AttachEmoji(name) => {
-                                        if let Err(slack_err) = reactions_add(
-                                            &slack_session,
-                                            &event_channel_id.to_string(),
-                                            event.origin.ts.as_ref(),
-                                            name,
-                                        )
-                                        .await
-                                        {
-                                            // log error
-                                            error!("{}", slack_err);
-                                        }
-                                    }
-                                    MessageAction::ThreadedMessage(msg) => {
-                                        if let Err(slack_err) = slack_session
-                                            .chat_post_message(
-                                                &SlackApiChatPostMessageRequest::new(
-                                                    event_channel_id.to_owned(),
-                                                    SlackMessageContent::new()
-                                                        .with_text(msg.to_owned()),
-                                                )
-                                                .with_thread_ts(event.origin.ts.to_owned()),
-                                            )
-                                            .await
-                                        {
-                                            error!("{}", slack_err);
-                                        }
-                                    }
-                                    MessageAction::ChannelMessage(msg) => {
-                                        if let Err(slack_err) = slack_session
-                                            .chat_post_message(
-                                                &SlackApiChatPostMessageRequest::new(
-                                                    event_channel_id.to_owned(),
-                                                    SlackMessageContent::new()
-                                                        .with_text(msg.to_owned()),
-                                                ),
-                                            )
-                                            .await
-                                        {
-                                            error!("{}", slack_err);
-                                        }
-                                    }
-                                    MessageAction::MsgOncallInThread {
-                                        escalation_policy_id,
-                                        message,
-                                    } => {
-                                        // get oncall user
-                                        match &get_or_init_app_config().await.pagerduty_config {
-                                            Some(pd) => {
-                                                match  pd.get_oncalls(escalation_policy_id.to_owned()).await {
-                                                    Ok(oncalls_list) => {
-                                                        if let Some(pd_user) = oncalls_list.oncalls.first() {
-                                                            let slack_user = slack_session.users_lookup_by_email(&SlackApiUsersLookupByEmailRequest::new(pd_user.user.email.clone().into())).await;
-                                                            match slack_user {
-                                                                Ok(slack_profile) => {
-                                                                    if let Err(slack_err) = slack_session.chat_post_message(
-                                                                        &SlackApiChatPostMessageRequest::new(
-                                                                            event_channel_id.to_owned(),
-                                                                            SlackMessageContent::new()
-                                                                                .with_text(format!("<@{}> - {message}", slack_profile.user.id)),
-                                                                        )
-                                                                        .with_thread_ts(event.origin.ts.to_owned()),
-                                                                    )
-                                                                    .await {
-                                                                        error!("Error posting to thread: {}", slack_err)
-                                                                    }
-
-                                                                },
-                                                                Err(slack_err) => error!("Unable to get slack profile for PD user - {}", slack_err), 
-                                                            }
-                                                        }
-                                                    },
-                                                    Err(err) => error!("Error fetching oncalls from pd for escalation policy {} - {}", escalation_policy_id, err),
-                                                }
-
-                                            },
-                                            None => error!("No pagerduty token configured, unable to tag user in thread"),
-                                        }
-                                    }
-                                    MessageAction::ForwardMessageToChannel {
-                                        channel,
-                                        msg_context,
-                                    } => {
-                                        match slack_session
-                                            .chat_get_permalink(
-                                                &SlackApiChatGetPermalinkRequest::new(
-                                                    event_channel_id.to_owned(),
-                                                    event.origin.ts.to_owned(),
-                                                ),
-                                            )
-                                            .await
-                                        {
-                                            Ok(permalink_resp) => {
-                                                let permalink = permalink_resp.permalink;
-                                                let sender = get_sender(&event.sender);
-                                                if let Err(slack_err) = slack_session
-                                                    .chat_post_message(
-                                                        &SlackApiChatPostMessageRequest::new(
-                                                            channel.into(),
-                                                            SlackMessageContent::new().with_text(
-                                                                format_forwarded_message(
-                                                                    event_channel_id.as_ref(),
-                                                                    &sender,
-                                                                    &permalink.to_string(),
-                                                                    msg_context,
-                                                                ),
-                                                            ),
-                                                        ),
-                                                    )
-                                                    .await
-                                                {
-                                                    error!(
-                                                        "Failed to forward message {}",
-                                                        slack_err
-                                                    );
-                                                }
-                                            }
-                                            Err(slack_err) => error!(
-                                                "Failed to get permalink to forward message: {}",
-                                                slack_err
-                                            ),
-                                        };
-                                    }
-                                }
-                            }
-                        };
+            let context = ActionContext {
+                channel_id: event_channel_id,
+                message_ts: Some(event.origin.ts),
+                sender: get_sender(&event.sender),
+                template_vars: message_event_template_vars(&message_content),
+            };
+
+            let fired_response_ids = run_matching_responses(
+                slack_client,
+                responses_for_channel_id,
+                &message_content,
+                &context,
+            )
+            .await;
+
+            // only Responses that check edits need to remember what already ran
+            remember_fired_responses(
+                &context,
+                fired_response_ids
+                    .into_iter()
+                    .filter(|id| edit_response_ids.contains(id))
+                    .collect(),
+            )
+            .await;
+        }
+        SlackEventCallbackBody::AppMention(event) => {
+            let message_content = strip_app_mention(&event.content.text.unwrap_or_default());
+
+            let responses_for_mention =
+                match get_responses_for_listener(ReceptionistListener::AppMention {
+                    channel_id: Some(event.channel.to_string()),
+                })
+                .await
+                {
+                    Ok(responses) => responses,
+                    Err(e) => {
+                        error!("unable to get responses for app mention: {e}");
+                        return default_event_response;
                     }
+                };
+
+            let context = ActionContext {
+                channel_id: event.channel,
+                message_ts: Some(event.origin.ts),
+                sender: event.user.to_string(),
+                template_vars: message_event_template_vars(&message_content),
+            };
+
+            run_matching_responses(
+                slack_client,
+                responses_for_mention,
+                &message_content,
+                &context,
+            )
+            .await;
+        }
+        SlackEventCallbackBody::MemberJoinedChannel(event) => {
+            handle_member_joined_channel(slack_client, event).await;
+        }
+        _ => debug!("Ignoring unsupported event callback"),
+    }
+
+    default_event_response
+}
+
+/// Run the Responses listening for users joining this channel, actions are sent to the joining user
+async fn handle_member_joined_channel(
+    slack_client: &SlackStateWorkaround,
+    event: SlackMemberJoinedChannelEvent,
+) {
+    let responses_for_channel =
+        match get_responses_for_listener(ReceptionistListener::MemberJoinedChannel {
+            channel_id: event.channel.to_string(),
+            once_per_user: false,
+            skip_guests: false,
+        })
+        .await
+        {
+            Ok(responses) => responses,
+            Err(e) => {
+                error!("unable to get responses for member joined channel: {e}");
+                return;
+            }
+        };
+
+    if responses_for_channel.is_empty() {
+        return;
+    }
+
+    let slack_session = slack_client.open_session();
+    let user_id = event.user.to_string();
+
+    // only look the user up if a Response needs to know whether they're a guest
+    let is_guest = if responses_for_channel.iter().any(|r| {
+        matches!(
+            r.listener,
+            ReceptionistListener::MemberJoinedChannel {
+                skip_guests: true,
+                ..
+            }
+        )
+    }) {
+        match slack_session
+            .users_info(&SlackApiUsersInfoRequest::new(event.user.to_owned()))
+            .await
+        {
+            Ok(user_info) => {
+                user_info.user.flags.is_restricted.unwrap_or_default()
+                    || user_info.user.flags.is_ultra_restricted.unwrap_or_default()
+            }
+            Err(slack_err) => {
+                // don't risk welcoming a guest
+                error!("Unable to look up joining user {}: {}", user_id, slack_err);
+                true
+            }
+        }
+    } else {
+        false
+    };
+
+    let context = ActionContext {
+        channel_id: event.channel.to_owned(),
+        message_ts: None,
+        sender: user_id.to_owned(),
+        template_vars: HashMap::from([
+            ("user_id".to_string(), user_id.to_owned()),
+            ("channel_id".to_string(), event.channel.to_string()),
+        ]),
+    };
+
+    for rec_response in &responses_for_channel {
+        let (once_per_user, skip_guests) = match rec_response.listener {
+            ReceptionistListener::MemberJoinedChannel {
+                once_per_user,
+                skip_guests,
+                ..
+            } => (once_per_user, skip_guests),
+            _ => continue,
+        };
+
+        if skip_guests && is_guest {
+            continue;
+        }
+
+        if once_per_user {
+            match has_welcomed_member(&rec_response.id, &user_id).await {
+                Ok(false) => (),
+                Ok(true) => continue,
+                Err(e) => {
+                    error!("unable to check if user was already welcomed: {e}");
+                    continue;
                 }
             }
         }
-        _ => todo!(),
+
+        run_response_actions(&slack_session, rec_response, &context).await;
+
+        if once_per_user {
+            if let Err(e) = record_welcomed_member(&rec_response.id, &user_id).await {
+                error!("unable to record welcomed user: {e}");
+            }
+        }
     }
+}
 
-    default_event_response
+/// Rerun Responses that opted in to edits, skipping any that already ran for the original message
+async fn handle_message_edit(slack_client: &SlackStateWorkaround, event: SlackMessageEvent) {
+    let edited_message = match event.message {
+        Some(edited_message) => edited_message,
+        None => return,
+    };
+
+    if edited_message.sender.bot_id.is_some() {
+        return;
+    }
+
+    let event_channel_id = event
+        .origin
+        .channel
+        .unwrap_or_else(|| SlackChannelId("".to_string()));
+    let channel_type = event
+        .origin
+        .channel_type
+        .map(|channel_type| channel_type.0)
+        .unwrap_or_default();
+
+    let responses_for_edits: Vec<ReceptionistResponse> =
+        get_responses_for_channel(slack_client, &event_channel_id, &channel_type)
+            .await
+            .into_iter()
+            .filter(|r| r.run_on_edits)
+            .collect();
+
+    if responses_for_edits.is_empty() {
+        return;
+    }
+
+    // edit events don't say whether the message is a thread reply, and new replies never trigger Responses
+    let slack_session = slack_client.open_session();
+    match message_thread_ts(&slack_session, &event_channel_id, &edited_message.ts).await {
+        Ok(thread_ts) if is_thread_reply(&edited_message.ts, thread_ts.as_ref()) => return,
+        Ok(_) => (),
+        Err(slack_err) => {
+            error!("unable to check if edited message is a thread reply: {slack_err}");
+            return;
+        }
+    }
+
+    let already_fired =
+        match get_fired_response_ids(event_channel_id.as_ref(), edited_message.ts.as_ref()).await {
+            Ok(already_fired) => already_fired,
+            Err(e) => {
+                // better to miss an edit than to post the same reply twice
+                error!("unable to get fired responses for edited message: {e}");
+                return;
+            }
+        };
+
+    let message_content = edited_message
+        .content
+        .and_then(|content| content.text)
+        .unwrap_or_default();
+
+    let context = ActionContext {
+        channel_id: event_channel_id,
+        message_ts: Some(edited_message.ts),
+        sender: get_sender(&edited_message.sender),
+        template_vars: message_event_template_vars(&message_content),
+    };
+
+    let fired_response_ids = run_matching_responses(
+        slack_client,
+        responses_for_edits
+            .into_iter()
+            .filter(|r| !already_fired.contains(&r.id))
+            .collect(),
+        &message_content,
+        &context,
+    )
+    .await;
+
+    remember_fired_responses(&context, fired_response_ids).await;
+}
+
+/// Message events only know the text, actions fill in the sender and channel themselves
+fn message_event_template_vars(message_text: &str) -> HashMap<String, String> {
+    HashMap::from([("message_text".to_string(), message_text.to_owned())])
+}
+
+async fn remember_fired_responses(context: &ActionContext, response_ids: Vec<String>) {
+    let message_ts = match &context.message_ts {
+        Some(message_ts) => message_ts,
+        None => return,
+    };
+
+    if response_ids.is_empty() {
+        return;
+    }
+
+    if let Err(e) = record_fired_responses(
+        context.channel_id.as_ref(),
+        message_ts.as_ref(),
+        response_ids,
+    )
+    .await
+    {
+        error!("unable to record fired responses: {e}");
+    }
+}
+
+/// Responses listening to messages in this channel.
+/// Multi-channel listeners are narrowed down to those that apply here,
+/// the channel name is only fetched from Slack when a pattern listener needs it
+async fn get_responses_for_channel(
+    slack_client: &SlackStateWorkaround,
+    channel_id: &SlackChannelId,
+    channel_type: &str,
+) -> Vec<ReceptionistResponse> {
+    let responses = match get_responses_for_listener(ReceptionistListener::SlackChannel {
+        channel_id: channel_id.to_string(),
+    })
+    .await
+    {
+        Ok(responses) => responses,
+        Err(e) => {
+            error!("unable to get responses for channel {channel_id}: {e}");
+            return Vec::new();
+        }
+    };
+
+    let mut incoming_channel = IncomingChannel {
+        id: channel_id.to_string(),
+        name: None,
+        is_public: channel_type == PUBLIC_CHANNEL_TYPE,
+    };
+
+    if responses.iter().any(|r| r.listener.needs_channel_name()) {
+        match slack_client
+            .open_session()
+            .conversations_info(&SlackApiConversationsInfoRequest::new(
+                incoming_channel.id.to_owned().into(),
+            ))
+            .await
+        {
+            Ok(info) => incoming_channel.name = info.channel.name,
+            Err(slack_err) => error!(
+                "Unable to get channel name for {}: {}",
+                incoming_channel.id, slack_err
+            ),
+        }
+    }
+
+    responses
+        .into_iter()
+        .filter(|r| r.listener.matches_channel(&incoming_channel))
+        .collect()
+}
+
+/// Run the DM Responses that match the message, or the fallback Responses if none did
+async fn handle_direct_message(
+    slack_client: &SlackStateWorkaround,
+    message_content: &str,
+    context: &ActionContext,
+) {
+    let responses_for_dm = match get_responses_for_listener(ReceptionistListener::DirectMessage {
+        is_fallback: false,
+    })
+    .await
+    {
+        Ok(responses) => responses,
+        Err(e) => {
+            error!("unable to get responses for direct message: {e}");
+            return;
+        }
+    };
+
+    let (fallback_responses, dm_responses): (Vec<_>, Vec<_>) = responses_for_dm
+        .into_iter()
+        .partition(|r| r.listener.is_fallback());
+
+    if !run_matching_responses(slack_client, dm_responses, message_content, context)
+        .await
+        .is_empty()
+    {
+        return;
+    }
+
+    let slack_session = slack_client.open_session();
+    for rec_response in &fallback_responses {
+        run_response_actions(&slack_session, rec_response, context).await;
+    }
+}
+
+/// Run the actions of every Response whose message conditions match the incoming text.
+/// Returns the IDs of the Responses that ran
+async fn run_matching_responses(
+    slack_client: &SlackStateWorkaround,
+    responses: Vec<ReceptionistResponse>,
+    message_content: &str,
+    context: &ActionContext,
+) -> Vec<String> {
+    let slack_session = slack_client.open_session();
+    let mut fired_response_ids = Vec::new();
+
+    for rec_response in responses.iter().filter(|r| {
+        r.conditions
+            .iter()
+            .any(|t_type| matches!(&t_type, &ReceptionistCondition::ForMessage(_)))
+    }) {
+        if rec_response.check_for_match(message_content) {
+            fired_response_ids.push(rec_response.id.to_owned());
+            run_response_actions(&slack_session, rec_response, context).await;
+        }
+    }
+
+    fired_response_ids
+}
+
+/// Responses are only stored and fired in the in memory database during tests
+#[cfg(all(test, feature = "tempdb"))]
+mod tests {
+    use super::*;
+    use crate::{create_response, MessageCondition};
+    use serde_json::json;
+
+    fn message_event(event_id: &str) -> SlackPushEvent {
+        serde_json::from_value(json!({
+            "type": "event_callback",
+            "team_id": "T0001",
+            "api_app_id": "A0001",
+            "event_id": event_id,
+            "event_time": 1650000000,
+            "event": {
+                "type": "message",
+                "channel": "C0001",
+                "channel_type": "channel",
+                "user": "U0001",
+                "text": "deploy failed",
+                "ts": "1650000000.000100"
+            }
+        }))
+        .unwrap()
+    }
+
+    fn deploy_failed_response() -> ReceptionistResponse {
+        let mut rec_response = ReceptionistResponse::new(
+            vec!["U0001".to_string()],
+            ReceptionistListener::SlackChannel {
+                channel_id: "C0001".to_string(),
+            },
+            vec![],
+            vec![ReceptionistCondition::ForMessage(
+                MessageCondition::MatchPhrase("deploy failed".to_string()),
+            )],
+        );
+        // fired Responses are only recorded when they check edits
+        rec_response.run_on_edits = true;
+        rec_response
+    }
+
+    /// A redelivered event is acked without running anything, only its first delivery
+    /// fires the Responses for the message
+    #[tokio::test]
+    async fn test_redelivered_event_does_not_fire_again() {
+        let slack_state = SlackStateWorkaround::new_from_env();
+
+        let first_response = deploy_failed_response();
+        let first_response_id = first_response.id.to_owned();
+        create_response(first_response).await.unwrap();
+
+        let (status, _) = handle_slack_event(&slack_state, message_event("Ev0001")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_fired_response_ids("C0001", "1650000000.000100")
+                .await
+                .unwrap(),
+            vec![first_response_id.to_owned()]
+        );
+
+        // a Response created since would match, but the redelivery doesn't run anything
+        let second_response = deploy_failed_response();
+        let second_response_id = second_response.id.to_owned();
+        create_response(second_response).await.unwrap();
+
+        let (status, _) = handle_slack_event(&slack_state, message_event("Ev0001")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_fired_response_ids("C0001", "1650000000.000100")
+                .await
+                .unwrap(),
+            vec![first_response_id]
+        );
+
+        handle_slack_event(&slack_state, message_event("Ev0002")).await;
+        assert!(get_fired_response_ids("C0001", "1650000000.000100")
+            .await
+            .unwrap()
+            .contains(&second_response_id));
+    }
 }
@@ -1,5 +1,10 @@
-use super::SlackStateWorkaround;
-use crate::{process_action_event, process_submission_event};
+use super::{
+    options_load::SlackBlockSuggestion, shortcuts::is_run_response_view, SlackStateWorkaround,
+};
+use crate::{
+    handle_slack_options_load, process_action_event, process_global_shortcut,
+    process_message_shortcut, process_run_response_submission, process_submission_event,
+};
 use axum::{
     extract::{Extension, Form},
     http::StatusCode,
@@ -24,13 +29,18 @@ pub async fn axum_handler_slack_interactions_api(
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SlackInteractionWrapper {
     // payload: SlackInteractionEvent, // but as a Form
-    payload: String,
+    pub(crate) payload: String,
 }
 
 pub async fn handle_slack_interaction(
     slack_state: &SlackStateWorkaround,
     payload: SlackInteractionWrapper,
 ) -> (StatusCode, Value) {
+    // lets the interactivity request url double as the options load url
+    if SlackBlockSuggestion::from_payload(&payload.payload).is_some() {
+        return handle_slack_options_load(payload).await;
+    }
+
     if let Ok(interaction_event) = from_str::<SlackInteractionEvent>(&payload.payload) {
         match interaction_event {
             SlackInteractionEvent::BlockActions(block_action_event) => {
@@ -38,6 +48,25 @@ pub async fn handle_slack_interaction(
                     error!("error: {}", result);
                 }
             }
+            SlackInteractionEvent::Shortcut(shortcut_event) => {
+                if let Err(e) = process_global_shortcut(shortcut_event, slack_state).await {
+                    error!("error: {}", e);
+                }
+            }
+            SlackInteractionEvent::MessageAction(message_action_event) => {
+                if let Err(e) = process_message_shortcut(message_action_event, slack_state).await {
+                    error!("error: {}", e);
+                }
+            }
+            SlackInteractionEvent::ViewSubmission(view_submission_event)
+                if is_run_response_view(&view_submission_event) =>
+            {
+                if let Err(e) =
+                    process_run_response_submission(view_submission_event, slack_state).await
+                {
+                    error!("error: {}", e);
+                }
+            }
             SlackInteractionEvent::ViewSubmission(view_submission_event) => {
                 match process_submission_event(view_submission_event).await {
                     Ok(opt) => {
@@ -50,7 +79,8 @@ pub async fn handle_slack_interaction(
                     }
                 }
             }
-            _ => todo!("Other interaction events are not implemented"),
+            // ex: view_closed, acked without doing anything
+            _ => (),
         }
 
         (StatusCode::NO_CONTENT, serde_json::to_value("").unwrap())
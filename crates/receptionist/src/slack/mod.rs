@@ -1,16 +1,28 @@
+pub mod action_runner;
 pub mod api_calls;
 pub mod commands_api;
 pub mod events_api;
 pub mod interaction_api;
+pub mod options_load;
+pub mod shortcuts;
 pub mod state_values;
 pub mod utils;
 pub mod verification;
 
-pub use commands_api::{axum_handler_handle_slack_commands_api, handle_slack_command};
+pub use action_runner::{run_response_actions, ActionContext};
+pub use commands_api::{
+    axum_handler_handle_slack_commands_api, handle_slack_command, KEYWORD_COMMAND,
+};
 pub use events_api::{axum_handler_slack_events_api, handle_slack_event};
 pub use interaction_api::{
     axum_handler_slack_interactions_api, handle_slack_interaction, SlackInteractionWrapper,
 };
+pub use options_load::{axum_handler_slack_options_load, handle_slack_options_load};
+pub use shortcuts::{
+    process_global_shortcut, process_message_shortcut, process_run_response_submission,
+    CREATE_RESPONSE_SHORTCUT_CALLBACK_ID, OPEN_MANAGER_SHORTCUT_CALLBACK_ID,
+    RUN_RESPONSE_SHORTCUT_CALLBACK_ID,
+};
 pub use slack_morphism::signature_verifier::SlackEventSignatureVerifier;
 pub use state_values::*;
 pub use utils::*;
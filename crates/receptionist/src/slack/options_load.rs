@@ -0,0 +1,159 @@
+//! Options for `external_select` elements, Slack asks for them as the user types
+use super::SlackInteractionWrapper;
+use crate::{
+    config::get_or_init_app_config, pagerduty::models::NamedResource, BlockSectionRouter,
+    PagerDutyOncallTarget,
+};
+use anyhow::{anyhow, bail, Result};
+use axum::{extract::Form, http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, json, Value};
+use slack_morphism::prelude::*;
+use tracing::error;
+
+/// `type` of the interaction payloads sent to the options load URL
+pub const BLOCK_SUGGESTION_TYPE: &str = "block_suggestion";
+
+/// Slack's request for the options matching what the user typed
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SlackBlockSuggestion {
+    #[serde(rename = "type")]
+    pub suggestion_type: String,
+    pub action_id: String,
+    /// what the user typed so far
+    #[serde(default)]
+    pub value: String,
+}
+
+impl SlackBlockSuggestion {
+    /// `None` for every other kind of interaction payload
+    pub fn from_payload(payload: &str) -> Option<Self> {
+        from_str::<Self>(payload)
+            .ok()
+            .filter(|suggestion| suggestion.suggestion_type == BLOCK_SUGGESTION_TYPE)
+    }
+}
+
+pub async fn axum_handler_slack_options_load(
+    Form(body): Form<SlackInteractionWrapper>,
+) -> impl IntoResponse {
+    let response = handle_slack_options_load(body).await;
+    (response.0, Json(response.1))
+}
+
+/// Failures are logged and answered with no options, so the select shows "no results" instead of an error
+pub async fn handle_slack_options_load(payload: SlackInteractionWrapper) -> (StatusCode, Value) {
+    let suggestion = match SlackBlockSuggestion::from_payload(&payload.payload) {
+        Some(suggestion) => suggestion,
+        None => {
+            error!("Options load `payload` is not a block suggestion");
+            return (StatusCode::BAD_REQUEST, Value::default());
+        }
+    };
+
+    let options = match load_options(&suggestion).await {
+        Ok(options) => options,
+        Err(e) => {
+            error!("Unable to load options for {}: {}", suggestion.action_id, e);
+            vec![]
+        }
+    };
+
+    (StatusCode::OK, json!({ "options": options }))
+}
+
+async fn load_options(
+    suggestion: &SlackBlockSuggestion,
+) -> Result<Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>>> {
+    let (route, _index) = BlockSectionRouter::from_string_with_index(&suggestion.action_id)
+        .ok_or_else(|| anyhow!("route not found"))?;
+
+    match route {
+        BlockSectionRouter::PDEscalationPolicySelected => {
+            let pagerduty = get_or_init_app_config()
+                .await
+                .pagerduty_config
+                .as_ref()
+                .ok_or_else(|| anyhow!("No PagerDuty token configured"))?;
+
+            let escalation_policies = pagerduty
+                .search_escalation_policies(&suggestion.value)
+                .await?;
+            let schedules = pagerduty.search_schedules(&suggestion.value).await?;
+
+            Ok(pagerduty_target_choice_items(
+                escalation_policies,
+                schedules,
+            ))
+        }
+        _ => bail!("{route} has no options to load"),
+    }
+}
+
+/// Escalation policies first since they're the usual target, values are saved as the action's target
+fn pagerduty_target_choice_items(
+    escalation_policies: Vec<NamedResource>,
+    schedules: Vec<NamedResource>,
+) -> Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> {
+    let policy_items = escalation_policies.into_iter().map(|policy| {
+        SlackBlockChoiceItem::new(
+            pt!(policy.name),
+            PagerDutyOncallTarget::EscalationPolicy(policy.id).to_string(),
+        )
+    });
+
+    let schedule_items = schedules.into_iter().map(|schedule| {
+        SlackBlockChoiceItem::new(
+            pt!(format!("Schedule: {}", schedule.name)),
+            PagerDutyOncallTarget::Schedule(schedule.id).to_string(),
+        )
+    });
+
+    policy_items.chain(schedule_items).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_parse_block_suggestions() {
+        let suggestion = SlackBlockSuggestion::from_payload(
+            &json!({
+                "type": "block_suggestion",
+                "action_id": "pd-escalation-policy-selected_IDX_0",
+                "block_id": "BLOCK-pd-escalation-policy-selected_IDX_0",
+                "value": "paym"
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(suggestion.value, "paym");
+
+        assert!(SlackBlockSuggestion::from_payload(
+            &json!({"type": "block_actions", "actions": []}).to_string()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn should_list_policies_before_schedules() {
+        let resource = |id: &str, name: &str| NamedResource {
+            id: id.to_string(),
+            name: name.to_string(),
+        };
+
+        let items = pagerduty_target_choice_items(
+            vec![resource("PPOLICY", "Payments")],
+            vec![resource("PSCHED1", "Payments Primary")],
+        );
+
+        assert_eq!(
+            serde_json::to_value(items).unwrap(),
+            json!([
+                {"text": {"type": "plain_text", "text": "Payments"}, "value": "PPOLICY"},
+                {"text": {"type": "plain_text", "text": "Schedule: Payments Primary"}, "value": "schedule:PSCHED1"}
+            ])
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Slack shortcuts, ex: opening the manager or running a Response against a message it should have caught
+#[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+use crate::database::{get_response_by_id, get_responses_for_listener};
+use crate::{
+    extract_action_block_states, run_response_actions, slack::api_calls::conversations_message,
+    ActionContext, BlockSectionRouter, IncomingChannel, ManagerViewModes, MetaForManagerView,
+    ReceptionistListener, ReceptionistResponse, SlackStateWorkaround,
+};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string};
+use slack_morphism::prelude::*;
+use std::collections::HashMap;
+use tracing::error;
+
+/// `callback_id` of the "Run Receptionist response…" message shortcut in the app manifest
+pub const RUN_RESPONSE_SHORTCUT_CALLBACK_ID: &str = "run_response";
+/// `callback_id` of the "Create response from this message" message shortcut in the app manifest
+pub const CREATE_RESPONSE_SHORTCUT_CALLBACK_ID: &str = "create_response";
+/// `callback_id` of the global shortcut that opens the manager, same as `/rec-manage`
+pub const OPEN_MANAGER_SHORTCUT_CALLBACK_ID: &str = "open_manager";
+/// `callback_id` of the modal opened by the run response shortcut, so its submission isn't parsed as the manager
+pub const RUN_RESPONSE_VIEW_CALLBACK_ID: &str = "run-response-view";
+
+/// The message a run response modal was opened from.
+/// Its text is fetched again on submit, messages can be longer than Slack allows in private_metadata
+#[derive(Debug, Serialize, Deserialize)]
+struct RunResponseMeta {
+    channel_id: String,
+    message_ts: String,
+}
+
+pub async fn process_global_shortcut(
+    shortcut_event: SlackInteractionShortcutEvent,
+    slack_state: &SlackStateWorkaround,
+) -> Result<()> {
+    if shortcut_event.callback_id.as_ref() != OPEN_MANAGER_SHORTCUT_CALLBACK_ID {
+        bail!(
+            "Unknown global shortcut callback_id: {}",
+            shortcut_event.callback_id
+        )
+    }
+
+    slack_state
+        .open_manager_modal_view(
+            shortcut_event.trigger_id,
+            &MetaForManagerView::new(ManagerViewModes::Home, shortcut_event.user.id.to_string()),
+        )
+        .await
+}
+
+pub async fn process_message_shortcut(
+    shortcut_event: SlackInteractionMessageActionEvent,
+    slack_state: &SlackStateWorkaround,
+) -> Result<()> {
+    match shortcut_event.callback_id.as_ref() {
+        RUN_RESPONSE_SHORTCUT_CALLBACK_ID => {
+            open_run_response_view(shortcut_event, slack_state).await
+        }
+        CREATE_RESPONSE_SHORTCUT_CALLBACK_ID => {
+            open_prefilled_manager_view(shortcut_event, slack_state).await
+        }
+        _ => bail!(
+            "Unknown message shortcut callback_id: {}",
+            shortcut_event.callback_id
+        ),
+    }
+}
+
+/// Open the manager in `CreateResponse` mode, listening to the message's channel for the message's text
+async fn open_prefilled_manager_view(
+    shortcut_event: SlackInteractionMessageActionEvent,
+    slack_state: &SlackStateWorkaround,
+) -> Result<()> {
+    let channel_id = shortcut_event
+        .channel
+        .ok_or_else(|| anyhow!("No channel in message shortcut"))?
+        .id;
+    let message_text = shortcut_event
+        .message
+        .and_then(|message| message.content.text)
+        .unwrap_or_default();
+
+    let mut meta = MetaForManagerView::new(
+        ManagerViewModes::CreateResponse,
+        shortcut_event.user.id.to_string(),
+    );
+    meta.response = Some(prefilled_response(channel_id.to_string(), message_text)?);
+
+    slack_state
+        .open_manager_modal_view(shortcut_event.trigger_id, &meta)
+        .await
+}
+
+fn prefilled_response(channel_id: String, message_text: String) -> Result<ReceptionistResponse> {
+    let mut response = ReceptionistResponse::default();
+    response.update_slack_channel(Some(channel_id))?;
+    response.update_message_condition_string(message_text.trim().to_string(), 0)?;
+    Ok(response)
+}
+
+async fn open_run_response_view(
+    shortcut_event: SlackInteractionMessageActionEvent,
+    slack_state: &SlackStateWorkaround,
+) -> Result<()> {
+    let channel_id = shortcut_event
+        .channel
+        .ok_or_else(|| anyhow!("No channel in message shortcut"))?
+        .id;
+    let message = shortcut_event
+        .message
+        .ok_or_else(|| anyhow!("No message in message shortcut"))?;
+
+    let meta = RunResponseMeta {
+        channel_id: channel_id.to_string(),
+        message_ts: message.origin.ts.to_string(),
+    };
+
+    let responses = get_responses_for_message_shortcut(slack_state, &channel_id).await?;
+
+    slack_state
+        .open_session()
+        .views_open(&SlackApiViewsOpenRequest::new(
+            shortcut_event.trigger_id,
+            run_response_view(&responses, &meta)?,
+        ))
+        .await
+        .map_err(|slack_err| anyhow!("Unable to open run response modal: {}", slack_err))?;
+
+    Ok(())
+}
+
+/// Responses that could have run for a message in this channel, including @mention Responses
+async fn get_responses_for_message_shortcut(
+    slack_state: &SlackStateWorkaround,
+    channel_id: &SlackChannelId,
+) -> Result<Vec<ReceptionistResponse>> {
+    let mut incoming_channel = IncomingChannel {
+        id: channel_id.to_string(),
+        name: None,
+        is_public: false,
+    };
+
+    // the shortcut payload doesn't include the channel type, so look up the name and visibility together
+    match slack_state
+        .open_session()
+        .conversations_info(&SlackApiConversationsInfoRequest::new(
+            channel_id.to_owned(),
+        ))
+        .await
+    {
+        Ok(info) => {
+            incoming_channel.name = info.channel.name;
+            incoming_channel.is_public = !info.channel.flags.is_private.unwrap_or(true);
+        }
+        Err(slack_err) => error!(
+            "Unable to get channel info for {}: {}",
+            channel_id, slack_err
+        ),
+    }
+
+    let mut responses: Vec<ReceptionistResponse> =
+        get_responses_for_listener(ReceptionistListener::SlackChannel {
+            channel_id: channel_id.to_string(),
+        })
+        .await?
+        .into_iter()
+        .filter(|r| r.listener.matches_channel(&incoming_channel))
+        .collect();
+
+    responses.extend(
+        get_responses_for_listener(ReceptionistListener::AppMention {
+            channel_id: Some(channel_id.to_string()),
+        })
+        .await?,
+    );
+
+    Ok(responses)
+}
+
+fn run_response_view(
+    responses: &[ReceptionistResponse],
+    meta: &RunResponseMeta,
+) -> Result<SlackView> {
+    let blocks = if responses.is_empty() {
+        slack_blocks![some_into(SlackSectionBlock::new().with_text(pt!(
+            "There are no Responses for messages in this channel yet, create one with the manager first."
+        )))]
+    } else {
+        let options: Vec<SlackBlockChoiceItem<SlackBlockPlainTextOnly>> = responses
+            .iter()
+            .map(|res| res.to_response_choice_item())
+            .collect();
+
+        slack_blocks![
+            some_into(
+                SlackInputBlock::new(
+                    pt!("Response to run"),
+                    SlackInputBlockElement::StaticSelect(
+                        SlackBlockStaticSelectElement::new(
+                            BlockSectionRouter::RunResponseSelection.to_action_id(None),
+                            pt!("Select a Response"),
+                        )
+                        .with_options(options)
+                    )
+                )
+                .without_optional()
+                .with_block_id(BlockSectionRouter::RunResponseSelection.to_block_id(None))
+            ),
+            some_into(SlackContextBlock::new(vec![md!(
+                "_The Response's actions run against the message without checking its conditions_"
+            )]))
+        ]
+    };
+
+    let modal = SlackModalView::new("Run a Response".into(), blocks)
+        .with_callback_id(RUN_RESPONSE_VIEW_CALLBACK_ID.into())
+        .opt_close(Some("Cancel".into()))
+        .with_private_metadata(to_string(meta)?);
+
+    Ok(SlackView::Modal(if responses.is_empty() {
+        modal
+    } else {
+        modal.opt_submit(Some("Run".into()))
+    }))
+}
+
+pub fn is_run_response_view(submission_event: &SlackInteractionViewSubmissionEvent) -> bool {
+    match &submission_event.view.view {
+        SlackView::Modal(modal) => modal.callback_id.as_ref().map_or(false, |callback_id| {
+            callback_id.as_ref() == RUN_RESPONSE_VIEW_CALLBACK_ID
+        }),
+        SlackView::Home(_) => false,
+    }
+}
+
+/// Run the selected Response's actions against the message the shortcut was used on
+pub async fn process_run_response_submission(
+    submission_event: SlackInteractionViewSubmissionEvent,
+    slack_state: &SlackStateWorkaround,
+) -> Result<()> {
+    let meta: RunResponseMeta = match &submission_event.view.view {
+        SlackView::Modal(modal) => from_str(
+            modal
+                .private_metadata
+                .as_deref()
+                .ok_or_else(|| anyhow!("no private_metadata field in view"))?,
+        )?,
+        SlackView::Home(_) => bail!("Home views are unimplemented"),
+    };
+
+    let state = submission_event
+        .view
+        .state_params
+        .state
+        .ok_or_else(|| anyhow!("no state in run response submission"))?;
+
+    let response_id = extract_action_block_states(state)?
+        .get(&BlockSectionRouter::RunResponseSelection.to_string_with_index(None))
+        .ok_or_else(|| anyhow!("no Response selected"))?
+        .get_value_from_static_select()?;
+
+    let rec_response = get_response_by_id(&response_id).await?;
+
+    let channel_id: SlackChannelId = meta.channel_id.into();
+    let message_ts: SlackTs = meta.message_ts.into();
+    let slack_session = slack_state.open_session();
+
+    let message = conversations_message(&slack_session, &channel_id, &message_ts)
+        .await
+        .map_err(|slack_err| anyhow!("Unable to fetch message {message_ts}: {slack_err}"))?
+        .ok_or_else(|| anyhow!("Message {message_ts} no longer exists in {channel_id}"))?;
+
+    let context = ActionContext {
+        channel_id,
+        message_ts: Some(message_ts),
+        sender: message
+            .user
+            .map(|user| user.to_string())
+            .unwrap_or_default(),
+        template_vars: HashMap::from([("message_text".to_string(), message.text)]),
+    };
+
+    run_response_actions(&slack_session, &rec_response, &context).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessageCondition, ReceptionistCondition};
+
+    #[test]
+    fn test_prefilled_response() {
+        let response = prefilled_response("C123".to_string(), " disk is full \n".to_string())
+            .expect("default response has a message condition");
+
+        assert_eq!(
+            response.listener,
+            ReceptionistListener::SlackChannel {
+                channel_id: "C123".to_string()
+            }
+        );
+        assert_eq!(
+            response.conditions,
+            vec![ReceptionistCondition::ForMessage(
+                MessageCondition::MatchPhrase("disk is full".to_string())
+            )]
+        );
+    }
+}
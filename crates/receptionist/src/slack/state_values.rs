@@ -19,11 +19,28 @@ pub enum ViewBlockStateType {
         selected_option: StaticSelectSelectedOptionValueState,
     },
     PlainTextInput {
-        value: String,
+        /// `None` when an optional input is left empty
+        value: Option<String>,
     },
     MultiUsersSelect {
         selected_users: Vec<String>,
     },
+    MultiConversationsSelect {
+        selected_conversations: Vec<SlackConversationId>,
+    },
+    UsersSelect {
+        selected_user: Option<String>,
+    },
+    Datepicker {
+        /// `YYYY-MM-DD`
+        selected_date: Option<String>,
+    },
+    MultiStaticSelect {
+        selected_options: Vec<StaticSelectSelectedOptionValueState>,
+    },
+    ExternalSelect {
+        selected_option: Option<StaticSelectSelectedOptionValueState>,
+    },
 }
 
 impl ViewBlockStateType {
@@ -46,7 +63,9 @@ impl ViewBlockStateType {
 
     pub fn get_plain_text_value(&self) -> Result<String> {
         match self {
-            ViewBlockStateType::PlainTextInput { value } => Ok(value.to_owned()),
+            ViewBlockStateType::PlainTextInput { value } => {
+                Ok(value.to_owned().unwrap_or_default())
+            }
             _ => bail!("block is not a plain text input"),
         }
     }
@@ -59,6 +78,54 @@ impl ViewBlockStateType {
             _ => bail!("block is not a multi_users_select input"),
         }
     }
+
+    pub fn get_users_select_value(&self) -> Result<Option<String>> {
+        match self {
+            ViewBlockStateType::UsersSelect { selected_user } => Ok(selected_user.to_owned()),
+            _ => bail!("block is not a users_select input"),
+        }
+    }
+
+    pub fn get_datepicker_value(&self) -> Result<Option<String>> {
+        match self {
+            ViewBlockStateType::Datepicker { selected_date } => Ok(selected_date.to_owned()),
+            _ => bail!("block is not a datepicker input"),
+        }
+    }
+
+    pub fn get_multi_static_select_values(&self) -> Result<Vec<String>> {
+        match self {
+            ViewBlockStateType::MultiStaticSelect { selected_options } => Ok(selected_options
+                .iter()
+                .map(|option| option.value.to_owned())
+                .collect()),
+            _ => bail!("block is not a multi_static_select input"),
+        }
+    }
+
+    /// Value and text of the picked option
+    pub fn get_external_select_option(&self) -> Result<Option<(String, String)>> {
+        match self {
+            ViewBlockStateType::ExternalSelect { selected_option } => {
+                Ok(selected_option.as_ref().map(|option| {
+                    (
+                        option.value.to_owned(),
+                        option.text["text"].as_str().unwrap_or_default().to_string(),
+                    )
+                }))
+            }
+            _ => bail!("block is not an external_select input"),
+        }
+    }
+
+    pub fn get_multi_conversations_select_value(&self) -> Result<Vec<SlackConversationId>> {
+        match self {
+            ViewBlockStateType::MultiConversationsSelect {
+                selected_conversations,
+            } => Ok(selected_conversations.to_owned()),
+            _ => bail!("block is not a multi_conversations_select input"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -90,4 +157,48 @@ mod tests {
         let _view_block_state_type: ViewBlockStateType = from_value(test_2).unwrap();
         let _custom_slack_view_state_wrapper: CustomSlackViewState = from_value(test).unwrap();
     }
+
+    #[test]
+    fn test_rotation_input_value_types() {
+        let date: ViewBlockStateType =
+            from_value(json!({"type": "datepicker", "selected_date": "2022-05-02"})).unwrap();
+        assert_eq!(
+            date.get_datepicker_value().unwrap(),
+            Some("2022-05-02".to_string())
+        );
+
+        let user: ViewBlockStateType =
+            from_value(json!({"type": "users_select", "selected_user": null})).unwrap();
+        assert_eq!(user.get_users_select_value().unwrap(), None);
+
+        let removed: ViewBlockStateType = from_value(json!({
+            "type": "multi_static_select",
+            "selected_options": [{"text": {"type": "plain_text", "text": "override"}, "value": "1"}]
+        }))
+        .unwrap();
+        assert_eq!(
+            removed.get_multi_static_select_values().unwrap(),
+            vec!["1".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_external_select_option() {
+        let target: ViewBlockStateType = from_value(json!({
+            "type": "external_select",
+            "selected_option": {"text": {"type": "plain_text", "text": "Schedule: Payments"}, "value": "schedule:PXYZ"}
+        }))
+        .unwrap();
+        assert_eq!(
+            target.get_external_select_option().unwrap(),
+            Some((
+                "schedule:PXYZ".to_string(),
+                "Schedule: Payments".to_string()
+            ))
+        );
+
+        let cleared: ViewBlockStateType =
+            from_value(json!({"type": "external_select", "selected_option": null})).unwrap();
+        assert_eq!(cleared.get_external_select_option().unwrap(), None);
+    }
 }
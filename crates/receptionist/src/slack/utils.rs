@@ -29,6 +29,28 @@ impl SlackStateWorkaround {
         self.slack_client.open_session(&self.bot_token)
     }
 
+    pub async fn open_manager_modal_view(
+        &self,
+        trigger_id: SlackTriggerId,
+        private_metadata: &MetaForManagerView,
+    ) -> Result<()> {
+        let view_open_request =
+            SlackApiViewsOpenRequest::new(trigger_id, new_manager_view(private_metadata).await);
+
+        self.open_session()
+            .views_open(&view_open_request)
+            .await
+            .map_err(|slack_err| {
+                anyhow!(
+                    "Error opening manager view with meta. Error: {} | Meta: {:?}",
+                    slack_err,
+                    &private_metadata
+                )
+            })?;
+
+        Ok(())
+    }
+
     pub async fn update_manager_modal_view(
         &self,
         view_id: SlackViewId,
@@ -98,12 +120,18 @@ impl MessageHelpers for SlackMessageEvent {
     }
 }
 
+/// Replies have a `thread_ts` pointing at their parent, while a parent's `thread_ts` is its own `ts`
+pub fn is_thread_reply(ts: &SlackTs, thread_ts: Option<&SlackTs>) -> bool {
+    thread_ts.map_or(false, |thread_ts| thread_ts != ts)
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "response_action", rename_all = "snake_case")]
 pub enum SlackResponseAction {
     /// HashMap<SlackBlockId -> error_message>
     Errors { errors: HashMap<String, String> },
-    // Update,
+    /// Replace the submitted view
+    Update { view: SlackView },
 }
 
 impl SlackResponseAction {
@@ -151,6 +179,34 @@ pub fn add_emoji_colons(emoji_name: &str) -> String {
     }
 }
 
+/// Remove the `@receptionist` mention from the text of an `app_mention` event so that
+/// conditions only run against what the user actually asked
+/// # Examples
+/// ```rust
+/// use receptionist::strip_app_mention;
+/// assert_eq!("who is oncall?", strip_app_mention("<@U0BOT> who is oncall?"));
+/// assert_eq!("hey who is oncall?", strip_app_mention("hey <@U0BOT> who is oncall?"));
+/// assert_eq!("no mention", strip_app_mention("no mention"));
+/// ```
+pub fn strip_app_mention(text: &str) -> String {
+    let mention_start = text.find("<@");
+    let mention_end =
+        mention_start.and_then(|start| text[start..].find('>').map(|end| start + end));
+
+    match (mention_start, mention_end) {
+        (Some(start), Some(end)) => {
+            let (before, after) = (text[..start].trim(), text[end + 1..].trim());
+            [before, after]
+                .iter()
+                .filter(|s| !s.is_empty())
+                .copied()
+                .collect::<Vec<&str>>()
+                .join(" ")
+        }
+        _ => text.trim().to_string(),
+    }
+}
+
 pub fn render_channel_id(channel_id: &str) -> String {
     format!("<#{channel_id}>")
 }
@@ -199,6 +255,27 @@ mod tests {
         assert_eq!("rust", remove_emoji_colons("rust"))
     }
 
+    #[test]
+    fn test_strip_app_mention() {
+        assert_eq!(
+            "who is oncall?",
+            strip_app_mention("<@U0BOT> who is oncall?")
+        );
+        assert_eq!(
+            "who is oncall?",
+            strip_app_mention("<@U0BOT>who is oncall?")
+        );
+        assert_eq!("", strip_app_mention("<@U0BOT>"));
+    }
+
+    #[test]
+    fn test_is_thread_reply() {
+        let ts: SlackTs = "1.2".into();
+        assert!(!is_thread_reply(&ts, None));
+        assert!(!is_thread_reply(&ts, Some(&"1.2".into())));
+        assert!(is_thread_reply(&ts, Some(&"1.1".into())));
+    }
+
     #[test]
     fn test_add_emoji_colons() {
         assert_eq!(":rust:", add_emoji_colons(":rust:"));
@@ -7,3 +7,16 @@ pub fn write_serde_struct_to_file<P: AsRef<Path>>(path: P, obj: impl Serialize)
     to_writer_pretty(&File::create(path).expect("unable to create file"), &obj)
         .expect("unable to write to file")
 }
+
+/// Percent-encode everything except unreserved characters, for URL fragments and path segments
+pub fn percent_encode(input: &str) -> String {
+    input
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,271 @@
+//! Inbound webhooks let external systems (CI, monitoring, ticketing) run a Response by posting JSON
+#[cfg(any(feature = "tempdb", feature = "dynamodb"))]
+use crate::database::{get_response_by_id, get_webhook_secret, set_webhook_secret};
+use crate::{
+    run_response_actions, verify_webhook_signature, ActionContext, ReceptionistListener,
+    SlackStateWorkaround, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+use anyhow::{anyhow, Result};
+use axum::{
+    body::Bytes,
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use nanoid::nanoid;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+pub async fn axum_handler_inbound_webhook(
+    Extension(slack_state): Extension<Arc<SlackStateWorkaround>>,
+    Path(response_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let header_str = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    let response = handle_inbound_webhook(
+        &*slack_state,
+        &response_id,
+        header_str(WEBHOOK_SIGNATURE_HEADER),
+        header_str(WEBHOOK_TIMESTAMP_HEADER),
+        &body,
+    )
+    .await;
+
+    (response.0, Json(response.1))
+}
+
+/// Verify a webhook request against its Response's secret, then run the Response if its conditions match the payload
+pub async fn handle_inbound_webhook(
+    slack_state: &SlackStateWorkaround,
+    response_id: &str,
+    signature: Option<&str>,
+    timestamp: Option<&str>,
+    body: &[u8],
+) -> (StatusCode, Value) {
+    let rec_response = match get_response_by_id(response_id).await {
+        Ok(rec_response) => rec_response,
+        Err(e) => {
+            warn!("No response found for webhook {}: {}", response_id, e);
+            return (
+                StatusCode::NOT_FOUND,
+                json!({"ok": false, "error": "not_found"}),
+            );
+        }
+    };
+
+    let channel_id = match &rec_response.listener {
+        ReceptionistListener::Webhook { channel_id } => channel_id,
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                json!({"ok": false, "error": "not_found"}),
+            )
+        }
+    };
+
+    let secret = match get_webhook_secret(response_id).await {
+        Ok(Some(secret)) => secret,
+        Ok(None) => {
+            warn!("No signing secret stored for webhook {}", response_id);
+            return (
+                StatusCode::UNAUTHORIZED,
+                json!({"ok": false, "error": "invalid_signature"}),
+            );
+        }
+        Err(e) => {
+            warn!(
+                "Unable to load signing secret of webhook {}: {}",
+                response_id, e
+            );
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({"ok": false, "error": "internal_error"}),
+            );
+        }
+    };
+
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or_default();
+
+    let verified = match (signature, timestamp) {
+        (Some(signature), Some(timestamp)) => {
+            verify_webhook_signature(&secret, signature, timestamp, body, now_secs)
+        }
+        _ => Err(anyhow!("Missing webhook signature headers")),
+    };
+
+    if let Err(e) = verified {
+        warn!("Rejected webhook for {}: {}", response_id, e);
+        return (
+            StatusCode::UNAUTHORIZED,
+            json!({"ok": false, "error": "invalid_signature"}),
+        );
+    }
+
+    let payload: Value = match serde_json::from_slice(body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                json!({"ok": false, "error": format!("invalid_json: {e}")}),
+            )
+        }
+    };
+
+    if !rec_response.check_for_json_match(&payload) {
+        return (StatusCode::OK, json!({"ok": true, "ran": false}));
+    }
+
+    let mut template_vars = flatten_json_vars(&payload);
+    template_vars.insert("channel_id".to_string(), channel_id.to_owned());
+
+    let context = ActionContext {
+        channel_id: channel_id.to_owned().into(),
+        message_ts: None,
+        sender: String::default(),
+        template_vars,
+    };
+
+    let slack_session = slack_state.open_session();
+    run_response_actions(&slack_session, &rec_response, &context).await;
+
+    (StatusCode::OK, json!({"ok": true, "ran": true}))
+}
+
+/// Store a new signing secret for a webhook Response, requests signed with the old one are rejected right away
+pub async fn rotate_webhook_secret(response_id: &str) -> Result<String> {
+    let secret = nanoid!(32);
+    set_webhook_secret(response_id, &secret).await?;
+    Ok(secret)
+}
+
+/// Template variables for every field of a payload, keyed by the same dot paths that conditions use
+fn flatten_json_vars(payload: &Value) -> HashMap<String, String> {
+    fn flatten(prefix: &str, value: &Value, vars: &mut HashMap<String, String>) {
+        let child_key = |key: &str| {
+            if prefix.is_empty() {
+                key.to_string()
+            } else {
+                format!("{prefix}.{key}")
+            }
+        };
+
+        match value {
+            Value::Object(fields) => {
+                for (key, field) in fields {
+                    flatten(&child_key(key), field, vars)
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    flatten(&child_key(&index.to_string()), item, vars)
+                }
+            }
+            Value::String(field) => {
+                vars.insert(prefix.to_string(), field.to_owned());
+            }
+            Value::Null => (),
+            other => {
+                vars.insert(prefix.to_string(), other.to_string());
+            }
+        }
+    }
+
+    let mut vars = HashMap::new();
+    flatten("", payload, &mut vars);
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten_json_vars() {
+        let vars = flatten_json_vars(&json!({
+            "alert": {"name": "disk full", "value": 97.5},
+            "hosts": ["web-1", "web-2"],
+            "resolved": null
+        }));
+
+        assert_eq!(vars["alert.name"], "disk full");
+        assert_eq!(vars["alert.value"], "97.5");
+        assert_eq!(vars["hosts.1"], "web-2");
+        assert!(!vars.contains_key("resolved"));
+    }
+
+    /// Requests are checked against the secret stored for the Response, a rotated secret replaces the old one
+    #[cfg(feature = "tempdb")]
+    #[tokio::test]
+    async fn test_webhook_is_verified_with_stored_secret() {
+        use crate::{create_response, sign_webhook_body, ReceptionistResponse};
+
+        async fn signed_status(
+            slack_state: &SlackStateWorkaround,
+            response_id: &str,
+            secret: &str,
+        ) -> StatusCode {
+            let body = br#"{"alert": "disk full"}"#;
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                .to_string();
+            let signature = sign_webhook_body(secret, &timestamp, body).unwrap();
+
+            handle_inbound_webhook(
+                slack_state,
+                response_id,
+                Some(&signature),
+                Some(&timestamp),
+                body,
+            )
+            .await
+            .0
+        }
+
+        let slack_state = SlackStateWorkaround::new_from_env();
+        let rec_response = ReceptionistResponse::new(
+            vec!["U0001".to_string()],
+            ReceptionistListener::Webhook {
+                channel_id: "C0001".to_string(),
+            },
+            vec![],
+            vec![],
+        );
+        let response_id = rec_response.id.to_owned();
+        create_response(rec_response).await.unwrap();
+
+        // no secret is stored until the Response is saved from the manager
+        assert_eq!(
+            signed_status(&slack_state, &response_id, "guess").await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let first_secret = rotate_webhook_secret(&response_id).await.unwrap();
+        assert_eq!(
+            signed_status(&slack_state, &response_id, &first_secret).await,
+            StatusCode::OK
+        );
+
+        let second_secret = rotate_webhook_secret(&response_id).await.unwrap();
+        assert_eq!(
+            signed_status(&slack_state, &response_id, &first_secret).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            signed_status(&slack_state, &response_id, &second_secret).await,
+            StatusCode::OK
+        );
+    }
+}
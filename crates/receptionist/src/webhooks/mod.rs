@@ -0,0 +1,3 @@
+pub mod inbound;
+pub mod outbound;
+pub mod signature;
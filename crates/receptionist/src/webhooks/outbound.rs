@@ -0,0 +1,242 @@
+//! Outgoing webhook requests for `MessageAction::Webhook`: signed, retried with backoff and time limited
+use crate::{
+    parse_webhook_headers, render_json_template, sign_webhook_body, HttpsClient, WebhookRequest,
+    WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+use anyhow::{anyhow, bail, Result};
+use hyper::{
+    header::{CONTENT_TYPE, USER_AGENT},
+    Body, Method, Request, StatusCode,
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+/// Limit for each attempt, covering the response body as well as the headers
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+const WEBHOOK_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Response text longer than this is cut off before it is posted to Slack
+const MAX_REPLY_LEN: usize = 3000;
+
+/// Send the webhook, retrying timeouts, connection errors, 429s and 5xx responses.
+/// Returns the text to reply with, if the service responded with any
+pub async fn send_webhook(
+    client: &HttpsClient,
+    webhook: &WebhookRequest,
+    template_vars: &HashMap<String, String>,
+) -> Result<Option<String>> {
+    let body = render_webhook_body(&webhook.body, template_vars)?;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+
+        // signed per attempt so retries don't fall outside the receiver's timestamp window
+        let request = build_webhook_request(webhook, &body, &unix_timestamp())?;
+
+        let retry_reason =
+            match tokio::time::timeout(WEBHOOK_TIMEOUT, client.request(request)).await {
+                Ok(Ok(response)) if response.status().is_success() => {
+                    let bytes = tokio::time::timeout(
+                        WEBHOOK_TIMEOUT,
+                        hyper::body::to_bytes(response.into_body()),
+                    )
+                    .await
+                    .map_err(|_| {
+                        anyhow!("timed out reading webhook response from {}", webhook.url)
+                    })??;
+
+                    return Ok(webhook_reply_text(&bytes));
+                }
+                Ok(Ok(response)) if is_retryable_status(response.status()) => {
+                    format!("status {}", response.status())
+                }
+                Ok(Ok(response)) => bail!(
+                    "webhook to {} failed with status {}",
+                    webhook.url,
+                    response.status()
+                ),
+                Ok(Err(http_err)) => http_err.to_string(),
+                Err(_) => "timed out".to_string(),
+            };
+
+        if attempt >= WEBHOOK_MAX_ATTEMPTS {
+            bail!(
+                "webhook to {} failed after {attempt} attempts: {retry_reason}",
+                webhook.url
+            );
+        }
+
+        warn!(
+            "webhook to {} failed ({retry_reason}), retrying attempt {attempt}",
+            webhook.url
+        );
+        tokio::time::sleep(backoff_delay(attempt)).await;
+    }
+}
+
+/// Fill in template variables inside the body's JSON strings, an empty body is sent as-is
+fn render_webhook_body(body: &str, template_vars: &HashMap<String, String>) -> Result<Vec<u8>> {
+    if body.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let body: Value =
+        serde_json::from_str(body).map_err(|e| anyhow!("webhook body is not valid JSON: {e}"))?;
+
+    Ok(serde_json::to_vec(&render_json_template(
+        body,
+        template_vars,
+    ))?)
+}
+
+fn build_webhook_request(
+    webhook: &WebhookRequest,
+    body: &[u8],
+    timestamp: &str,
+) -> Result<Request<Body>> {
+    let method = Method::from_bytes(webhook.method.to_string().as_bytes())?;
+
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(webhook.url.as_str())
+        .header(USER_AGENT, "Receptionist")
+        .header(CONTENT_TYPE, "application/json");
+
+    for (name, value) in parse_webhook_headers(&webhook.headers)? {
+        builder = builder.header(name, value);
+    }
+
+    if !webhook.signing_secret.is_empty() {
+        builder = builder.header(WEBHOOK_TIMESTAMP_HEADER, timestamp).header(
+            WEBHOOK_SIGNATURE_HEADER,
+            sign_webhook_body(&webhook.signing_secret, timestamp, body)?,
+        );
+    }
+
+    Ok(builder.body(Body::from(body.to_vec()))?)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Doubles after every failed attempt: 500ms, 1s, 2s..
+fn backoff_delay(attempt: u32) -> Duration {
+    WEBHOOK_INITIAL_BACKOFF * 2u32.saturating_pow(attempt.saturating_sub(1))
+}
+
+/// The `text` field of a JSON response, or the whole body if the response is plain text
+fn webhook_reply_text(body: &[u8]) -> Option<String> {
+    let body = String::from_utf8_lossy(body);
+    let body = body.trim();
+
+    let text = match serde_json::from_str::<Value>(body) {
+        Ok(json) => json.get("text")?.as_str()?.trim().to_string(),
+        Err(_) => body.to_string(),
+    };
+
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.chars().take(MAX_REPLY_LEN).collect())
+    }
+}
+
+fn unix_timestamp() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        new_https_client,
+        pagerduty::stub_server::{spawn_stub_server, StubResponse},
+        verify_webhook_signature, WebhookMethod,
+    };
+    use serde_json::json;
+
+    fn test_webhook() -> WebhookRequest {
+        WebhookRequest {
+            url: "https://tickets.example.com/api/tickets".to_string(),
+            method: WebhookMethod::Put,
+            headers: "Authorization: Bearer abc\n\nX-Team: payments".to_string(),
+            body: r#"{"summary": "from {{user_id}}"}"#.to_string(),
+            signing_secret: "secret".to_string(),
+            reply_in_thread: true,
+        }
+    }
+
+    #[test]
+    fn test_build_webhook_request_is_signed() {
+        let webhook = test_webhook();
+        let vars = HashMap::from([("user_id".to_string(), r#"U1 "quoted""#.to_string())]);
+        let body = render_webhook_body(&webhook.body, &vars).unwrap();
+        let request = build_webhook_request(&webhook, &body, "1650000000").unwrap();
+
+        assert_eq!(request.method(), Method::PUT);
+        assert_eq!(request.headers()["authorization"], "Bearer abc");
+        assert_eq!(request.headers()["x-team"], "payments");
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            serde_json::json!({"summary": r#"from U1 "quoted""#})
+        );
+
+        let signature = request.headers()[WEBHOOK_SIGNATURE_HEADER]
+            .to_str()
+            .unwrap();
+        assert!(
+            verify_webhook_signature("secret", signature, "1650000000", &body, 1650000001).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+
+        assert_eq!(backoff_delay(1), Duration::from_millis(500));
+        assert_eq!(backoff_delay(3), Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_send_webhook_retries_failed_attempt() {
+        let stub = spawn_stub_server(vec![
+            StubResponse::json(503, json!({})),
+            StubResponse::json(200, json!({"text": "Created TICKET-1"})),
+        ])
+        .await;
+        let webhook = WebhookRequest {
+            url: format!("{}/api/tickets", stub.base_url),
+            ..test_webhook()
+        };
+
+        let reply = send_webhook(&new_https_client(), &webhook, &HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(reply, Some("Created TICKET-1".to_string()));
+        assert_eq!(stub.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_webhook_reply_text() {
+        assert_eq!(
+            webhook_reply_text(br#"{"text": "Created TICKET-1", "id": 1}"#),
+            Some("Created TICKET-1".to_string())
+        );
+        assert_eq!(webhook_reply_text(b"ok\n"), Some("ok".to_string()));
+        assert_eq!(webhook_reply_text(br#"{"id": 1}"#), None);
+        assert_eq!(webhook_reply_text(b""), None);
+    }
+}
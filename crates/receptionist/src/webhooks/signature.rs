@@ -0,0 +1,92 @@
+//! Signatures for webhook requests, modeled on Slack's request signing:
+//! `v0=` + hex HMAC-SHA256 of `v0:<unix timestamp>:<body>`
+use anyhow::{anyhow, bail, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-receptionist-signature";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-receptionist-request-timestamp";
+
+const SIGNATURE_VERSION: &str = "v0";
+/// Requests signed longer ago than this are rejected to prevent replays
+const MAX_TIMESTAMP_AGE_SECS: i64 = 60 * 5;
+
+fn new_mac(secret: &str, timestamp: &str, body: &[u8]) -> Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| anyhow!("Invalid webhook secret: {}", e))?;
+    mac.update(format!("{SIGNATURE_VERSION}:{timestamp}:").as_bytes());
+    mac.update(body);
+    Ok(mac)
+}
+
+/// Signature header value for a request body
+/// # Examples
+/// ```rust
+/// use receptionist::{sign_webhook_body, verify_webhook_signature};
+///
+/// let signature = sign_webhook_body("secret", "1650000000", b"{}").unwrap();
+/// assert!(signature.starts_with("v0="));
+/// assert!(verify_webhook_signature("secret", &signature, "1650000000", b"{}", 1650000010).is_ok());
+/// ```
+pub fn sign_webhook_body(secret: &str, timestamp: &str, body: &[u8]) -> Result<String> {
+    let mac = new_mac(secret, timestamp, body)?;
+
+    Ok(format!(
+        "{SIGNATURE_VERSION}={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+/// Check a request's signature in constant time and that it was signed recently
+pub fn verify_webhook_signature(
+    secret: &str,
+    signature: &str,
+    timestamp: &str,
+    body: &[u8],
+    now_secs: i64,
+) -> Result<()> {
+    let signed_at: i64 = timestamp
+        .parse()
+        .map_err(|_| anyhow!("Invalid webhook timestamp: {}", timestamp))?;
+
+    if (now_secs - signed_at).abs() > MAX_TIMESTAMP_AGE_SECS {
+        bail!("Webhook timestamp is too old: {}", timestamp)
+    }
+
+    let signature_bytes = signature
+        .strip_prefix(&format!("{SIGNATURE_VERSION}="))
+        .and_then(|hex_signature| hex::decode(hex_signature).ok())
+        .ok_or_else(|| anyhow!("Malformed webhook signature"))?;
+
+    new_mac(secret, timestamp, body)?
+        .verify_slice(&signature_bytes)
+        .map_err(|_| anyhow!("Webhook signature does not match"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_webhook_signature() {
+        let body = br#"{"status":"firing"}"#;
+        let signature = sign_webhook_body("secret", "1650000000", body).unwrap();
+
+        assert!(
+            verify_webhook_signature("secret", &signature, "1650000000", body, 1650000000).is_ok()
+        );
+        // wrong secret
+        assert!(
+            verify_webhook_signature("other", &signature, "1650000000", body, 1650000000).is_err()
+        );
+        // tampered body
+        assert!(
+            verify_webhook_signature("secret", &signature, "1650000000", b"{}", 1650000000)
+                .is_err()
+        );
+        // replayed later
+        assert!(
+            verify_webhook_signature("secret", &signature, "1650000000", body, 1650001000).is_err()
+        );
+    }
+}
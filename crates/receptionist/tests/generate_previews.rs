@@ -1,6 +1,6 @@
 use receptionist::{
-    write_serde_struct_to_file, MessageAction, MessageCondition, ReceptionistAction,
-    ReceptionistCondition, ReceptionistResponse,
+    write_serde_struct_to_file, MessageAction, MessageCondition, OncallTarget,
+    OncallUnavailableAction, ReceptionistAction, ReceptionistCondition, ReceptionistResponse,
 };
 use serde::{Deserialize, Serialize};
 use slack_morphism::prelude::*;
@@ -53,8 +53,11 @@ fn gen_action_tag_oncall() {
 
     let action = rec_response.actions.first_mut().unwrap();
     *action = ReceptionistAction::ForMessage(MessageAction::MsgOncallInThread {
-        escalation_policy_id: "some_id".into(),
+        target: OncallTarget::PdEscalationPolicy("some_id".into()),
+        target_name: "Some Escalation Policy".into(),
         message: "some_message".into(),
+        mention_level: None,
+        unavailable_action: OncallUnavailableAction::default(),
     });
 
     write_preview_file("tag_oncall_in_thread", rec_response)
@@ -15,12 +15,14 @@ const CMD_PUSH_IMAGE_AWS: &str = "push-image-aws";
 const CMD_BUILD_LAMBDA_EVENTS: &str = "build-lambda-events";
 const CMD_BUILD_LAMBDA_INTERACTIONS: &str = "build-lambda-interactions";
 const CMD_BUILD_LAMBDA_COMMANDS: &str = "build-lambda-commands";
+const CMD_BUILD_LAMBDA_SCHEDULER: &str = "build-lambda-scheduler";
 const CMD_BUILD_LAMBDA_ALL: &str = "build-lambda-all";
 
 // lambda package names
 const LAMBDA_EVENTS: &str = "rec_lambda_events";
 const LAMBDA_INTERACTIONS: &str = "rec_lambda_interactions";
 const LAMBDA_COMMANDS: &str = "rec_lambda_commands";
+const LAMBDA_SCHEDULER: &str = "rec_lambda_scheduler";
 
 fn main() {
     if let Err(e) = try_main() {
@@ -37,14 +39,19 @@ fn try_main() -> Result<(), DynError> {
         Some(c) if c == CMD_BUILD_LAMBDA_INTERACTIONS => {
             prep_lambda_for_terraform(LAMBDA_INTERACTIONS)?
         }
+        Some(c) if c == CMD_BUILD_LAMBDA_SCHEDULER => prep_lambda_for_terraform(LAMBDA_SCHEDULER)?,
         Some(c) if c == CMD_BUILD_LAMBDA_ALL => {
-            let thread_handles =
-                [LAMBDA_EVENTS, LAMBDA_COMMANDS, LAMBDA_INTERACTIONS].map(|pkg_name| {
-                    thread::spawn(|| {
-                        prep_lambda_for_terraform(pkg_name)
-                            .expect("failed to prep lambda: {pkg_name}")
-                    })
-                });
+            let thread_handles = [
+                LAMBDA_EVENTS,
+                LAMBDA_COMMANDS,
+                LAMBDA_INTERACTIONS,
+                LAMBDA_SCHEDULER,
+            ]
+            .map(|pkg_name| {
+                thread::spawn(|| {
+                    prep_lambda_for_terraform(pkg_name).expect("failed to prep lambda: {pkg_name}")
+                })
+            });
 
             let mut errors = Vec::new();
             for t in thread_handles {
@@ -71,6 +78,7 @@ fn print_help() {
 {CMD_BUILD_LAMBDA_EVENTS}         cross-compile [events] lambda binary
 {CMD_BUILD_LAMBDA_COMMANDS}       cross-compile [commands] lambda binary
 {CMD_BUILD_LAMBDA_INTERACTIONS}   cross-compile [interactions] lambda binary
+{CMD_BUILD_LAMBDA_SCHEDULER}      cross-compile [scheduler] lambda binary
 {CMD_BUILD_LAMBDA_ALL}            cross-compile all lambdas
 "
     )
@@ -140,6 +140,11 @@ resource "aws_dynamodb_table" "receptionist_bot_table" {
     projection_type = "ALL"
   }
 
+  ttl {
+    attribute_name = "expires_at"
+    enabled        = true
+  }
+
   tags = {
     Name    = "receptionist_bot_table"
     project = "receptionist_bot"
@@ -99,6 +99,12 @@ data "archive_file" "interactions_lambda_zip" {
   type        = "zip"
 }
 
+data "archive_file" "scheduler_lambda_zip" {
+  source_file = "archives/rec_lambda_scheduler/bootstrap"
+  output_path = "archives/rec_lambda_scheduler/scheduler_lambda.zip"
+  type        = "zip"
+}
+
 resource "aws_iam_role" "lambda_execution_role" {
   managed_policy_arns = [
     "arn:aws:iam::aws:policy/service-role/AWSLambdaBasicExecutionRole",
@@ -185,6 +191,46 @@ resource "aws_lambda_function" "interactions_api" {
   }
 }
 
+resource "aws_lambda_function" "scheduler" {
+  function_name = "receptionist_scheduler"
+  architectures = ["arm64"]
+
+  source_code_hash = data.archive_file.scheduler_lambda_zip.output_base64sha256
+  filename         = data.archive_file.scheduler_lambda_zip.output_path
+
+  handler = "bootstrap"
+  runtime = "provided.al2"
+  timeout = 50
+
+  role = aws_iam_role.lambda_execution_role.arn
+
+  environment {
+    variables = {
+      SLACK_BOT_TOKEN = "${aws_ssm_parameter.slack_token_parameter.value}"
+      PAGERDUTY_TOKEN = "${aws_ssm_parameter.pagerduty_token_parameter.value}"
+    }
+  }
+}
+
+# ticks the scheduler lambda so it can run any scheduled responses that are due
+resource "aws_cloudwatch_event_rule" "scheduler_tick" {
+  name                = "receptionist_scheduler_tick"
+  description         = "Run due Receptionist scheduled responses"
+  schedule_expression = "rate(1 minute)"
+}
+
+resource "aws_cloudwatch_event_target" "scheduler_tick" {
+  rule = aws_cloudwatch_event_rule.scheduler_tick.name
+  arn  = aws_lambda_function.scheduler.arn
+}
+
+resource "aws_lambda_permission" "scheduler_tick" {
+  statement_id  = "allow_eventbridge_invoke"
+  function_name = aws_lambda_function.scheduler.function_name
+  action        = "lambda:InvokeFunction"
+  principal     = "events.amazonaws.com"
+  source_arn    = aws_cloudwatch_event_rule.scheduler_tick.arn
+}
 
 resource "aws_apigatewayv2_api" "api" {
   name          = "receptionist_api"
@@ -249,6 +295,12 @@ resource "aws_apigatewayv2_route" "interactions_api" {
   target    = "integrations/${aws_apigatewayv2_integration.interactions_api.id}"
 }
 
+resource "aws_apigatewayv2_route" "options_load_api" {
+  api_id    = aws_apigatewayv2_api.api.id
+  route_key = "POST /slack/options"
+  target    = "integrations/${aws_apigatewayv2_integration.interactions_api.id}"
+}
+
 resource "aws_apigatewayv2_stage" "api" {
   api_id      = aws_apigatewayv2_api.api.id
   name        = "prod"
@@ -265,6 +317,7 @@ resource "aws_apigatewayv2_deployment" "api" {
       jsonencode(aws_apigatewayv2_route.events_api),
       jsonencode(aws_apigatewayv2_integration.interactions_api),
       jsonencode(aws_apigatewayv2_route.interactions_api),
+      jsonencode(aws_apigatewayv2_route.options_load_api),
       jsonencode(aws_apigatewayv2_integration.commands_api),
       jsonencode(aws_apigatewayv2_route.commands_api),
       ]
@@ -332,6 +385,11 @@ resource "aws_dynamodb_table" "receptionist_bot_table" {
     projection_type = "ALL"
   }
 
+  ttl {
+    attribute_name = "expires_at"
+    enabled        = true
+  }
+
   tags = {
     Name    = "receptionist_bot_table"
     project = "receptionist_bot"